@@ -1,4 +1,4 @@
-use super::registry::{Command, CommandContext, CommandResult};
+use super::registry::{Command, CommandContext, CommandError, CommandResult};
 use crate::state::EditorState;
 
 pub fn switch_to_buffer(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
@@ -22,10 +22,144 @@ pub fn list_buffers(state: &mut EditorState, _ctx: &CommandContext) -> CommandRe
     Ok(())
 }
 
+pub fn read_only_mode(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    use crate::core::buffer::BufferMode;
+
+    let read_only = {
+        let buffer = match state.current_buffer_mut() {
+            Some(b) => b,
+            None => return Ok(()),
+        };
+        buffer.read_only = !buffer.read_only;
+        // A buffer stuck in ReadOnly mode would flip right back
+        if !buffer.read_only && buffer.mode == BufferMode::ReadOnly {
+            buffer.mode = BufferMode::Fundamental;
+        }
+        buffer.read_only
+    };
+
+    state.message = Some(if read_only {
+        "Buffer is now read-only".to_string()
+    } else {
+        "Buffer is now editable".to_string()
+    });
+    Ok(())
+}
+
+/// Flips the buffer between LF and CRLF endings; the change lands on
+/// disk at the next save, so the buffer is marked modified.
+pub fn toggle_line_ending(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    use crate::core::buffer::LineEnding;
+
+    let label = {
+        let buffer = match state.current_buffer_mut() {
+            Some(b) => b,
+            None => return Ok(()),
+        };
+        buffer.line_ending = match buffer.line_ending {
+            LineEnding::Lf => LineEnding::Crlf,
+            LineEnding::Crlf => LineEnding::Lf,
+        };
+        buffer.modified = true;
+        buffer.line_ending.label()
+    };
+
+    state.message = Some(format!("Line ending: {}", label));
+    Ok(())
+}
+
+pub fn narrow_to_region(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let region = state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region());
+    let Some((start, end)) = region else {
+        return Err(CommandError::NoMark);
+    };
+
+    if let Some(buffer) = state.current_buffer_mut() {
+        buffer.narrow = Some((start, end));
+    }
+    state.message = Some("Narrowed".to_string());
+    Ok(())
+}
+
+pub fn widen(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if let Some(buffer) = state.current_buffer_mut() {
+        buffer.narrow = None;
+    }
+    state.message = Some("Widened".to_string());
+    Ok(())
+}
+
 pub fn all_commands() -> Vec<Command> {
     vec![
         Command::new("switch-to-buffer", switch_to_buffer),
         Command::new("kill-buffer", kill_buffer),
         Command::new("list-buffers", list_buffers),
+        Command::new("read-only-mode", read_only_mode),
+        Command::new("toggle-line-ending", toggle_line_ending),
+        Command::new("narrow-to-region", narrow_to_region),
+        Command::new("widen", widen),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::buffer::BufferMode;
+    use crate::core::Buffer;
+
+    #[test]
+    fn test_read_only_mode_toggles_and_downgrades_mode() {
+        let mut state = EditorState::new();
+        let mut buffer = Buffer::from_string("test", "hello");
+        buffer.read_only = true;
+        buffer.mode = BufferMode::ReadOnly;
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+
+        let ctx = CommandContext::new();
+        read_only_mode(&mut state, &ctx).unwrap();
+
+        let buffer = state.current_buffer().unwrap();
+        assert!(!buffer.read_only);
+        assert_eq!(buffer.mode, BufferMode::Fundamental);
+        assert_eq!(state.message.as_deref(), Some("Buffer is now editable"));
+
+        read_only_mode(&mut state, &ctx).unwrap();
+        assert!(state.current_buffer().unwrap().read_only);
+        assert_eq!(state.message.as_deref(), Some("Buffer is now read-only"));
+    }
+
+    #[test]
+    fn test_narrow_to_region_and_widen() {
+        use crate::core::position::CharOffset;
+
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", "one two three");
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+
+        let ctx = CommandContext::new();
+        assert!(matches!(
+            narrow_to_region(&mut state, &ctx),
+            Err(CommandError::NoMark)
+        ));
+
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        cursors.primary.set_mark(CharOffset(4));
+        cursors.primary.set_position(CharOffset(7));
+
+        narrow_to_region(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().narrow,
+            Some((CharOffset(4), CharOffset(7)))
+        );
+
+        widen(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().narrow, None);
+    }
+}
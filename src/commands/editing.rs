@@ -1,9 +1,10 @@
 use crate::core::cursor::CursorId;
 use crate::core::mark::Mark;
 use crate::core::position::CharOffset;
+use crate::state::editor::PrefixPending;
 use crate::state::EditorState;
 
-use super::registry::{Command, CommandContext, CommandError, CommandResult};
+use super::registry::{Command, CommandContext, CommandError, CommandResult, PrefixArg};
 
 fn delete_region_if_active(state: &mut EditorState, buffer_id: crate::core::buffer::BufferId) -> bool {
     let regions: Vec<(CursorId, CharOffset, CharOffset)> = {
@@ -50,13 +51,98 @@ pub fn self_insert(state: &mut EditorState, c: char) -> CommandResult {
         return Err(CommandError::ReadOnly);
     }
 
+    if state.electric_pair {
+        if let Some(result) = electric_pair_insert(state, buffer_id, c) {
+            return result;
+        }
+    }
+
     let cursors = &mut state.windows.current_mut().unwrap().cursors;
     if let Some(buffer) = state.buffers.get_mut(buffer_id) {
-        buffer.insert_char(cursors, c);
+        if buffer.overwrite {
+            buffer.overwrite_char(cursors, c);
+        } else {
+            buffer.insert_char(cursors, c);
+        }
     }
     Ok(())
 }
 
+/// Pairs handled by electric-pair-mode; quotes close with themselves.
+const ELECTRIC_PAIRS: [(char, char); 5] = [
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('"', '"'),
+    ('\'', '\''),
+];
+
+/// The electric-pair hook for `self_insert`: inserts the matching close
+/// after an opening character, wraps an active region instead, and
+/// skips over a closing character already at point. Returns `None` when
+/// the keystroke should fall through to a plain insert.
+fn electric_pair_insert(
+    state: &mut EditorState,
+    buffer_id: crate::core::buffer::BufferId,
+    c: char,
+) -> Option<CommandResult> {
+    let close = ELECTRIC_PAIRS
+        .iter()
+        .find(|(open, _)| *open == c)
+        .map(|(_, close)| *close);
+    let is_close = ELECTRIC_PAIRS.iter().any(|(_, close)| *close == c);
+    if close.is_none() && !is_close {
+        return None;
+    }
+
+    // With an active region, an opening character wraps it instead.
+    let has_region = state
+        .current_window()
+        .map(|w| w.cursors.all_cursors().any(|cur| cur.region().is_some()))
+        .unwrap_or(false);
+    if has_region && close.is_some() {
+        wrap_region_with(state, &c.to_string());
+        return Some(Ok(()));
+    }
+
+    // Typing a close that is already next to point just moves over it.
+    if is_close {
+        let all_at_close = {
+            let window = state.windows.current()?;
+            let buffer = state.buffers.get(buffer_id)?;
+            window.cursors.all_cursors().all(|cur| {
+                cur.position.0 < buffer.text.len_chars() && buffer.text.char(cur.position.0) == c
+            })
+        };
+        if all_at_close {
+            for cursor in state.windows.current_mut()?.cursors.all_cursors_mut() {
+                let pos = cursor.position;
+                cursor.set_position(CharOffset(pos.0 + 1));
+            }
+            return Some(Ok(()));
+        }
+    }
+
+    // Insert both halves as a single undoable edit, point between them.
+    let close = close?;
+    let texts: Vec<(CursorId, String)> = state
+        .windows
+        .current()?
+        .cursors
+        .all_cursors()
+        .map(|cur| (cur.id, format!("{}{}", c, close)))
+        .collect();
+    let cursors = &mut state.windows.current_mut()?.cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.insert_at_cursors(cursors, texts);
+    }
+    for cursor in state.windows.current_mut()?.cursors.all_cursors_mut() {
+        let pos = cursor.position;
+        cursor.set_position(CharOffset(pos.0.saturating_sub(1)));
+    }
+    Some(Ok(()))
+}
+
 pub fn delete_char(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
     let count = ctx.repeat_count();
     let buffer_id = match state.windows.current() {
@@ -140,6 +226,54 @@ pub fn newline(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
     Ok(())
 }
 
+/// Inserts a newline plus a copy of the current line's leading
+/// whitespace, so code stays indented. When point sits inside the
+/// indentation only the part before point is copied.
+pub fn newline_and_indent(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    use crate::core::rope_ext::RopeExt;
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let read_only = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.read_only)
+        .unwrap_or(false);
+    if read_only {
+        return Err(CommandError::ReadOnly);
+    }
+
+    // Indentation prefix per cursor, clipped at point's column
+    let texts: Vec<(CursorId, String)> = {
+        let window = state.windows.current().unwrap();
+        let buffer = state.buffers.get(buffer_id).unwrap();
+        window
+            .cursors
+            .all_cursors()
+            .map(|cursor| {
+                let pos = buffer.text.char_to_position(cursor.position);
+                let indent: String = buffer
+                    .text
+                    .line(pos.line)
+                    .chars()
+                    .take(pos.column)
+                    .take_while(|c| matches!(c, ' ' | '\t'))
+                    .collect();
+                (cursor.id, format!("\n{}", indent))
+            })
+            .collect()
+    };
+
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.insert_at_cursors(cursors, texts);
+    }
+    Ok(())
+}
+
 pub fn open_line(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
     let count = ctx.repeat_count();
     let buffer_id = match state.windows.current() {
@@ -216,7 +350,12 @@ pub fn transpose_chars(state: &mut EditorState, _ctx: &CommandContext) -> Comman
     Ok(())
 }
 
-pub fn set_mark_command(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+pub fn set_mark_command(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    // C-u C-SPC retraces the mark ring instead of setting a new mark.
+    if !matches!(ctx.prefix_arg, PrefixArg::None) {
+        return pop_mark(state);
+    }
+
     let buffer_id = match state.windows.current() {
         Some(w) => w.buffer_id,
         None => return Ok(()),
@@ -231,12 +370,97 @@ pub fn set_mark_command(state: &mut EditorState, _ctx: &CommandContext) -> Comma
         if let Some(buffer) = state.buffers.get_mut(buffer_id) {
             buffer.mark_ring.push(Mark::new(primary_pos));
         }
+
+        // The global ring only records jumps into a different buffer
+        // than its latest entry.
+        if state.global_mark_ring.front().map(|&(id, _)| id) != Some(buffer_id) {
+            if state.global_mark_ring.len() >= 16 {
+                state.global_mark_ring.pop_back();
+            }
+            state
+                .global_mark_ring
+                .push_front((buffer_id, Mark::new(primary_pos)));
+        }
     }
 
     state.message = Some("Mark set".to_string());
     Ok(())
 }
 
+/// Moves point to the head of the buffer's mark ring and rotates it, so
+/// repeated `C-u C-SPC` walks the earlier mark positions.
+fn pop_mark(state: &mut EditorState) -> CommandResult {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let mark = match state.buffers.get_mut(buffer_id) {
+        Some(buffer) => match buffer.mark_ring.current().copied() {
+            Some(mark) => {
+                buffer.mark_ring.rotate();
+                mark
+            }
+            None => return Err(CommandError::Other("No mark to pop".to_string())),
+        },
+        None => return Ok(()),
+    };
+
+    if let Some(window) = state.windows.current_mut() {
+        window.cursors.primary.set_position(mark.position());
+        window.cursors.primary.deactivate_mark();
+    }
+    state.message = Some("Mark popped".to_string());
+    Ok(())
+}
+
+pub fn pop_global_mark(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let (buffer_id, mark) = match state.global_mark_ring.pop_front() {
+        Some(entry) => entry,
+        None => return Err(CommandError::Other("No global mark".to_string())),
+    };
+    state.global_mark_ring.push_back((buffer_id, mark));
+
+    let len = match state.buffers.get(buffer_id) {
+        Some(buffer) => buffer.text.len_chars(),
+        None => {
+            return Err(CommandError::Other(
+                "Global mark buffer no longer exists".to_string(),
+            ))
+        }
+    };
+
+    state.buffers.set_current(buffer_id);
+    state.windows.set_current_buffer(buffer_id);
+    if let Some(window) = state.windows.current_mut() {
+        window.cursors.remove_secondary_cursors();
+        // Positions in the global ring are not edit-adjusted; clamp.
+        window
+            .cursors
+            .primary
+            .set_position(CharOffset(mark.position().0.min(len)));
+        window.cursors.primary.deactivate_mark();
+    }
+    Ok(())
+}
+
+pub fn rectangle_mark_mode(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if let Some(window) = state.windows.current_mut() {
+        if window.rectangle_mark {
+            window.rectangle_mark = false;
+            state.message = Some("Rectangle mark mode disabled".to_string());
+        } else {
+            window.rectangle_mark = true;
+            if window.cursors.primary.region().is_none() {
+                let pos = window.cursors.primary.position;
+                window.cursors.primary.set_mark(pos);
+            }
+            state.message = Some("Rectangle mark mode".to_string());
+        }
+    }
+    Ok(())
+}
+
 pub fn exchange_point_and_mark(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
     if let Some(window) = state.windows.current_mut() {
         for cursor in window.cursors.all_cursors_mut() {
@@ -288,6 +512,23 @@ pub fn undo_command(state: &mut EditorState, _ctx: &CommandContext) -> CommandRe
     Ok(())
 }
 
+pub fn undo_only_command(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        if buffer.undo_only(cursors) {
+            state.message = Some("Undo!".to_string());
+        } else {
+            state.message = Some("No further undo information".to_string());
+        }
+    }
+    Ok(())
+}
+
 pub fn redo_command(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
     let buffer_id = match state.windows.current() {
         Some(w) => w.buffer_id,
@@ -305,10 +546,25 @@ pub fn redo_command(state: &mut EditorState, _ctx: &CommandContext) -> CommandRe
     Ok(())
 }
 
+pub fn universal_argument(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let pending = PrefixPending::universal();
+    state.message = Some(pending.echo());
+    state.prefix_pending = Some(pending);
+    Ok(())
+}
+
+pub fn negative_argument(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let pending = PrefixPending::negative();
+    state.message = Some(pending.echo());
+    state.prefix_pending = Some(pending);
+    Ok(())
+}
+
 pub fn keyboard_quit(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
     if let Some(window) = state.windows.current_mut() {
         window.cursors.deactivate_all_marks();
         window.cursors.remove_secondary_cursors();
+        window.rectangle_mark = false;
     }
 
     state.minibuffer.clear();
@@ -364,14 +620,9 @@ pub fn spawn_cursors_at_word_matches(
         return Ok(());
     }
 
-    let text = buffer.text.to_string();
-    let mut cursor_positions = Vec::new();
-
-    let mut search_start = 0;
-    while let Some(pos) = text[search_start..].find(&word) {
-        let abs_pos = search_start + pos;
-        let word_start = CharOffset(abs_pos);
-        let word_end = CharOffset(abs_pos + word.len());
+    let word_len = word.chars().count();
+    for word_start in crate::core::rope_ext::search_all(&buffer.text, &word) {
+        let word_end = CharOffset(word_start.0 + word_len);
 
         let is_current_cursor = if primary_cursor.mark.is_some() {
             let (curr_start, curr_end) = primary_cursor
@@ -383,23 +634,527 @@ pub fn spawn_cursors_at_word_matches(
         };
 
         if !is_current_cursor {
-            let cursor_pos = CharOffset(word_start.0 + cursor_offset.min(word.len()));
-            cursor_positions.push(cursor_pos);
+            window
+                .cursors
+                .add_cursor(CharOffset(word_start.0 + cursor_offset.min(word_len)));
         }
+    }
+
+    let count = window.cursors.count();
+    state.message = Some(format!("Spawned {} cursor(s)", count));
+
+    Ok(())
+}
+
+pub fn mark_next_like_this(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let buffer = match state.buffers.get(buffer_id) {
+        Some(b) => b,
+        None => return Ok(()),
+    };
 
-        search_start = abs_pos + 1;
+    let window = match state.windows.current_mut() {
+        Some(w) => w,
+        None => return Ok(()),
+    };
+
+    use crate::core::cursor::MarkNextState;
+    use crate::core::rope_ext::{find_word_boundary_backward, find_word_boundary_forward};
+
+    // The first invocation anchors on the region or word at point; later
+    // ones reuse the stored anchor so the point is free to move.
+    let (word, cursor_offset) = match &window.cursors.mark_next {
+        Some(mark_next) => (mark_next.word.clone(), mark_next.cursor_offset),
+        None => {
+            let primary = &window.cursors.primary;
+            if let Some((start, end)) = primary.region() {
+                (
+                    buffer.slice(start, end).to_string(),
+                    primary.position.0 - start.0,
+                )
+            } else {
+                let word_start = find_word_boundary_backward(&buffer.text, primary.position);
+                let word_end = find_word_boundary_forward(&buffer.text, primary.position);
+                if word_start == word_end {
+                    return Err(CommandError::Other("No word at point".to_string()));
+                }
+                (
+                    buffer.slice(word_start, word_end).to_string(),
+                    primary.position.0 - word_start.0,
+                )
+            }
+        }
+    };
+
+    if word.is_empty() {
+        return Err(CommandError::Other("No word at point".to_string()));
+    }
+
+    // Search below the last (highest-position) cursor's match.
+    let last_word_start = window
+        .cursors
+        .all_cursors()
+        .map(|c| c.position.0)
+        .max()
+        .unwrap_or(0)
+        .saturating_sub(cursor_offset);
+
+    let word_len = word.chars().count();
+    let mut added = None;
+    for word_start in crate::core::rope_ext::search_all(&buffer.text, &word) {
+        if word_start.0 <= last_word_start {
+            continue;
+        }
+        let cursor_pos = CharOffset(word_start.0 + cursor_offset.min(word_len));
+        if let Some(id) = window.cursors.add_cursor(cursor_pos) {
+            added = Some(id);
+            break;
+        }
     }
 
-    for pos in cursor_positions {
-        window.cursors.add_cursor(pos);
+    match added {
+        Some(id) => {
+            window
+                .cursors
+                .mark_next
+                .get_or_insert_with(|| {
+                    Box::new(MarkNextState {
+                        word,
+                        cursor_offset,
+                        added: Vec::new(),
+                    })
+                })
+                .added
+                .push(id);
+            state.message = Some(format!("Marked {} cursors", window.cursors.count()));
+        }
+        None => state.message = Some("No more matches below".to_string()),
     }
 
-    let count = window.cursors.count();
-    state.message = Some(format!("Spawned {} cursor(s)", count));
+    Ok(())
+}
+
+/// Spawns a cursor one line above or below the outermost cursor in that
+/// direction, at the primary's column (clamped to the line length).
+fn add_cursor_on_line(state: &mut EditorState, below: bool) -> CommandResult {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let buffer = match state.buffers.get(buffer_id) {
+        Some(b) => b,
+        None => return Ok(()),
+    };
+
+    let window = match state.windows.current_mut() {
+        Some(w) => w,
+        None => return Ok(()),
+    };
+
+    use crate::core::rope_ext::RopeExt;
+
+    let column = buffer
+        .text
+        .char_to_position(window.cursors.primary.position)
+        .column;
+
+    // Repeated calls extend from the cursor furthest along already.
+    let edge = if below {
+        window.cursors.all_cursors().map(|c| c.position).max()
+    } else {
+        window.cursors.all_cursors().map(|c| c.position).min()
+    }
+    .unwrap_or(CharOffset(0));
+
+    let line = buffer.text.char_to_position(edge).line;
+    let target_line = if below {
+        if line + 1 >= buffer.text.total_lines() {
+            state.message = Some("No line below".to_string());
+            return Ok(());
+        }
+        line + 1
+    } else {
+        match line.checked_sub(1) {
+            Some(l) => l,
+            None => {
+                state.message = Some("No line above".to_string());
+                return Ok(());
+            }
+        }
+    };
+
+    let line_len = buffer.text.line_len_chars(target_line);
+    let target = CharOffset(buffer.text.line_start_char(target_line).0 + column.min(line_len));
+    window.cursors.add_cursor(target);
+    state.message = Some(format!("{} cursors", window.cursors.count()));
+    Ok(())
+}
+
+pub fn add_cursor_on_next_line(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    add_cursor_on_line(state, true)
+}
+
+pub fn add_cursor_on_previous_line(
+    state: &mut EditorState,
+    _ctx: &CommandContext,
+) -> CommandResult {
+    add_cursor_on_line(state, false)
+}
+
+pub fn unmark_last_like_this(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let window = match state.windows.current_mut() {
+        Some(w) => w,
+        None => return Ok(()),
+    };
+
+    let popped = window
+        .cursors
+        .mark_next
+        .as_mut()
+        .and_then(|mark_next| mark_next.added.pop());
+
+    match popped {
+        Some(id) => {
+            window.cursors.remove_cursor(id);
+            state.message = Some(format!("Marked {} cursors", window.cursors.count()));
+        }
+        None => state.message = Some("No cursors to unmark".to_string()),
+    }
 
     Ok(())
 }
 
+/// The closing string inferred for known opening pairs; anything else
+/// closes with itself (useful for quotes).
+fn closing_for(open: &str) -> &str {
+    match open {
+        "(" => ")",
+        "[" => "]",
+        "{" => "}",
+        "<" => ">",
+        other => other,
+    }
+}
+
+pub fn wrap_region(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let has_region = state
+        .current_window()
+        .map(|w| w.cursors.all_cursors().any(|c| c.region().is_some()))
+        .unwrap_or(false);
+    if !has_region {
+        return Err(CommandError::NoMark);
+    }
+
+    state.start_minibuffer_prompt("Wrap region with: ", "wrap-region");
+    Ok(())
+}
+
+/// Minibuffer callback for `wrap-region`: wraps every active region in
+/// `open` and its inferred closing string, leaving the text selected.
+pub fn wrap_region_with(state: &mut EditorState, open: &str) {
+    if open.is_empty() {
+        return;
+    }
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return,
+    };
+    let regions: Vec<(CursorId, CharOffset, CharOffset)> = state
+        .windows
+        .current()
+        .map(|w| {
+            w.cursors
+                .all_cursors()
+                .filter_map(|cursor| cursor.region().map(|(start, end)| (cursor.id, start, end)))
+                .collect()
+        })
+        .unwrap_or_default();
+    if regions.is_empty() {
+        return;
+    }
+
+    let close = closing_for(open).to_string();
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.wrap_regions(cursors, regions, open, &close);
+    }
+}
+
+/// Toggles `prefix` on every line of `text`: stripped when all
+/// non-blank lines already carry it, otherwise inserted at each line's
+/// first non-whitespace column. Blank lines are left alone.
+fn toggle_comment_lines(text: &str, prefix: &str) -> String {
+    let had_final_newline = text.ends_with('\n');
+    let body = text.strip_suffix('\n').unwrap_or(text);
+    let bare_prefix = prefix.trim_end();
+
+    let all_commented = body
+        .split('\n')
+        .filter(|line| !line.trim().is_empty())
+        .all(|line| line.trim_start().starts_with(bare_prefix))
+        && body.split('\n').any(|line| !line.trim().is_empty());
+
+    let lines: Vec<String> = body
+        .split('\n')
+        .map(|line| {
+            if line.trim().is_empty() {
+                return line.to_string();
+            }
+            let indent_len = line.len() - line.trim_start().len();
+            let (indent, rest) = line.split_at(indent_len);
+            if all_commented {
+                let rest = rest
+                    .strip_prefix(prefix)
+                    .or_else(|| rest.strip_prefix(bare_prefix))
+                    .unwrap_or(rest);
+                format!("{}{}", indent, rest)
+            } else {
+                format!("{}{}{}", indent, prefix, rest)
+            }
+        })
+        .collect();
+
+    let mut result = lines.join("\n");
+    if had_final_newline {
+        result.push('\n');
+    }
+    result
+}
+
+/// Toggles the buffer's line-comment prefix on the current line, or on
+/// every line of the active region as one undo group. The region stays
+/// active afterward.
+pub fn comment_line(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    use crate::core::rope_ext::RopeExt;
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let read_only = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.read_only)
+        .unwrap_or(false);
+    if read_only {
+        return Err(CommandError::ReadOnly);
+    }
+
+    let (region_start, region_end, had_region) = {
+        let window = state.windows.current().unwrap();
+        match window.cursors.primary.region() {
+            Some((start, end)) => (start, end, true),
+            None => {
+                let point = window.cursors.primary.position;
+                (point, point, false)
+            }
+        }
+    };
+
+    let (start, end, new_text) = {
+        let buffer = state.buffers.get(buffer_id).unwrap();
+        let start_line = buffer.text.char_to_position(region_start).line;
+        let end_pos = buffer.text.char_to_position(region_end);
+        // A region ending at a line start does not include that line
+        let end_line = if had_region && end_pos.line > start_line && end_pos.column == 0 {
+            end_pos.line - 1
+        } else {
+            end_pos.line
+        };
+
+        let start = buffer.text.line_start_char(start_line);
+        let end = if end_line + 1 < buffer.text.total_lines() {
+            buffer.text.line_start_char(end_line + 1)
+        } else {
+            CharOffset(buffer.text.len_chars())
+        };
+        let old = buffer.slice(start, end);
+        (start, end, toggle_comment_lines(&old, &buffer.comment_prefix))
+    };
+
+    let new_len = new_text.chars().count();
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.replace_region(cursors, start, end, &new_text);
+    }
+
+    if had_region {
+        let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+        cursor.set_mark(start);
+        cursor.set_position(CharOffset(start.0 + new_len));
+    }
+    Ok(())
+}
+
+/// Re-wraps `text` (one paragraph) to `fill_column`, collapsing runs of
+/// whitespace to single spaces and reusing the first line's indentation
+/// on every line. Words longer than the fill column stay intact.
+fn fill_string(text: &str, fill_column: usize) -> String {
+    let had_final_newline = text.ends_with('\n');
+    let indent: String = text
+        .chars()
+        .take_while(|c| matches!(c, ' ' | '\t'))
+        .collect();
+    let indent_width = indent.chars().count();
+
+    let mut result = String::new();
+    let mut line_len = 0;
+    for word in text.split_whitespace() {
+        let word_len = word.chars().count();
+        if line_len == 0 {
+            result.push_str(&indent);
+            result.push_str(word);
+            line_len = indent_width + word_len;
+        } else if line_len + 1 + word_len <= fill_column {
+            result.push(' ');
+            result.push_str(word);
+            line_len += 1 + word_len;
+        } else {
+            result.push('\n');
+            result.push_str(&indent);
+            result.push_str(word);
+            line_len = indent_width + word_len;
+        }
+    }
+    if had_final_newline && !result.is_empty() {
+        result.push('\n');
+    }
+    result
+}
+
+/// Re-wraps the paragraph around point (bounded by blank lines) to
+/// `fill_column` as a single undo entry.
+pub fn fill_paragraph(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    use crate::core::rope_ext::RopeExt;
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let read_only = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.read_only)
+        .unwrap_or(false);
+    if read_only {
+        return Err(CommandError::ReadOnly);
+    }
+
+    let fill_column = state.fill_column;
+    let (start, end, new_text) = {
+        let window = state.windows.current().unwrap();
+        let buffer = state.buffers.get(buffer_id).unwrap();
+        let point_line = buffer
+            .text
+            .char_to_position(window.cursors.primary.position)
+            .line;
+        let blank = |line: usize| buffer.text.line(line).to_string().trim().is_empty();
+        if blank(point_line) {
+            return Ok(());
+        }
+
+        let mut first = point_line;
+        while first > 0 && !blank(first - 1) {
+            first -= 1;
+        }
+        let mut last = point_line;
+        while last + 1 < buffer.text.total_lines() && !blank(last + 1) {
+            last += 1;
+        }
+
+        let start = buffer.text.line_start_char(first);
+        let end = if last + 1 < buffer.text.total_lines() {
+            buffer.text.line_start_char(last + 1)
+        } else {
+            CharOffset(buffer.text.len_chars())
+        };
+        let old = buffer.slice(start, end);
+        (start, end, fill_string(&old, fill_column))
+    };
+
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.replace_region(cursors, start, end, &new_text);
+    }
+    Ok(())
+}
+
+pub fn electric_pair_mode(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.electric_pair = !state.electric_pair;
+    state.message = Some(if state.electric_pair {
+        "Electric pair mode enabled".to_string()
+    } else {
+        "Electric pair mode disabled".to_string()
+    });
+    Ok(())
+}
+
+/// C-q: reads the next key raw and inserts it literally — a control
+/// character, tab, or newline — bypassing key resolution entirely. With
+/// a numeric prefix, inserts the character with that octal code
+/// instead of reading a key.
+pub fn quoted_insert(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    if ctx.prefix_arg.is_set() {
+        let code = ctx.count();
+        return match u32::from_str_radix(&code.to_string(), 8)
+            .ok()
+            .and_then(char::from_u32)
+        {
+            Some(c) => insert_literal_char(state, c),
+            None => Err(CommandError::Other(format!("Invalid octal code: {}", code))),
+        };
+    }
+    state.start_char_capture("Quoted insert: ", "quoted-insert");
+    Ok(())
+}
+
+/// Inserts `c` directly, skipping electric pairs and overwrite-mode.
+pub fn insert_literal_char(state: &mut EditorState, c: char) -> CommandResult {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+    let read_only = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.read_only)
+        .unwrap_or(false);
+    if read_only {
+        return Err(CommandError::ReadOnly);
+    }
+
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.insert_char(cursors, c);
+    }
+    Ok(())
+}
+
+/// Toggles replacing the char at point on self-insert, per buffer.
+pub fn overwrite_mode(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let enabled = match state.buffers.current_mut() {
+        Some(buffer) => {
+            buffer.overwrite = !buffer.overwrite;
+            buffer.overwrite
+        }
+        None => return Ok(()),
+    };
+    state.message = Some(if enabled {
+        "Overwrite mode enabled".to_string()
+    } else {
+        "Overwrite mode disabled".to_string()
+    });
+    Ok(())
+}
+
 pub fn clear_multiple_cursors(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
     if let Some(window) = state.windows.current_mut() {
         window.cursors.remove_secondary_cursors();
@@ -418,19 +1173,35 @@ pub fn all_commands() -> Vec<Command> {
         Command::editing("delete-char", delete_char),
         Command::editing("delete-backward-char", delete_backward_char),
         Command::new("newline", newline),
+        Command::new("newline-and-indent", newline_and_indent),
         Command::new("open-line", open_line),
         Command::new("transpose-chars", transpose_chars),
         Command::mark("set-mark-command", set_mark_command),
+        Command::new("pop-global-mark", pop_global_mark),
         Command::mark("exchange-point-and-mark", exchange_point_and_mark),
         Command::mark("mark-whole-buffer", mark_whole_buffer),
         Command::new("undo", undo_command),
+        Command::new("undo-only", undo_only_command),
         Command::new("redo", redo_command),
         Command::new("keyboard-quit", keyboard_quit),
+        Command::mark("rectangle-mark-mode", rectangle_mark_mode),
+        Command::mark("universal-argument", universal_argument),
+        Command::mark("negative-argument", negative_argument),
         Command::new(
             "spawn-cursors-at-word-matches",
             spawn_cursors_at_word_matches,
         ),
         Command::new("clear-multiple-cursors", clear_multiple_cursors),
+        Command::mark("mark-next-like-this", mark_next_like_this),
+        Command::new("unmark-last-like-this", unmark_last_like_this),
+        Command::new("add-cursor-on-next-line", add_cursor_on_next_line),
+        Command::new("add-cursor-on-previous-line", add_cursor_on_previous_line),
+        Command::mark("wrap-region", wrap_region),
+        Command::new("electric-pair-mode", electric_pair_mode),
+        Command::new("overwrite-mode", overwrite_mode),
+        Command::editing("quoted-insert", quoted_insert),
+        Command::new("comment-line", comment_line),
+        Command::new("fill-paragraph", fill_paragraph),
     ]
 }
 
@@ -448,6 +1219,49 @@ mod tests {
         state
     }
 
+    #[test]
+    fn test_wrap_region_infers_closing_paren() {
+        let mut state = make_state("abc def\n");
+        {
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.primary.set_mark(CharOffset(0));
+            cursors.primary.position = CharOffset(3);
+        }
+
+        wrap_region_with(&mut state, "(");
+
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "(abc) def\n"
+        );
+        // The wrapped text stays selected
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.region(),
+            Some((CharOffset(1), CharOffset(4)))
+        );
+    }
+
+    #[test]
+    fn test_wrap_region_quote_closes_with_itself() {
+        let mut state = make_state("abc def\n");
+        {
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.primary.set_mark(CharOffset(4));
+            cursors.primary.position = CharOffset(7);
+        }
+
+        wrap_region_with(&mut state, "\"");
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "abc \"def\"\n"
+        );
+
+        // Both insertions undo as one group
+        let ctx = CommandContext::new();
+        undo_command(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "abc def\n");
+    }
+
     #[test]
     fn test_delete_char() {
         let mut state = make_state("hello");
@@ -473,6 +1287,232 @@ mod tests {
         assert_eq!(state.current_buffer().unwrap().text.to_string(), "hello\n");
     }
 
+    #[test]
+    fn test_fill_paragraph_rewraps_with_indent() {
+        let mut state = make_state("  one two\n  three   four five\n\nnext\n");
+        state.fill_column = 12;
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(4);
+        let ctx = CommandContext::new();
+
+        fill_paragraph(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "  one two\n  three four\n  five\n\nnext\n"
+        );
+    }
+
+    #[test]
+    fn test_fill_string_keeps_overlong_words_intact() {
+        assert_eq!(
+            fill_string("a veryveryverylongword b\n", 8),
+            "a\nveryveryverylongword\nb\n"
+        );
+    }
+
+    #[test]
+    fn test_comment_line_toggles_at_indent_column() {
+        let mut state = make_state("    foo\n");
+        let ctx = CommandContext::new();
+
+        comment_line(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "    // foo\n"
+        );
+
+        comment_line(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "    foo\n");
+    }
+
+    #[test]
+    fn test_comment_line_region_is_one_undo_group() {
+        let mut state = make_state("foo\nbar\nbaz\n");
+        {
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.primary.set_mark(CharOffset(0));
+            // Ends at the start of the third line, which is excluded
+            cursors.primary.position = CharOffset(8);
+        }
+        let ctx = CommandContext::new();
+
+        comment_line(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "// foo\n// bar\nbaz\n"
+        );
+        // Region stays active over the commented lines
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.region(),
+            Some((CharOffset(0), CharOffset(14)))
+        );
+
+        undo_command(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "foo\nbar\nbaz\n"
+        );
+    }
+
+    #[test]
+    fn test_electric_pair_inserts_pair_and_undoes_as_one() {
+        let mut state = make_state("");
+        state.electric_pair = true;
+
+        self_insert(&mut state, '(').unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "()");
+        // Point sits between the pair
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(1)
+        );
+
+        let ctx = CommandContext::new();
+        undo_command(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "");
+    }
+
+    #[test]
+    fn test_electric_pair_skips_over_close() {
+        let mut state = make_state("()");
+        state.electric_pair = true;
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(1);
+
+        self_insert(&mut state, ')').unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "()");
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(2)
+        );
+    }
+
+    #[test]
+    fn test_electric_pair_wraps_active_region() {
+        let mut state = make_state("abc");
+        state.electric_pair = true;
+        {
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.primary.set_mark(CharOffset(0));
+            cursors.primary.position = CharOffset(3);
+        }
+
+        self_insert(&mut state, '[').unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "[abc]");
+    }
+
+    #[test]
+    fn test_newline_and_indent_copies_indentation() {
+        let mut state = make_state("    foo");
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(7);
+        let ctx = CommandContext::new();
+
+        newline_and_indent(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "    foo\n    "
+        );
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(12)
+        );
+    }
+
+    #[test]
+    fn test_newline_and_indent_inside_indentation() {
+        // Point sits after two of the four indent spaces; only those two
+        // are copied so the indentation is not duplicated.
+        let mut state = make_state("    foo");
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(2);
+        let ctx = CommandContext::new();
+
+        newline_and_indent(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "  \n    foo"
+        );
+    }
+
+    #[test]
+    fn test_quoted_insert_takes_the_next_key_literally() {
+        let mut state = make_state("");
+
+        // C-j would normally run newline-and-indent; TAB would indent
+        state.dispatch("C-q C-j").unwrap();
+        state.dispatch("C-q <tab>").unwrap();
+
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "\n\t");
+    }
+
+    #[test]
+    fn test_quoted_insert_reads_octal_from_the_prefix() {
+        let mut state = make_state("");
+        let ctx = CommandContext::with_prefix(PrefixArg::Universal(101));
+
+        quoted_insert(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "A");
+
+        let ctx = CommandContext::with_prefix(PrefixArg::Universal(9));
+        assert!(quoted_insert(&mut state, &ctx).is_err());
+    }
+
+    #[test]
+    fn test_overwrite_mode_replaces_at_point() {
+        let mut state = make_state("abc");
+        let ctx = CommandContext::new();
+        overwrite_mode(&mut state, &ctx).unwrap();
+
+        self_insert(&mut state, 'X').unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "Xbc");
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(1)
+        );
+
+        // One undo reverts the delete-and-insert as a unit
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        let buffer = state.buffers.current_mut().unwrap();
+        assert!(buffer.undo(cursors));
+        assert_eq!(buffer.text.to_string(), "abc");
+    }
+
+    #[test]
+    fn test_overwrite_mode_inserts_at_end_of_line() {
+        let mut state = make_state("ab\ncd");
+        let ctx = CommandContext::new();
+        overwrite_mode(&mut state, &ctx).unwrap();
+        state.windows.current_mut().unwrap().cursors.primary.position = CharOffset(2);
+
+        self_insert(&mut state, 'X').unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "abX\ncd");
+
+        // Backspace still deletes normally
+        delete_backward_char(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "ab\ncd");
+    }
+
     #[test]
     fn test_transpose_chars() {
         let mut state = make_state("ab");
@@ -488,4 +1528,187 @@ mod tests {
         transpose_chars(&mut state, &ctx).unwrap();
         assert_eq!(state.current_buffer().unwrap().text.to_string(), "ba");
     }
+
+    #[test]
+    fn test_mark_next_like_this_adds_one_cursor_per_call() {
+        let mut state = make_state("foo bar\nfoo baz\nfoo qux\n");
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(1);
+        let ctx = CommandContext::new();
+
+        mark_next_like_this(&mut state, &ctx).unwrap();
+        {
+            let cursors = &state.windows.current().unwrap().cursors;
+            assert_eq!(cursors.count(), 2);
+            // New cursors inherit the point's offset inside the word.
+            let positions: Vec<_> = cursors.all_cursors().map(|c| c.position.0).collect();
+            assert_eq!(positions, vec![1, 9]);
+        }
+        assert_eq!(state.message, Some("Marked 2 cursors".to_string()));
+
+        mark_next_like_this(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.current().unwrap().cursors.count(), 3);
+
+        // All occurrences are marked; a further call reports exhaustion.
+        mark_next_like_this(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.current().unwrap().cursors.count(), 3);
+        assert_eq!(state.message, Some("No more matches below".to_string()));
+    }
+
+    #[test]
+    fn test_unmark_last_like_this_removes_the_newest_cursor() {
+        let mut state = make_state("foo\nfoo\nfoo\n");
+        let ctx = CommandContext::new();
+
+        mark_next_like_this(&mut state, &ctx).unwrap();
+        mark_next_like_this(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.current().unwrap().cursors.count(), 3);
+
+        unmark_last_like_this(&mut state, &ctx).unwrap();
+        {
+            let cursors = &state.windows.current().unwrap().cursors;
+            let positions: Vec<_> = cursors.all_cursors().map(|c| c.position.0).collect();
+            assert_eq!(positions, vec![0, 4]);
+        }
+
+        unmark_last_like_this(&mut state, &ctx).unwrap();
+        unmark_last_like_this(&mut state, &ctx).unwrap();
+        assert_eq!(state.message, Some("No cursors to unmark".to_string()));
+    }
+
+    #[test]
+    fn test_add_cursor_on_next_line_clamps_to_line_length() {
+        let mut state = make_state("abcdef\nab\nabcdef\n");
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(4);
+        let ctx = CommandContext::new();
+
+        add_cursor_on_next_line(&mut state, &ctx).unwrap();
+        add_cursor_on_next_line(&mut state, &ctx).unwrap();
+        let cursors = &state.windows.current().unwrap().cursors;
+        let positions: Vec<_> = cursors.all_cursors().map(|c| c.position.0).collect();
+        // The short middle line ends before column 4, so its cursor sits
+        // at end of line; the third line gets the full column back.
+        assert_eq!(positions, vec![4, 9, 14]);
+    }
+
+    #[test]
+    fn test_add_cursor_on_previous_line_stops_at_first_line() {
+        let mut state = make_state("one\ntwo\n");
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(5);
+        let ctx = CommandContext::new();
+
+        add_cursor_on_previous_line(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.current().unwrap().cursors.count(), 2);
+
+        add_cursor_on_previous_line(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.current().unwrap().cursors.count(), 2);
+        assert_eq!(state.message, Some("No line above".to_string()));
+    }
+
+    #[test]
+    fn test_pop_mark_retraces_the_mark_ring() {
+        let mut state = make_state("abcdefgh");
+        let ctx = CommandContext::new();
+
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(2);
+        set_mark_command(&mut state, &ctx).unwrap();
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(5);
+        set_mark_command(&mut state, &ctx).unwrap();
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(7);
+
+        let pop_ctx = CommandContext::with_prefix(PrefixArg::Universal(4));
+        set_mark_command(&mut state, &pop_ctx).unwrap();
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(5)
+        );
+
+        set_mark_command(&mut state, &pop_ctx).unwrap();
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(2)
+        );
+    }
+
+    #[test]
+    fn test_pop_global_mark_jumps_across_buffers() {
+        let mut state = make_state("first buffer");
+        let first = state.windows.current().unwrap().buffer_id;
+        let ctx = CommandContext::new();
+
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(3);
+        set_mark_command(&mut state, &ctx).unwrap();
+
+        let second = state.buffers.add(Buffer::from_string("other", "second buffer"));
+        state.buffers.set_current(second);
+        state.windows.set_current_buffer(second);
+        set_mark_command(&mut state, &ctx).unwrap();
+
+        // First pop lands on the ring head (this buffer), the second
+        // retraces to the mark in the first buffer.
+        pop_global_mark(&mut state, &ctx).unwrap();
+        pop_global_mark(&mut state, &ctx).unwrap();
+        let window = state.windows.current().unwrap();
+        assert_eq!(window.buffer_id, first);
+        assert_eq!(window.cursors.primary.position, CharOffset(3));
+    }
+
+    #[test]
+    fn test_mark_next_like_this_uses_active_region() {
+        let mut state = make_state("ab abc ab\n");
+        {
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.primary.set_mark(CharOffset(0));
+            cursors.primary.position = CharOffset(2);
+        }
+        let ctx = CommandContext::new();
+
+        mark_next_like_this(&mut state, &ctx).unwrap();
+        let cursors = &state.windows.current().unwrap().cursors;
+        let positions: Vec<_> = cursors.all_cursors().map(|c| c.position.0).collect();
+        // The region "ab" matches inside "abc" first; substring search,
+        // like spawn-cursors-at-word-matches.
+        assert_eq!(positions, vec![2, 5]);
+    }
 }
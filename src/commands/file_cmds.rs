@@ -31,6 +31,184 @@ pub fn write_file(state: &mut EditorState, _ctx: &CommandContext) -> CommandResu
     Ok(())
 }
 
+pub fn insert_file(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Insert file: ", "insert-file-complete");
+    Ok(())
+}
+
+/// Splices the contents of `path` into the current buffer at point as
+/// one undoable edit, leaving point after the inserted text and the
+/// mark before it. The buffer keeps visiting its own file.
+pub fn insert_file_at_point(state: &mut EditorState, path: &str) {
+    use crate::core::mark::Mark;
+    use crate::core::position::CharOffset;
+
+    let buffer_id = match state.windows.current() {
+        Some(window) => window.buffer_id,
+        None => return,
+    };
+    if state.buffers.get(buffer_id).map(|b| b.read_only) != Some(false) {
+        state.message = Some("Buffer is read-only".to_string());
+        return;
+    }
+
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            state.message = Some(format!("Error reading {}: {}", path, e));
+            return;
+        }
+    };
+    let char_count = contents.chars().count();
+
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    let buffer = state.buffers.get_mut(buffer_id).unwrap();
+    buffer.insert_string(cursors, &contents);
+
+    // Leave an inactive mark at the start of each cursor's insertion
+    for cursor in cursors.all_cursors_mut() {
+        cursor.mark = Some(CharOffset(cursor.position.0.saturating_sub(char_count)));
+        cursor.mark_active = false;
+    }
+    let primary_start = CharOffset(cursors.primary.position.0.saturating_sub(char_count));
+    buffer.mark_ring.push(Mark::new(primary_start));
+
+    state.message = Some(format!("Inserted {} ({} characters)", path, char_count));
+}
+
+/// Prompts for a path and writes the active region's text there,
+/// leaving the buffer's file association and modified flag alone.
+pub fn write_region(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let has_region = state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region())
+        .is_some();
+    if !has_region {
+        return Err(CommandError::NoMark);
+    }
+    state.start_minibuffer_prompt("Write region to file: ", "write-region-complete");
+    Ok(())
+}
+
+/// The minibuffer callback for `write-region`: saves the region to
+/// `path`.
+pub fn write_region_to(state: &mut EditorState, path: &str) {
+    let region = state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region());
+    let text = match (region, state.current_buffer()) {
+        (Some((start, end)), Some(buffer)) => buffer.slice(start, end),
+        _ => {
+            state.message = Some("No mark set".to_string());
+            return;
+        }
+    };
+
+    match std::fs::write(path, &text) {
+        Ok(()) => {
+            state.message = Some(format!("Wrote region to {}", path));
+        }
+        Err(e) => {
+            state.message = Some(format!("Error writing {}: {}", path, e));
+        }
+    }
+}
+
+/// Prompts for a coding system and re-reads the visited file with it,
+/// overriding the encoding detected on open.
+pub fn revert_buffer_with_coding_system(
+    state: &mut EditorState,
+    _ctx: &CommandContext,
+) -> CommandResult {
+    let visiting = state
+        .current_buffer()
+        .map(|b| b.file_path.is_some())
+        .unwrap_or(false);
+    if !visiting {
+        return Err(CommandError::Other(
+            "Buffer is not visiting a file".to_string(),
+        ));
+    }
+    state.start_minibuffer_prompt("Coding system: ", "revert-coding-complete");
+    Ok(())
+}
+
+/// The minibuffer callback for `revert-buffer-with-coding-system`:
+/// re-reads the file as `name` and resets cursors to the start.
+pub fn revert_buffer_as(state: &mut EditorState, name: &str) {
+    use crate::core::position::CharOffset;
+    use crate::core::FileEncoding;
+
+    let Some(encoding) = FileEncoding::from_name(name) else {
+        state.message = Some(format!("Unknown coding system: {}", name));
+        return;
+    };
+
+    let buffer_id = match state.windows.current() {
+        Some(window) => window.buffer_id,
+        None => return,
+    };
+    let buffer = state.buffers.get_mut(buffer_id).unwrap();
+    match buffer.revert_with_encoding(encoding) {
+        Ok(()) => {
+            let name = buffer.name.clone();
+            let label = buffer.encoding.label();
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.remove_secondary_cursors();
+            cursors.primary.set_position(CharOffset(0));
+            cursors.primary.clear_mark();
+            state.message = Some(format!("Reverted {} as {}", name, label));
+        }
+        Err(e) => {
+            state.message = Some(format!("Error reverting: {}", e));
+        }
+    }
+}
+
+/// TAB completion for the coding-system prompt.
+pub fn complete_coding_system(_state: &EditorState, input: &str) -> Vec<String> {
+    crate::core::buffer::CODING_SYSTEMS
+        .iter()
+        .filter(|name| name.starts_with(input))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+pub fn recover_file(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    use crate::core::position::CharOffset;
+
+    let buffer_id = match state.windows.current() {
+        Some(window) => window.buffer_id,
+        None => return Ok(()),
+    };
+
+    let (auto_path, len) = {
+        let buffer = state
+            .buffers
+            .get(buffer_id)
+            .ok_or_else(|| CommandError::Other("No buffer".to_string()))?;
+        let auto_path = buffer
+            .auto_save_path()
+            .filter(|p| p.exists())
+            .ok_or_else(|| {
+                CommandError::Other(format!("No auto-save file for {}", buffer.name))
+            })?;
+        (auto_path, buffer.len_chars())
+    };
+
+    let content = std::fs::read_to_string(&auto_path)
+        .map_err(|e| CommandError::Other(format!("Error reading auto-save file: {}", e)))?;
+
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    let buffer = state.buffers.get_mut(buffer_id).unwrap();
+    buffer.replace_region(cursors, CharOffset(0), CharOffset(len), &content);
+
+    state.message = Some(format!("Recovered from {}", auto_path.display()));
+    Ok(())
+}
+
 pub fn exit(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
     let has_modified = state.buffers.iter().any(|b| b.modified);
     if has_modified {
@@ -47,6 +225,107 @@ pub fn all_commands() -> Vec<Command> {
         Command::new("find-file", find_file),
         Command::new("save-buffer", save_buffer),
         Command::new("write-file", write_file),
+        Command::new("insert-file", insert_file),
+        Command::mark("write-region", write_region),
+        Command::new(
+            "revert-buffer-with-coding-system",
+            revert_buffer_with_coding_system,
+        ),
+        Command::new("recover-file", recover_file),
         Command::new("exit", exit),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::position::CharOffset;
+    use crate::core::Buffer;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    #[test]
+    fn test_insert_file_at_point_splices_and_marks() {
+        let path = std::env::temp_dir().join(format!("enacs-insert-{}.txt", std::process::id()));
+        std::fs::write(&path, "XYZ").unwrap();
+
+        let mut state = make_state("hello");
+        state.windows.current_mut().unwrap().cursors.primary.position = CharOffset(2);
+        insert_file_at_point(&mut state, path.to_str().unwrap());
+
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "heXYZllo");
+        let cursor = &state.windows.current().unwrap().cursors.primary;
+        assert_eq!(cursor.position, CharOffset(5));
+        // The mark sits before the insertion, inactive
+        assert_eq!(cursor.mark, Some(CharOffset(2)));
+        assert!(!cursor.mark_active);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_region_requires_a_region() {
+        let mut state = make_state("hello world");
+        let ctx = CommandContext::new();
+        assert!(matches!(
+            write_region(&mut state, &ctx),
+            Err(CommandError::NoMark)
+        ));
+    }
+
+    #[test]
+    fn test_write_region_to_saves_region_only() {
+        let path = std::env::temp_dir().join(format!("enacs-region-{}.txt", std::process::id()));
+
+        let mut state = make_state("hello world");
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        cursors.primary.set_mark(CharOffset(6));
+        cursors.primary.set_position(CharOffset(11));
+        write_region_to(&mut state, path.to_str().unwrap());
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "world");
+        assert!(!state.current_buffer().unwrap().modified);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_revert_with_coding_system_needs_a_file() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+        assert!(matches!(
+            revert_buffer_with_coding_system(&mut state, &ctx),
+            Err(CommandError::Other(_))
+        ));
+    }
+
+    #[test]
+    fn test_revert_buffer_as_rejects_unknown_coding_system() {
+        let mut state = make_state("hello");
+        revert_buffer_as(&mut state, "ebcdic");
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Unknown coding system: ebcdic")
+        );
+    }
+
+    #[test]
+    fn test_insert_file_reports_unreadable_file() {
+        let mut state = make_state("hello");
+        insert_file_at_point(&mut state, "/no/such/enacs-file");
+
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "hello");
+        assert!(state
+            .message
+            .as_deref()
+            .unwrap()
+            .starts_with("Error reading"));
+    }
+}
@@ -0,0 +1,282 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::thread;
+
+use crate::core::{Buffer, BufferId};
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandError, CommandResult};
+
+pub const GREP_BUFFER: &str = "*Grep*";
+
+/// One `project-grep` hit: 1-based line and column within `path`,
+/// which is relative to the project root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepMatch {
+    pub path: String,
+    pub line: usize,
+    pub col: usize,
+    pub text: String,
+}
+
+/// Renders a match as its `path:line:col: text` buffer line.
+pub fn format_match(m: &GrepMatch) -> String {
+    format!("{}:{}:{}: {}", m.path, m.line, m.col, m.text)
+}
+
+/// Parses a `*Grep*` buffer line back into a match.
+pub fn parse_match_line(line: &str) -> Option<GrepMatch> {
+    let mut parts = line.splitn(4, ':');
+    let path = parts.next()?;
+    let line_no = parts.next()?.parse().ok()?;
+    let col = parts.next()?.parse().ok()?;
+    let text = parts.next()?.strip_prefix(' ')?;
+    Some(GrepMatch {
+        path: path.to_string(),
+        line: line_no,
+        col,
+        text: text.to_string(),
+    })
+}
+
+/// An in-flight or finished project search: the background thread feeds
+/// matches through `receiver`, and `next-error`/`previous-error` walk
+/// the collected `matches`.
+pub struct GrepState {
+    pub receiver: Receiver<GrepMatch>,
+    pub root: PathBuf,
+    pub buffer: BufferId,
+    pub matches: Vec<GrepMatch>,
+    pub current: Option<usize>,
+    pub done: bool,
+}
+
+/// NUL bytes near the start mark a file we shouldn't grep.
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(1024).any(|&b| b == 0)
+}
+
+/// Drains pending matches from the background search into the `*Grep*`
+/// buffer. Called from the frontend event loops each tick.
+pub fn poll(state: &mut EditorState) {
+    let mut new_lines = String::new();
+    let mut finished = false;
+
+    if let Some(grep) = state.grep.as_mut() {
+        if grep.done {
+            return;
+        }
+        loop {
+            match grep.receiver.try_recv() {
+                Ok(m) => {
+                    new_lines.push_str(&format_match(&m));
+                    new_lines.push('\n');
+                    grep.matches.push(m);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    grep.done = true;
+                    finished = true;
+                    break;
+                }
+            }
+        }
+
+        if !new_lines.is_empty() {
+            if let Some(buffer) = state.buffers.get_mut(grep.buffer) {
+                let end = buffer.text.len_chars();
+                buffer.text.insert(end, &new_lines);
+            }
+        }
+        if finished {
+            state.message = Some(format!("Grep finished: {} matches", grep.matches.len()));
+        }
+    }
+}
+
+pub fn project_grep(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if super::project::current_root(state).is_none() {
+        return Err(CommandError::Other("No project root found".to_string()));
+    }
+    state.start_minibuffer_prompt("Grep project: ", "project-grep");
+    Ok(())
+}
+
+/// Minibuffer callback for `project-grep`: shows a fresh `*Grep*`
+/// buffer and spawns the search thread.
+pub fn start_search(state: &mut EditorState, pattern: &str) {
+    if pattern.is_empty() {
+        state.message = Some("Empty search pattern".to_string());
+        return;
+    }
+    let root = match super::project::current_root(state) {
+        Some(r) => r,
+        None => {
+            state.message = Some("No project root found".to_string());
+            return;
+        }
+    };
+
+    if let Some(id) = state.buffers.find_by_name(GREP_BUFFER) {
+        state.buffers.kill(id);
+    }
+    let mut buffer = Buffer::new(GREP_BUFFER);
+    buffer.read_only = true;
+    let id = state.buffers.add(buffer);
+    state.buffers.set_current(id);
+    state.windows.set_current_buffer(id);
+
+    state.message = Some(format!("Grepping for \"{}\"...", pattern));
+
+    let files = super::project::list_files(&root);
+    let (sender, receiver) = channel();
+    let thread_root = root.clone();
+    let pattern = pattern.to_string();
+
+    thread::spawn(move || {
+        for rel in files {
+            let bytes = match fs::read(thread_root.join(&rel)) {
+                Ok(b) => b,
+                Err(_) => continue,
+            };
+            if looks_binary(&bytes) {
+                continue;
+            }
+            let contents = String::from_utf8_lossy(&bytes);
+
+            for (line_idx, line) in contents.lines().enumerate() {
+                let mut start = 0;
+                while let Some(found) = line[start..].find(&pattern) {
+                    let byte_col = start + found;
+                    let col = line[..byte_col].chars().count() + 1;
+                    let sent = sender.send(GrepMatch {
+                        path: rel.clone(),
+                        line: line_idx + 1,
+                        col,
+                        text: line.trim_end().to_string(),
+                    });
+                    if sent.is_err() {
+                        return;
+                    }
+                    start = byte_col + pattern.len();
+                }
+            }
+        }
+    });
+
+    state.grep = Some(GrepState {
+        receiver,
+        root,
+        buffer: id,
+        matches: Vec::new(),
+        current: None,
+        done: false,
+    });
+}
+
+fn jump(state: &mut EditorState, dir: isize) -> CommandResult {
+    let (index, total, m) = {
+        let grep = state
+            .grep
+            .as_mut()
+            .ok_or_else(|| CommandError::Other("No grep results".to_string()))?;
+        if grep.matches.is_empty() {
+            return Err(CommandError::Other("No matches".to_string()));
+        }
+
+        let next = match grep.current {
+            None => {
+                if dir > 0 {
+                    0
+                } else {
+                    grep.matches.len() - 1
+                }
+            }
+            Some(i) => {
+                let n = i as isize + dir;
+                if n < 0 || n as usize >= grep.matches.len() {
+                    return Err(CommandError::Other("No more matches".to_string()));
+                }
+                n as usize
+            }
+        };
+        grep.current = Some(next);
+        (next, grep.matches.len(), grep.matches[next].clone())
+    };
+
+    let root = state.grep.as_ref().unwrap().root.clone();
+    state
+        .open_file(root.join(&m.path))
+        .map_err(|e| CommandError::Other(format!("Error opening {}: {}", m.path, e)))?;
+
+    let offset = {
+        use crate::core::rope_ext::RopeExt;
+
+        let buffer = state
+            .windows
+            .current_buffer_id()
+            .and_then(|id| state.buffers.get(id));
+        match buffer {
+            Some(buffer) => {
+                let line = m
+                    .line
+                    .saturating_sub(1)
+                    .min(buffer.text.total_lines().saturating_sub(1));
+                let target = buffer.text.line_start_char(line).0 + m.col.saturating_sub(1);
+                crate::core::position::CharOffset(target.min(buffer.text.total_chars()))
+            }
+            None => return Ok(()),
+        }
+    };
+    if let Some(window) = state.windows.current_mut() {
+        window.cursors.remove_secondary_cursors();
+        window.cursors.primary.position = offset;
+        window.cursors.primary.clear_mark();
+    }
+
+    state.message = Some(format!("Match {}/{}", index + 1, total));
+    Ok(())
+}
+
+pub fn next_error(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    jump(state, 1)
+}
+
+pub fn previous_error(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    jump(state, -1)
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![
+        Command::new("project-grep", project_grep),
+        Command::new("next-error", next_error),
+        Command::new("previous-error", previous_error),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_line_round_trips() {
+        let m = GrepMatch {
+            path: "src/main.rs".to_string(),
+            line: 42,
+            col: 7,
+            text: "    let x = frob();".to_string(),
+        };
+
+        let line = format_match(&m);
+        assert_eq!(line, "src/main.rs:42:7:     let x = frob();");
+        assert_eq!(parse_match_line(&line), Some(m));
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_lines() {
+        assert_eq!(parse_match_line("no colons here"), None);
+        assert_eq!(parse_match_line("path:notanumber:3: text"), None);
+        assert_eq!(parse_match_line(""), None);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::core::Buffer;
+use crate::keybinding::keymap::{KeyBinding, KeyMap};
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandResult};
+
+const HELP_BUFFER: &str = "*Help*";
+
+/// `C-h k`: arms describe mode; the next full key sequence is resolved
+/// against the keymap and reported instead of executed.
+pub fn describe_key(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.describing_key = Some(Vec::new());
+    state.message = Some("Describe key: ".to_string());
+    Ok(())
+}
+
+/// `M-x global-set-key`: prompts for a key spec like `C-c C-k`, then
+/// for a command name, and rebinds the global keymap at runtime.
+pub fn global_set_key(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Set key globally: ", "global-set-key-sequence");
+    Ok(())
+}
+
+/// One line per bound key of `map`, recursing into prefix maps so
+/// nested bindings show their full sequence.
+fn collect_bindings(map: &KeyMap, prefix: &str, out: &mut Vec<String>) {
+    for (key, binding) in map.iter() {
+        let seq = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{} {}", prefix, key)
+        };
+        match binding {
+            KeyBinding::Command(cmd) => out.push(format!("{:<16} {}", seq, cmd)),
+            KeyBinding::Prefix(sub) => collect_bindings(sub, &seq, out),
+            KeyBinding::Unbound => {}
+        }
+    }
+}
+
+/// `C-h b`: lists every key sequence and its command in a read-only
+/// `*Help*` buffer.
+pub fn describe_bindings(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let mut lines = Vec::new();
+    collect_bindings(&state.keymap, "", &mut lines);
+    lines.sort_unstable();
+
+    let contents = format!("Key bindings:\n\n{}\n", lines.join("\n"));
+
+    if let Some(id) = state.buffers.find_by_name(HELP_BUFFER) {
+        state.buffers.kill(id);
+    }
+    let mut buffer = Buffer::from_string(HELP_BUFFER, &contents);
+    buffer.read_only = true;
+    let id = state.buffers.add(buffer);
+    state.buffers.set_current(id);
+    state.windows.set_current_buffer(id);
+    Ok(())
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![
+        Command::new("describe-key", describe_key),
+        Command::new("describe-bindings", describe_bindings),
+        Command::new("global-set-key", global_set_key),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::Buffer;
+    use crate::keybinding::key::KeyEvent;
+    use crate::state::EditorState;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    #[test]
+    fn test_describe_key_reports_multi_key_binding() {
+        let mut state = make_state("hello");
+
+        state.handle_key(KeyEvent::ctrl('h'));
+        state.handle_key(KeyEvent::char('k'));
+        assert_eq!(state.message.as_deref(), Some("Describe key: "));
+
+        state.handle_key(KeyEvent::ctrl('x'));
+        state.handle_key(KeyEvent::ctrl('s'));
+        assert_eq!(
+            state.message.as_deref(),
+            Some("C-x C-s runs the command save-buffer")
+        );
+        // The described sequence was not executed
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "hello");
+    }
+
+    #[test]
+    fn test_global_set_key_binds_at_runtime() {
+        use crate::keybinding::key::{Key, Modifiers};
+
+        let mut state = make_state("one\ntwo\n");
+        let ctx = crate::commands::CommandContext::new();
+        super::global_set_key(&mut state, &ctx).unwrap();
+        assert_eq!(state.minibuffer.prompt, "Set key globally: ");
+
+        for c in "C-c C-n".chars() {
+            state.handle_key(KeyEvent::char(c));
+        }
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+        assert_eq!(state.minibuffer.prompt, "Bind C-c C-n to command: ");
+
+        for c in "next-line".chars() {
+            state.handle_key(KeyEvent::char(c));
+        }
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+        assert_eq!(state.message.as_deref(), Some("Bound to next-line"));
+
+        state.handle_key(KeyEvent::ctrl('c'));
+        state.handle_key(KeyEvent::ctrl('n'));
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            crate::core::position::CharOffset(4)
+        );
+    }
+
+    #[test]
+    fn test_describe_bindings_lists_nested_sequences() {
+        let mut state = make_state("");
+
+        state.handle_key(KeyEvent::ctrl('h'));
+        state.handle_key(KeyEvent::char('b'));
+
+        let buffer = state.current_buffer().unwrap();
+        assert_eq!(buffer.name, "*Help*");
+        assert!(buffer.read_only);
+
+        let text = buffer.text.to_string();
+        assert!(text
+            .lines()
+            .any(|l| l.starts_with("C-x C-s") && l.ends_with("save-buffer")));
+        assert!(text
+            .lines()
+            .any(|l| l.starts_with("C-h b") && l.ends_with("describe-bindings")));
+    }
+
+    #[test]
+    fn test_describe_key_reports_undefined_and_self_insert() {
+        let mut state = make_state("");
+
+        state.handle_key(KeyEvent::ctrl('h'));
+        state.handle_key(KeyEvent::char('k'));
+        state.handle_key(KeyEvent::ctrl('z'));
+        assert_eq!(state.message.as_deref(), Some("C-z is undefined"));
+
+        state.handle_key(KeyEvent::ctrl('h'));
+        state.handle_key(KeyEvent::char('k'));
+        state.handle_key(KeyEvent::char('a'));
+        assert_eq!(
+            state.message.as_deref(),
+            Some("a runs the command self-insert-command")
+        );
+        assert!(state.current_buffer().unwrap().is_empty());
+    }
+}
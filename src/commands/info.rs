@@ -0,0 +1,275 @@
+use crate::core::rope_ext::RopeExt;
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandError, CommandResult};
+
+const TAB_WIDTH: usize = 4;
+
+/// Visual column of the primary cursor on its line, counting tabs as
+/// the distance to the next tab stop.
+fn visual_column(state: &EditorState) -> usize {
+    let (buffer, window) = match (state.current_buffer(), state.current_window()) {
+        (Some(b), Some(w)) => (b, w),
+        _ => return 0,
+    };
+
+    let pos = buffer.text.char_to_position(window.cursors.primary.position);
+    let line = buffer.text.line(pos.line);
+
+    let mut col = 0;
+    for (i, ch) in line.chars().enumerate() {
+        if i >= pos.column {
+            break;
+        }
+        if ch == '\t' {
+            col += TAB_WIDTH - (col % TAB_WIDTH);
+        } else {
+            col += 1;
+        }
+    }
+    col
+}
+
+pub fn what_column(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let col = visual_column(state) + state.column_number_base;
+    state.message = Some(format!("Column {}", col));
+    Ok(())
+}
+
+/// A readable name for a character in the echo area; printable chars
+/// show themselves.
+fn describe_char(c: char) -> String {
+    match c {
+        '\n' => "newline".to_string(),
+        '\t' => "tab".to_string(),
+        ' ' => "space".to_string(),
+        c if c.is_control() => format!("control-{:02X}", c as u32),
+        c => c.to_string(),
+    }
+}
+
+/// `C-x =`: reports the character under point, the point's offset,
+/// line/column, and how far through the buffer it is.
+pub fn what_cursor_position(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let (buffer, window) = match (state.current_buffer(), state.current_window()) {
+        (Some(b), Some(w)) => (b, w),
+        _ => return Ok(()),
+    };
+
+    let point = window.cursors.primary.position;
+    let total = buffer.text.len_chars();
+    let pos = buffer.text.char_to_position(point);
+    let percent = (point.0 * 100).checked_div(total).unwrap_or(0);
+    let head = match buffer.text.char_at(point) {
+        Some(c) => format!("Char: {} (U+{:04X}) ", describe_char(c), c as u32),
+        None => "End of buffer: ".to_string(),
+    };
+
+    state.message = Some(format!(
+        "{}point={} of {} ({}%) line={} column={}",
+        head,
+        point.0,
+        total,
+        percent,
+        pos.line + 1,
+        pos.column + state.column_number_base
+    ));
+    Ok(())
+}
+
+/// `M-=`: counts lines, words, and characters in the region, or the
+/// accessible buffer when no region is active.
+pub fn count_words_region(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let (buffer, window) = match (state.current_buffer(), state.current_window()) {
+        (Some(b), Some(w)) => (b, w),
+        _ => return Ok(()),
+    };
+
+    let (start, end, what) = match window.cursors.primary.region() {
+        Some((start, end)) => (start, end, "Region"),
+        None => {
+            let (start, end) = buffer.narrow_bounds();
+            (start, end, "Buffer")
+        }
+    };
+
+    let text = buffer.slice(start, end);
+    let lines = text.lines().count();
+    let words = text.split_whitespace().count();
+    let chars = text.chars().count();
+
+    state.message = Some(format!(
+        "{} has {} lines, {} words, and {} characters",
+        what, lines, words, chars
+    ));
+    Ok(())
+}
+
+/// `M-x`: prompts for a command name, with TAB completion over the
+/// registry, and runs it.
+pub fn execute_extended_command(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("M-x ", "execute-extended-command");
+    Ok(())
+}
+
+/// `C-x z`: re-runs the last command with the prefix argument it was
+/// invoked with. Afterwards a solitary `z` repeats again, so C-x z z z
+/// runs it three times.
+pub fn repeat(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    let name = match ctx.last_command {
+        Some(name) if name != "repeat" => name,
+        _ => return Err(CommandError::Other("Nothing to repeat".to_string())),
+    };
+
+    state.prefix_arg = state.last_prefix_arg;
+    state.execute_command(name);
+    state.repeat_active = true;
+    Ok(())
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![
+        Command::motion("what-column", what_column),
+        Command::motion("what-cursor-position", what_cursor_position),
+        Command::motion("count-words-region", count_words_region),
+        Command::new("execute-extended-command", execute_extended_command),
+        Command::new("repeat", repeat),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::position::CharOffset;
+    use crate::core::Buffer;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    #[test]
+    fn test_what_cursor_position_reports_char_and_percent() {
+        let mut state = make_state("hello\nworld\n");
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(6);
+
+        let ctx = CommandContext::new();
+        what_cursor_position(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Char: w (U+0077) point=6 of 12 (50%) line=2 column=1")
+        );
+
+        // Past the last character there is no char to describe
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(12);
+        what_cursor_position(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.message.as_deref(),
+            Some("End of buffer: point=12 of 12 (100%) line=3 column=1")
+        );
+    }
+
+    #[test]
+    fn test_count_words_region_falls_back_to_buffer() {
+        let mut state = make_state("one two\nthree four five\n");
+
+        let ctx = CommandContext::new();
+        count_words_region(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Buffer has 2 lines, 5 words, and 24 characters")
+        );
+
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        cursors.primary.set_mark(CharOffset(0));
+        cursors.primary.set_position(CharOffset(7));
+        count_words_region(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Region has 1 lines, 2 words, and 7 characters")
+        );
+    }
+
+    #[test]
+    fn test_repeat_reruns_last_command_and_chains_on_z() {
+        use crate::keybinding::KeyEvent;
+
+        let mut state = make_state("hello world");
+
+        state.handle_key(KeyEvent::ctrl('f'));
+        state.handle_key(KeyEvent::ctrl('x'));
+        state.handle_key(KeyEvent::char('z'));
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(2)
+        );
+
+        // A bare `z` keeps repeating
+        state.handle_key(KeyEvent::char('z'));
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(3)
+        );
+
+        // Any other key ends the mini-mode; `z` self-inserts again
+        state.handle_key(KeyEvent::ctrl('b'));
+        state.handle_key(KeyEvent::char('z'));
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "hezllo world"
+        );
+    }
+
+    #[test]
+    fn test_repeat_restores_the_original_prefix() {
+        use crate::commands::registry::PrefixArg;
+        use crate::keybinding::KeyEvent;
+
+        let mut state = make_state("abcdefghij");
+
+        state.prefix_arg = PrefixArg::Universal(3);
+        state.handle_key(KeyEvent::ctrl('f'));
+        state.handle_key(KeyEvent::ctrl('x'));
+        state.handle_key(KeyEvent::char('z'));
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(6)
+        );
+    }
+
+    #[test]
+    fn test_what_column_is_tab_aware() {
+        let mut state = make_state("\tabc");
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(2);
+
+        let ctx = CommandContext::new();
+        what_column(&mut state, &ctx).unwrap();
+        assert_eq!(state.message.as_deref(), Some("Column 6"));
+
+        state.column_number_base = 0;
+        what_column(&mut state, &ctx).unwrap();
+        assert_eq!(state.message.as_deref(), Some("Column 5"));
+    }
+}
@@ -1,10 +1,15 @@
 use crate::core::cursor::CursorId;
 use crate::core::position::CharOffset;
 use crate::core::rope_ext::{find_word_boundary_backward, find_word_boundary_forward, RopeExt};
+use crate::core::{Buffer, BufferId};
+use crate::keybinding::key::{Key, Modifiers};
+use crate::keybinding::KeyEvent;
 use crate::state::EditorState;
 
 use super::registry::{Command, CommandContext, CommandError, CommandResult};
 
+const KILL_RING_BUFFER: &str = "*Kill Ring*";
+
 fn update_kill_rings(state: &mut EditorState, killed: Vec<(CursorId, String)>, prepend: bool) {
     let window = match state.windows.current_mut() {
         Some(w) => w,
@@ -28,6 +33,25 @@ fn update_kill_rings(state: &mut EditorState, killed: Vec<(CursorId, String)>, p
     }
 }
 
+/// Per-line spans of the active rectangle selection, clamped to each
+/// line's length, or `None` when rectangle-mark-mode is off.
+fn rectangle_line_regions(state: &EditorState) -> Option<Vec<(CharOffset, CharOffset)>> {
+    let window = state.windows.current()?;
+    let buffer = state.buffers.get(window.buffer_id)?;
+    let (top, bottom, left, right) = window.rectangle_region(&buffer.text)?;
+
+    let mut regions = Vec::new();
+    for line in top..=bottom {
+        let line_start = buffer.text.line_start_char(line).0;
+        let line_len = buffer.text.line_len_chars(line);
+        regions.push((
+            CharOffset(line_start + left.min(line_len)),
+            CharOffset(line_start + right.min(line_len)),
+        ));
+    }
+    Some(regions)
+}
+
 fn set_all_last_was_kill(state: &mut EditorState, was_kill: bool) {
     if let Some(window) = state.windows.current_mut() {
         for cursor in window.cursors.all_cursors_mut() {
@@ -218,6 +242,40 @@ pub fn kill_region(state: &mut EditorState, _ctx: &CommandContext) -> CommandRes
         return Err(CommandError::ReadOnly);
     }
 
+    // A rectangle selection kills the columnar span of every line it
+    // touches, saved as one newline-joined kill.
+    if let Some(line_regions) = rectangle_line_regions(state) {
+        let primary_id = state.windows.current().unwrap().cursors.primary.id;
+        let regions: Vec<(CursorId, CharOffset, CharOffset)> = line_regions
+            .into_iter()
+            .map(|(start, end)| (primary_id, start, end))
+            .collect();
+
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        let killed = if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+            buffer.delete_regions(cursors, regions)
+        } else {
+            Vec::new()
+        };
+
+        // delete_regions works bottom-up; restore top-down line order.
+        let text = killed
+            .iter()
+            .rev()
+            .map(|(_, s)| s.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let window = state.windows.current_mut().unwrap();
+        if !text.is_empty() {
+            window.cursors.primary.kill_ring.push(text, false);
+        }
+        window.cursors.deactivate_all_marks();
+        window.rectangle_mark = false;
+        set_all_last_was_kill(state, true);
+        return Ok(());
+    }
+
     let regions: Vec<(CursorId, CharOffset, CharOffset)> = {
         let window = state.windows.current().unwrap();
 
@@ -258,6 +316,23 @@ pub fn copy_region_as_kill(state: &mut EditorState, _ctx: &CommandContext) -> Co
         None => return Ok(()),
     };
 
+    if let Some(line_regions) = rectangle_line_regions(state) {
+        let text = line_regions
+            .iter()
+            .map(|&(start, end)| buffer.slice(start, end))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let window = state.windows.current_mut().unwrap();
+        if !text.is_empty() {
+            window.cursors.primary.kill_ring.push(text, false);
+        }
+        window.cursors.deactivate_all_marks();
+        window.rectangle_mark = false;
+        state.message = Some("Rectangle saved".to_string());
+        return Ok(());
+    }
+
     let copies: Vec<(CursorId, String)> = window
         .cursors
         .all_cursors()
@@ -350,7 +425,18 @@ pub fn yank(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
 
 pub fn yank_pop(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
     if ctx.last_command != Some("yank") && ctx.last_command != Some("yank-pop") {
-        state.message = Some("Previous command was not a yank".to_string());
+        // Like modern Emacs: M-y without a prior yank reads the entry
+        // to insert from the minibuffer instead of giving up.
+        let ring_empty = state
+            .windows
+            .current()
+            .map(|w| w.cursors.primary.kill_ring.is_empty())
+            .unwrap_or(true);
+        if ring_empty {
+            state.message = Some("Kill ring is empty".to_string());
+        } else {
+            state.start_minibuffer_prompt("Yank from kill ring: ", "yank-from-kill-ring");
+        }
         return Ok(());
     }
 
@@ -436,6 +522,203 @@ pub fn yank_pop(state: &mut EditorState, ctx: &CommandContext) -> CommandResult
     Ok(())
 }
 
+/// Multi-line entries show in the minibuffer with their newlines
+/// escaped, so a candidate stays on one line.
+fn flatten_entry(entry: &str) -> String {
+    entry.replace('\n', "\\n")
+}
+
+/// Kill-ring completion for the `yank-from-kill-ring` prompt.
+pub fn complete_kill_ring(state: &EditorState, input: &str) -> Vec<String> {
+    let Some(window) = state.current_window() else {
+        return Vec::new();
+    };
+    window
+        .cursors
+        .primary
+        .kill_ring
+        .iter()
+        .map(flatten_entry)
+        .filter(|entry| entry.starts_with(input))
+        .collect()
+}
+
+/// Inserts the entry picked at the `yank-from-kill-ring` prompt. The
+/// typed text maps back to the ring entry whose flattened form matches;
+/// free-form input is inserted as typed.
+pub fn apply_yank_from_kill_ring(state: &mut EditorState, content: &str) {
+    let text = state
+        .windows
+        .current()
+        .and_then(|w| {
+            w.cursors
+                .primary
+                .kill_ring
+                .iter()
+                .find(|entry| flatten_entry(entry) == content)
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| content.to_string());
+    if text.is_empty() {
+        return;
+    }
+
+    let origin = match state.windows.current() {
+        Some(w) => w.cursors.primary.position,
+        None => return,
+    };
+    yank_entry(state, origin, &text);
+}
+
+/// The kill-ring listing plus enough context to yank the picked entry
+/// back where point was.
+pub struct KillRingBrowse {
+    pub source: BufferId,
+    pub origin: CharOffset,
+    /// The ring entry shown on each display line of the listing;
+    /// separator lines carry `None`.
+    pub line_entries: Vec<Option<usize>>,
+    pub entries: Vec<String>,
+}
+
+/// Opens a `*Kill Ring*` buffer listing the primary cursor's kill ring,
+/// newest first, one numbered separator per entry.
+pub fn browse_kill_ring(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let (source, origin, entries) = {
+        let window = match state.windows.current() {
+            Some(w) => w,
+            None => return Ok(()),
+        };
+        let entries: Vec<String> = window
+            .cursors
+            .primary
+            .kill_ring
+            .iter()
+            .map(str::to_string)
+            .collect();
+        (window.buffer_id, window.cursors.primary.position, entries)
+    };
+    if entries.is_empty() {
+        return Err(CommandError::Other("Kill ring is empty".to_string()));
+    }
+
+    if let Some(id) = state.buffers.find_by_name(KILL_RING_BUFFER) {
+        state.buffers.kill(id);
+    }
+
+    let mut contents = String::new();
+    let mut line_entries = Vec::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        contents.push_str(&format!("---- {} ----\n", idx + 1));
+        line_entries.push(None);
+        for line in entry.split('\n') {
+            contents.push_str(line);
+            contents.push('\n');
+            line_entries.push(Some(idx));
+        }
+    }
+
+    let mut buffer = Buffer::from_string(KILL_RING_BUFFER, &contents);
+    buffer.read_only = true;
+    let id = state.buffers.add(buffer);
+    state.buffers.set_current(id);
+    state.windows.set_current_buffer(id);
+
+    state.kill_ring_browse = Some(KillRingBrowse {
+        source,
+        origin,
+        line_entries,
+        entries,
+    });
+    state.message = Some("RET to yank the entry at point, C-g to quit".to_string());
+    Ok(())
+}
+
+/// Intercepts RET (yank the entry at point where the browser was
+/// opened) and C-g/Escape (quit) while the kill-ring buffer is current.
+/// Returns true when consumed.
+pub fn handle_kill_ring_browse_key(state: &mut EditorState, key: KeyEvent) -> bool {
+    if !in_kill_ring_browse(state) {
+        return false;
+    }
+
+    match (key.key, key.modifiers) {
+        (Key::Enter, Modifiers::NONE) => {
+            let picked = entry_at_point(state);
+            let browse = state.kill_ring_browse.take();
+            close_kill_ring_browse(state, browse.as_ref().map(|b| b.source));
+            if let (Some(text), Some(browse)) = (picked, browse) {
+                yank_entry(state, browse.origin, &text);
+            }
+            true
+        }
+        (Key::Char('g'), Modifiers::CTRL) | (Key::Escape, _) => {
+            let browse = state.kill_ring_browse.take();
+            close_kill_ring_browse(state, browse.as_ref().map(|b| b.source));
+            state.message = Some("Quit".to_string());
+            true
+        }
+        _ => false,
+    }
+}
+
+fn in_kill_ring_browse(state: &EditorState) -> bool {
+    state.kill_ring_browse.is_some()
+        && state
+            .current_buffer()
+            .map(|b| b.name == KILL_RING_BUFFER)
+            .unwrap_or(false)
+}
+
+/// The ring entry on the listing line at point.
+fn entry_at_point(state: &EditorState) -> Option<String> {
+    let window = state.current_window()?;
+    let buffer = state.buffers.get(window.buffer_id)?;
+    let line = buffer
+        .text
+        .char_to_position(window.cursors.primary.position)
+        .line;
+    let browse = state.kill_ring_browse.as_ref()?;
+    let idx = browse.line_entries.get(line).copied().flatten()?;
+    browse.entries.get(idx).cloned()
+}
+
+fn close_kill_ring_browse(state: &mut EditorState, source: Option<BufferId>) {
+    let source = source.filter(|id| state.buffers.get(*id).is_some());
+
+    if let Some(id) = source {
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+    }
+
+    if let Some(id) = state.buffers.find_by_name(KILL_RING_BUFFER) {
+        state.buffers.kill(id);
+    }
+}
+
+/// Inserts `text` at `origin` in the restored source buffer, leaving
+/// point after it and the mark before, like `yank`.
+fn yank_entry(state: &mut EditorState, origin: CharOffset, text: &str) {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return,
+    };
+    if let Some(window) = state.windows.current_mut() {
+        window.cursors.remove_secondary_cursors();
+        window.cursors.primary.position = origin;
+        window.cursors.primary.clear_mark();
+    }
+
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.insert_string(cursors, text);
+    }
+
+    if let Some(window) = state.windows.current_mut() {
+        window.cursors.primary.set_mark(origin);
+    }
+}
+
 pub fn all_commands() -> Vec<Command> {
     vec![
         Command::kill("kill-line", kill_line),
@@ -445,6 +728,7 @@ pub fn all_commands() -> Vec<Command> {
         Command::new("copy-region-as-kill", copy_region_as_kill),
         Command::new("yank", yank),
         Command::new("yank-pop", yank_pop),
+        Command::new("browse-kill-ring", browse_kill_ring),
     ]
 }
 
@@ -554,6 +838,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_kill_region_rectangle() {
+        let mut state = make_state("abcdef\nabcdef\nabcdef\n");
+        {
+            let window = state.windows.current_mut().unwrap();
+            window.cursors.primary.set_mark(CharOffset(1));
+            // Point at line 2, column 4: a 3x3 rectangle over columns 1-4.
+            window.cursors.primary.position = CharOffset(18);
+            window.rectangle_mark = true;
+        }
+
+        let ctx = CommandContext::new();
+        kill_region(&mut state, &ctx).unwrap();
+
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "aef\naef\naef\n"
+        );
+        let window = state.windows.current().unwrap();
+        assert_eq!(window.cursors.primary.kill_ring.yank(), Some("bcd\nbcd\nbcd"));
+        assert!(!window.rectangle_mark);
+    }
+
+    #[test]
+    fn test_copy_region_rectangle_clamps_short_lines() {
+        let mut state = make_state("abcdef\nab\nabcdef\n");
+        {
+            let window = state.windows.current_mut().unwrap();
+            window.cursors.primary.set_mark(CharOffset(3));
+            window.cursors.primary.position = CharOffset(16);
+            window.rectangle_mark = true;
+        }
+
+        let ctx = CommandContext::new();
+        copy_region_as_kill(&mut state, &ctx).unwrap();
+
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "abcdef\nab\nabcdef\n"
+        );
+        let window = state.windows.current().unwrap();
+        assert_eq!(window.cursors.primary.kill_ring.yank(), Some("def\n\ndef"));
+    }
+
     #[test]
     fn test_multi_cursor_kill_word() {
         let mut state = make_state("aaa bbb ccc");
@@ -608,4 +936,85 @@ mod tests {
             "AAAX BBBY CCCZ"
         );
     }
+
+    #[test]
+    fn test_browse_kill_ring_yanks_the_picked_entry() {
+        let mut state = make_state("hello\n");
+        {
+            let ring = &mut state.windows.current_mut().unwrap().cursors.primary.kill_ring;
+            ring.push("first".to_string(), false);
+            ring.set_last_was_kill(false);
+            ring.push("second".to_string(), false);
+        }
+
+        state.run_command("browse-kill-ring");
+        assert_eq!(state.current_buffer().unwrap().name, KILL_RING_BUFFER);
+
+        // Newest first: line 0 is the separator of "second", line 3 the
+        // text of "first". Pick the older entry.
+        for _ in 0..3 {
+            state.handle_key(KeyEvent::ctrl('n'));
+        }
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+
+        assert_eq!(state.current_buffer().unwrap().name, "test");
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "firsthello\n"
+        );
+        // Point ends after the yanked text, the mark before it.
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(5)
+        );
+        assert!(state.kill_ring_browse.is_none());
+        assert!(state.buffers.find_by_name(KILL_RING_BUFFER).is_none());
+    }
+
+    #[test]
+    fn test_yank_pop_without_prior_yank_prompts_over_the_ring() {
+        let mut state = make_state("hello\n");
+        {
+            let ring = &mut state.windows.current_mut().unwrap().cursors.primary.kill_ring;
+            ring.push("first".to_string(), false);
+            ring.set_last_was_kill(false);
+            ring.push("a\nb".to_string(), false);
+        }
+
+        let ctx = CommandContext::new();
+        yank_pop(&mut state, &ctx).unwrap();
+        assert!(state.minibuffer.is_active());
+        assert_eq!(state.minibuffer.prompt, "Yank from kill ring: ");
+
+        // Candidates show flattened; picking one inserts the raw entry.
+        assert_eq!(complete_kill_ring(&state, "a"), vec!["a\\nb"]);
+        for c in "a\\nb".chars() {
+            state.handle_key(KeyEvent::char(c));
+        }
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "a\nbhello\n"
+        );
+    }
+
+    #[test]
+    fn test_yank_pop_with_empty_ring_still_messages() {
+        let mut state = make_state("hello\n");
+        let ctx = CommandContext::new();
+
+        yank_pop(&mut state, &ctx).unwrap();
+        assert!(!state.minibuffer.is_active());
+        assert_eq!(state.message.as_deref(), Some("Kill ring is empty"));
+    }
+
+    #[test]
+    fn test_browse_kill_ring_with_empty_ring_reports_an_error() {
+        let mut state = make_state("hello\n");
+        let ctx = CommandContext::new();
+
+        assert!(browse_kill_ring(&mut state, &ctx).is_err());
+        assert!(state.buffers.find_by_name(KILL_RING_BUFFER).is_none());
+    }
 }
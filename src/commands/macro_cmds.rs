@@ -0,0 +1,147 @@
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandError, CommandResult};
+
+/// Drops the keys that invoked the current macro command from the
+/// recording so replaying it doesn't re-trigger the command itself.
+fn strip_invoking_keys(state: &mut EditorState) {
+    let keep = state
+        .macro_keys
+        .len()
+        .saturating_sub(state.macro_pending_keys);
+    state.macro_keys.truncate(keep);
+}
+
+pub fn start_kbd_macro(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if state.recording_macro {
+        return Err(CommandError::Other(
+            "Already defining kbd macro".to_string(),
+        ));
+    }
+    state.macro_keys.clear();
+    state.recording_macro = true;
+    state.message = Some("Defining kbd macro...".to_string());
+    Ok(())
+}
+
+pub fn end_kbd_macro(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if !state.recording_macro {
+        return Err(CommandError::Other("Not defining kbd macro".to_string()));
+    }
+    strip_invoking_keys(state);
+    state.recording_macro = false;
+    state.message = Some("Keyboard macro defined".to_string());
+    Ok(())
+}
+
+pub fn call_last_kbd_macro(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    if state.executing_macro {
+        return Err(CommandError::Other(
+            "Keyboard macro called itself".to_string(),
+        ));
+    }
+
+    // C-x e while recording ends the definition and replays it.
+    if state.recording_macro {
+        strip_invoking_keys(state);
+        state.recording_macro = false;
+    }
+
+    if state.macro_keys.is_empty() {
+        return Err(CommandError::Other("No kbd macro defined".to_string()));
+    }
+
+    let keys = state.macro_keys.clone();
+    state.executing_macro = true;
+    for _ in 0..ctx.repeat_count() {
+        for key in &keys {
+            state.handle_key(*key);
+        }
+    }
+    state.executing_macro = false;
+
+    Ok(())
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![
+        Command::new("start-kbd-macro", start_kbd_macro),
+        Command::new("end-kbd-macro", end_kbd_macro),
+        Command::new("call-last-kbd-macro", call_last_kbd_macro),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::PrefixArg;
+    use crate::core::Buffer;
+    use crate::keybinding::KeyEvent;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    #[test]
+    fn test_record_and_replay_macro() {
+        let mut state = make_state("");
+
+        state.handle_key(KeyEvent::ctrl('x'));
+        state.handle_key(KeyEvent::char('('));
+        state.handle_key(KeyEvent::char('a'));
+        state.handle_key(KeyEvent::char('b'));
+        state.handle_key(KeyEvent::ctrl('x'));
+        state.handle_key(KeyEvent::char(')'));
+
+        assert!(!state.recording_macro);
+        assert_eq!(state.macro_keys.len(), 2);
+
+        state.handle_key(KeyEvent::ctrl('x'));
+        state.handle_key(KeyEvent::char('e'));
+
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "abab");
+    }
+
+    #[test]
+    fn test_replay_macro_with_repeat_count() {
+        let mut state = make_state("");
+
+        state.handle_key(KeyEvent::ctrl('x'));
+        state.handle_key(KeyEvent::char('('));
+        state.handle_key(KeyEvent::char('x'));
+        state.handle_key(KeyEvent::ctrl('x'));
+        state.handle_key(KeyEvent::char(')'));
+
+        let ctx = CommandContext::with_prefix(PrefixArg::Universal(3));
+        call_last_kbd_macro(&mut state, &ctx).unwrap();
+
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "xxxx");
+    }
+
+    #[test]
+    fn test_call_macro_without_definition() {
+        let mut state = make_state("");
+        let ctx = CommandContext::new();
+
+        assert!(call_last_kbd_macro(&mut state, &ctx).is_err());
+    }
+
+    #[test]
+    fn test_macro_recursion_guard() {
+        let mut state = make_state("");
+        let ctx = CommandContext::new();
+
+        state.macro_keys = vec![KeyEvent::ctrl('x'), KeyEvent::char('e')];
+        call_last_kbd_macro(&mut state, &ctx).unwrap();
+
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Keyboard macro called itself")
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use crate::core::{Buffer, BufferId};
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandError, CommandResult};
+
+const PREVIEW_BUFFER: &str = "*Markdown Preview*";
+
+/// How a rendered span should be styled by the frontend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MdStyle {
+    Text,
+    Bold,
+    Heading,
+    Bullet,
+    Code,
+}
+
+/// One styled run of preview text; a line is a sequence of spans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MdSpan {
+    pub text: String,
+    pub style: MdStyle,
+}
+
+impl MdSpan {
+    fn new(text: impl Into<String>, style: MdStyle) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+/// Splits `**bold**` runs out of a plain text line.
+fn parse_inline(text: &str) -> Vec<MdSpan> {
+    let mut spans = Vec::new();
+    let segments: Vec<&str> = text.split("**").collect();
+
+    // With an unmatched `**`, treat the marker as literal text.
+    if segments.len().is_multiple_of(2) {
+        spans.push(MdSpan::new(text, MdStyle::Text));
+        return spans;
+    }
+
+    for (i, segment) in segments.iter().enumerate() {
+        if segment.is_empty() {
+            continue;
+        }
+        let style = if i % 2 == 1 {
+            MdStyle::Bold
+        } else {
+            MdStyle::Text
+        };
+        spans.push(MdSpan::new(*segment, style));
+    }
+    spans
+}
+
+/// Transforms markdown source into styled preview lines: headings lose
+/// their `#` markers, bullets get a `•`, fenced code keeps its text
+/// verbatim, and `**bold**` runs are split into their own spans. Fence
+/// lines themselves are dropped.
+pub fn markdown_to_lines(source: &str) -> Vec<Vec<MdSpan>> {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for line in source.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+
+        if in_code {
+            lines.push(vec![MdSpan::new(line, MdStyle::Code)]);
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            lines.push(vec![MdSpan::new(&trimmed[hashes + 1..], MdStyle::Heading)]);
+        } else if let Some(rest) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* "))
+        {
+            let mut spans = vec![MdSpan::new("• ", MdStyle::Bullet)];
+            spans.extend(parse_inline(rest));
+            lines.push(spans);
+        } else {
+            lines.push(parse_inline(line));
+        }
+    }
+
+    lines
+}
+
+/// Flattens preview lines back into the text stored in the preview
+/// buffer; span boundaries within a line carry no characters.
+pub fn lines_to_text(lines: &[Vec<MdSpan>]) -> String {
+    let mut text = String::new();
+    for line in lines {
+        for span in line {
+            text.push_str(&span.text);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Live preview state: which buffer is being previewed, where the
+/// rendered view lives, and the styled lines the GUI draws from.
+pub struct MarkdownPreview {
+    pub source: BufferId,
+    pub preview: BufferId,
+    pub lines: Vec<Vec<MdSpan>>,
+}
+
+fn is_markdown_buffer(buffer: &Buffer) -> bool {
+    buffer.major_mode.language == "markdown" || buffer.name.ends_with(".md")
+}
+
+pub fn markdown_toggle_preview(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if state.markdown_preview.is_some() {
+        close_preview(state);
+        state.message = Some("Markdown preview disabled".to_string());
+        return Ok(());
+    }
+
+    let source = match state.windows.current_buffer_id() {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+    let source_text = match state.buffers.get(source) {
+        Some(b) if is_markdown_buffer(b) => b.text.to_string(),
+        Some(_) => {
+            return Err(CommandError::Other(
+                "Not a markdown buffer".to_string(),
+            ))
+        }
+        None => return Ok(()),
+    };
+
+    let lines = markdown_to_lines(&source_text);
+    let text = lines_to_text(&lines);
+    let mut buffer = Buffer::from_string(PREVIEW_BUFFER, &text);
+    buffer.read_only = true;
+    let preview = state.buffers.add(buffer);
+
+    // Show the rendered view in a side window, keeping focus here.
+    state.windows.split_horizontal();
+    state.windows.set_current_buffer(preview);
+    state.windows.cycle_prev();
+
+    state.markdown_preview = Some(MarkdownPreview {
+        source,
+        preview,
+        lines,
+    });
+    Ok(())
+}
+
+/// Regenerates the preview from the source buffer. Called from
+/// `post_command`, which is the closest thing we have to an idle timer.
+pub fn sync_preview(state: &mut EditorState) {
+    let (source, preview) = match &state.markdown_preview {
+        Some(p) => (p.source, p.preview),
+        None => return,
+    };
+
+    let source_text = match state.buffers.get(source) {
+        Some(b) => b.text.to_string(),
+        None => {
+            close_preview(state);
+            return;
+        }
+    };
+
+    let lines = markdown_to_lines(&source_text);
+    let text = lines_to_text(&lines);
+
+    if let Some(buffer) = state.buffers.get_mut(preview) {
+        if buffer.text != text {
+            buffer.text = ropey::Rope::from_str(&text);
+        }
+    }
+    if let Some(p) = state.markdown_preview.as_mut() {
+        p.lines = lines;
+    }
+}
+
+fn close_preview(state: &mut EditorState) {
+    let preview = match state.markdown_preview.take() {
+        Some(p) => p.preview,
+        None => return,
+    };
+
+    // Delete whichever windows show the preview, then the buffer itself.
+    while state.windows.count() > 1
+        && state
+            .windows
+            .iter()
+            .any(|w| w.buffer_id == preview)
+    {
+        if state.windows.current().map(|w| w.buffer_id) != Some(preview) {
+            state.windows.cycle_next();
+        }
+        if state.windows.current().map(|w| w.buffer_id) == Some(preview) {
+            state.windows.delete_current();
+        }
+    }
+    state.buffers.kill(preview);
+}
+
+/// Lines of the preview buffer that should get a code-block background,
+/// for the frontend's rect pass.
+pub fn code_block_rows(state: &EditorState, buffer_id: BufferId) -> Vec<usize> {
+    match &state.markdown_preview {
+        Some(p) if p.preview == buffer_id => p
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, spans)| spans.iter().any(|s| s.style == MdStyle::Code))
+            .map(|(i, _)| i)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![Command::new(
+        "markdown-toggle-preview",
+        markdown_toggle_preview,
+    )]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_to_lines_styles_basic_document() {
+        let doc = "# Title\n\nplain **bold** text\n- item\n```\ncode here\n```\n";
+        let lines = markdown_to_lines(doc);
+
+        assert_eq!(lines[0], vec![MdSpan::new("Title", MdStyle::Heading)]);
+        assert_eq!(lines[1], Vec::<MdSpan>::new());
+        assert_eq!(
+            lines[2],
+            vec![
+                MdSpan::new("plain ", MdStyle::Text),
+                MdSpan::new("bold", MdStyle::Bold),
+                MdSpan::new(" text", MdStyle::Text),
+            ]
+        );
+        assert_eq!(
+            lines[3],
+            vec![
+                MdSpan::new("• ", MdStyle::Bullet),
+                MdSpan::new("item", MdStyle::Text),
+            ]
+        );
+        assert_eq!(lines[4], vec![MdSpan::new("code here", MdStyle::Code)]);
+        assert_eq!(lines.len(), 5);
+    }
+
+    #[test]
+    fn test_unmatched_bold_marker_is_literal() {
+        let lines = markdown_to_lines("oops ** here\n");
+        assert_eq!(lines[0], vec![MdSpan::new("oops ** here", MdStyle::Text)]);
+    }
+}
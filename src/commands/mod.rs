@@ -1,9 +1,21 @@
 pub mod buffer_cmds;
 pub mod editing;
 pub mod file_cmds;
+pub mod grep;
+pub mod help;
+pub mod info;
 pub mod kill_yank;
+pub mod macro_cmds;
+pub mod markdown;
 pub mod motion;
+pub mod outline;
+pub mod project;
+pub mod register_cmds;
 pub mod registry;
+pub mod search;
+pub mod shell;
+pub mod theme_cmds;
+pub mod whitespace;
 pub mod window_cmds;
 
 pub use registry::{Command, CommandContext, CommandRegistry, CommandResult, PrefixArg};
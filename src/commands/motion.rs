@@ -1,5 +1,8 @@
 use crate::core::position::CharOffset;
-use crate::core::rope_ext::{find_word_boundary_backward, find_word_boundary_forward, RopeExt};
+use crate::core::rope_ext::{
+    find_sexp_backward, find_sexp_forward, find_word_boundary_backward,
+    find_word_boundary_forward, RopeExt,
+};
 use crate::state::EditorState;
 
 use super::registry::{Command, CommandContext, CommandResult};
@@ -30,7 +33,149 @@ pub fn backward_char(state: &mut EditorState, ctx: &CommandContext) -> CommandRe
     Ok(())
 }
 
+/// Maps a character column in a logical line to its (visual row, visual
+/// column) under hard wrapping at `wrap_width` columns. This mirrors how
+/// the terminal renderer lays wrapped lines out on the monospace grid,
+/// so motion commands can share the math without talking to the frontend.
+pub fn visual_row_col(char_col: usize, wrap_width: usize) -> (usize, usize) {
+    if wrap_width == 0 {
+        return (0, char_col);
+    }
+    (char_col / wrap_width, char_col % wrap_width)
+}
+
+/// The current window's wrap width, matching what the terminal
+/// renderer uses to lay out continuation rows.
+fn current_wrap_width(state: &EditorState) -> usize {
+    state
+        .windows
+        .current()
+        .map(|w| {
+            let total_lines = state
+                .buffers
+                .get(w.buffer_id)
+                .map(|b| b.text.total_lines())
+                .unwrap_or(0);
+            w.wrap_width(total_lines)
+        })
+        .unwrap_or(80)
+}
+
+fn move_visual_lines(state: &mut EditorState, down: bool, count: usize) -> CommandResult {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+    let wrap = current_wrap_width(state);
+
+    for _ in 0..count {
+        if let Some(window) = state.windows.current_mut() {
+            let buffer = match state.buffers.get(buffer_id) {
+                Some(b) => b,
+                None => return Ok(()),
+            };
+
+            for cursor in window.cursors.all_cursors_mut() {
+                let pos = buffer.text.char_to_position(cursor.position);
+                let (row, vis_col) = visual_row_col(pos.column, wrap);
+                let goal_col = cursor.pinned_goal.or(cursor.goal_column).unwrap_or(vis_col);
+                let line_len = buffer.text.line_len_chars(pos.line);
+                let line_start = buffer.text.line_start_char(pos.line);
+
+                if down {
+                    let next_row_start = (row + 1) * wrap;
+                    if next_row_start <= line_len {
+                        // Stay in this logical line, one screen row down
+                        let new_col = (next_row_start + goal_col).min(line_len);
+                        cursor.position = CharOffset(line_start.0 + new_col);
+                    } else if pos.line + 1 < buffer.text.total_lines() {
+                        let next_line = pos.line + 1;
+                        let next_len = buffer.text.line_len_chars(next_line);
+                        let next_start = buffer.text.line_start_char(next_line);
+                        cursor.position = CharOffset(next_start.0 + goal_col.min(next_len));
+                    }
+                } else if row > 0 {
+                    // One screen row up within this logical line
+                    let new_col = (row - 1) * wrap + goal_col;
+                    cursor.position = CharOffset(line_start.0 + new_col.min(line_len));
+                } else if pos.line > 0 {
+                    let prev_line = pos.line - 1;
+                    let prev_len = buffer.text.line_len_chars(prev_line);
+                    let prev_start = buffer.text.line_start_char(prev_line);
+                    let (last_row, _) = visual_row_col(prev_len, wrap);
+                    let new_col = (last_row * wrap + goal_col).min(prev_len);
+                    cursor.position = CharOffset(prev_start.0 + new_col);
+                }
+                cursor.goal_column = Some(goal_col);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn beginning_of_visual_line(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if !state.visual_line_mode {
+        return move_beginning_of_line(state, _ctx);
+    }
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+    let wrap = current_wrap_width(state);
+
+    if let Some(window) = state.windows.current_mut() {
+        let buffer = match state.buffers.get(buffer_id) {
+            Some(b) => b,
+            None => return Ok(()),
+        };
+
+        for cursor in window.cursors.all_cursors_mut() {
+            let pos = buffer.text.char_to_position(cursor.position);
+            let (row, _) = visual_row_col(pos.column, wrap);
+            let line_start = buffer.text.line_start_char(pos.line);
+            cursor.position = CharOffset(line_start.0 + row * wrap);
+            cursor.goal_column = Some(0);
+        }
+    }
+    Ok(())
+}
+
+pub fn end_of_visual_line(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if !state.visual_line_mode {
+        return move_end_of_line(state, _ctx);
+    }
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+    let wrap = current_wrap_width(state);
+
+    if let Some(window) = state.windows.current_mut() {
+        let buffer = match state.buffers.get(buffer_id) {
+            Some(b) => b,
+            None => return Ok(()),
+        };
+
+        for cursor in window.cursors.all_cursors_mut() {
+            let pos = buffer.text.char_to_position(cursor.position);
+            let (row, _) = visual_row_col(pos.column, wrap);
+            let line_len = buffer.text.line_len_chars(pos.line);
+            let line_start = buffer.text.line_start_char(pos.line);
+            let new_col = ((row + 1) * wrap).min(line_len);
+            cursor.position = CharOffset(line_start.0 + new_col);
+            cursor.goal_column = None;
+        }
+    }
+    Ok(())
+}
+
 pub fn next_line(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    if state.visual_line_mode {
+        return move_visual_lines(state, true, ctx.repeat_count());
+    }
+
     let count = ctx.repeat_count();
     let buffer_id = match state.windows.current() {
         Some(w) => w.buffer_id,
@@ -46,7 +191,7 @@ pub fn next_line(state: &mut EditorState, ctx: &CommandContext) -> CommandResult
 
             for cursor in window.cursors.all_cursors_mut() {
                 let pos = buffer.text.char_to_position(cursor.position);
-                let goal_col = cursor.goal_column.unwrap_or(pos.column);
+                let goal_col = cursor.pinned_goal.or(cursor.goal_column).unwrap_or(pos.column);
                 let total_lines = buffer.text.total_lines();
 
                 if pos.line + 1 < total_lines {
@@ -64,6 +209,10 @@ pub fn next_line(state: &mut EditorState, ctx: &CommandContext) -> CommandResult
 }
 
 pub fn previous_line(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    if state.visual_line_mode {
+        return move_visual_lines(state, false, ctx.repeat_count());
+    }
+
     let count = ctx.repeat_count();
     let buffer_id = match state.windows.current() {
         Some(w) => w.buffer_id,
@@ -79,7 +228,7 @@ pub fn previous_line(state: &mut EditorState, ctx: &CommandContext) -> CommandRe
 
             for cursor in window.cursors.all_cursors_mut() {
                 let pos = buffer.text.char_to_position(cursor.position);
-                let goal_col = cursor.goal_column.unwrap_or(pos.column);
+                let goal_col = cursor.pinned_goal.or(cursor.goal_column).unwrap_or(pos.column);
 
                 if pos.line > 0 {
                     let prev_line = pos.line - 1;
@@ -95,6 +244,45 @@ pub fn previous_line(state: &mut EditorState, ctx: &CommandContext) -> CommandRe
     Ok(())
 }
 
+/// Pins the vertical-motion goal column at each cursor's current
+/// column, so short intervening lines don't shrink it. A prefix arg
+/// clears the pin and restores the transient behavior.
+pub fn set_goal_column(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    if ctx.prefix_arg.is_set() {
+        if let Some(window) = state.windows.current_mut() {
+            for cursor in window.cursors.all_cursors_mut() {
+                cursor.pinned_goal = None;
+            }
+        }
+        state.message = Some("No goal column".to_string());
+        return Ok(());
+    }
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let mut primary_column = 0;
+    if let Some(window) = state.windows.current_mut() {
+        let buffer = match state.buffers.get(buffer_id) {
+            Some(b) => b,
+            None => return Ok(()),
+        };
+
+        for cursor in window.cursors.all_cursors_mut() {
+            let column = buffer.text.char_to_position(cursor.position).column;
+            cursor.pinned_goal = Some(column);
+        }
+        primary_column = buffer
+            .text
+            .char_to_position(window.cursors.primary.position)
+            .column;
+    }
+    state.message = Some(format!("Goal column set to {}", primary_column));
+    Ok(())
+}
+
 pub fn move_beginning_of_line(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
     let buffer_id = match state.windows.current() {
         Some(w) => w.buffer_id,
@@ -307,7 +495,7 @@ pub fn scroll_down_command(state: &mut EditorState, ctx: &CommandContext) -> Com
     Ok(())
 }
 
-pub fn recenter_top_bottom(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+pub fn recenter_top_bottom(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
     let buffer_id = match state.windows.current() {
         Some(w) => w.buffer_id,
         None => return Ok(()),
@@ -326,8 +514,66 @@ pub fn recenter_top_bottom(state: &mut EditorState, _ctx: &CommandContext) -> Co
     };
 
     if let Some(window) = state.windows.current_mut() {
-        let half_height = (window.height as usize) / 2;
-        window.scroll_line = cursor_line.saturating_sub(half_height);
+        // Repeats cycle center -> top -> bottom like Emacs; any other
+        // command in between starts over at center.
+        let cycle = if ctx.last_command == Some("recenter-top-bottom") {
+            (window.recenter_cycle + 1) % 3
+        } else {
+            0
+        };
+        window.recenter_cycle = cycle;
+
+        let text_rows = window.height.saturating_sub(1) as usize;
+        window.scroll_line = match cycle {
+            0 => cursor_line.saturating_sub(text_rows / 2),
+            1 => cursor_line,
+            _ => cursor_line.saturating_sub(text_rows.saturating_sub(1)),
+        };
+    }
+    Ok(())
+}
+
+/// M-r: moves point to the window's first visible line, then the last,
+/// then the middle on successive presses, without scrolling.
+pub fn move_to_window_line_top_bottom(
+    state: &mut EditorState,
+    ctx: &CommandContext,
+) -> CommandResult {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let target = {
+        let window = state.windows.current().unwrap();
+        let buffer = match state.buffers.get(buffer_id) {
+            Some(b) => b,
+            None => return Ok(()),
+        };
+
+        let cycle = if ctx.last_command == Some("move-to-window-line-top-bottom") {
+            (window.window_line_cycle + 1) % 3
+        } else {
+            0
+        };
+
+        let text_rows = (window.height.saturating_sub(1) as usize).max(1);
+        let last_line = buffer.text.total_lines().saturating_sub(1);
+        let bottom = (window.scroll_line + text_rows - 1).min(last_line);
+        let target_line = match cycle {
+            0 => window.scroll_line.min(last_line),
+            1 => bottom,
+            _ => (window.scroll_line + (bottom - window.scroll_line) / 2).min(last_line),
+        };
+        (cycle, buffer.text.line_start_char(target_line))
+    };
+
+    if let Some(window) = state.windows.current_mut() {
+        window.window_line_cycle = target.0;
+        for cursor in window.cursors.all_cursors_mut() {
+            cursor.position = target.1;
+            cursor.goal_column = Some(0);
+        }
     }
     Ok(())
 }
@@ -336,18 +582,26 @@ pub fn goto_line(state: &mut EditorState, ctx: &CommandContext) -> CommandResult
     let line = if ctx.prefix_arg.is_set() {
         (ctx.count() - 1).max(0) as usize
     } else {
+        state.start_minibuffer_prompt("Goto line: ", "goto-line-complete");
         return Ok(());
     };
 
+    goto_line_number(state, line);
+    Ok(())
+}
+
+/// Moves every cursor to the start of `line` (zero-based, clamped to
+/// the buffer). Shared by the prefix-arg and minibuffer paths.
+pub fn goto_line_number(state: &mut EditorState, line: usize) {
     let buffer_id = match state.windows.current() {
         Some(w) => w.buffer_id,
-        None => return Ok(()),
+        None => return,
     };
 
     if let Some(window) = state.windows.current_mut() {
         let buffer = match state.buffers.get(buffer_id) {
             Some(b) => b,
-            None => return Ok(()),
+            None => return,
         };
         let max_line = buffer.text.total_lines().saturating_sub(1);
         let target_line = line.min(max_line);
@@ -358,6 +612,53 @@ pub fn goto_line(state: &mut EditorState, ctx: &CommandContext) -> CommandResult
             cursor.goal_column = None;
         }
     }
+}
+
+pub fn forward_sexp(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    sexp_motion(state, ctx.repeat_count(), true)
+}
+
+pub fn backward_sexp(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    sexp_motion(state, ctx.repeat_count(), false)
+}
+
+/// Moves every cursor over `count` balanced expressions, stopping early
+/// with a message when the brackets don't balance.
+fn sexp_motion(state: &mut EditorState, count: usize, forward: bool) -> CommandResult {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let mut balanced = true;
+    if let Some(window) = state.windows.current_mut() {
+        let buffer = match state.buffers.get(buffer_id) {
+            Some(b) => b,
+            None => return Ok(()),
+        };
+
+        for cursor in window.cursors.all_cursors_mut() {
+            for _ in 0..count {
+                let target = if forward {
+                    find_sexp_forward(&buffer.text, cursor.position)
+                } else {
+                    find_sexp_backward(&buffer.text, cursor.position)
+                };
+                match target {
+                    Some(pos) => cursor.position = pos,
+                    None => {
+                        balanced = false;
+                        break;
+                    }
+                }
+            }
+            cursor.goal_column = None;
+        }
+    }
+
+    if !balanced {
+        state.message = Some("Unbalanced expression".to_string());
+    }
     Ok(())
 }
 
@@ -431,8 +732,11 @@ pub fn all_commands() -> Vec<Command> {
         Command::motion("backward-char", backward_char),
         Command::motion("next-line", next_line),
         Command::motion("previous-line", previous_line),
+        Command::motion("set-goal-column", set_goal_column),
         Command::motion("move-beginning-of-line", move_beginning_of_line),
         Command::motion("move-end-of-line", move_end_of_line),
+        Command::motion("beginning-of-visual-line", beginning_of_visual_line),
+        Command::motion("end-of-visual-line", end_of_visual_line),
         Command::motion("beginning-of-buffer", beginning_of_buffer),
         Command::motion("end-of-buffer", end_of_buffer),
         Command::motion("forward-word", forward_word),
@@ -440,7 +744,13 @@ pub fn all_commands() -> Vec<Command> {
         Command::motion("scroll-up-command", scroll_up_command),
         Command::motion("scroll-down-command", scroll_down_command),
         Command::motion("recenter-top-bottom", recenter_top_bottom),
+        Command::motion(
+            "move-to-window-line-top-bottom",
+            move_to_window_line_top_bottom,
+        ),
         Command::motion("goto-line", goto_line),
+        Command::motion("forward-sexp", forward_sexp),
+        Command::motion("backward-sexp", backward_sexp),
         Command::mark("forward-char-shift", forward_char_shift),
         Command::mark("backward-char-shift", backward_char_shift),
         Command::mark("next-line-shift", next_line_shift),
@@ -490,6 +800,192 @@ mod tests {
         assert!(pos.0 >= 6);
     }
 
+    #[test]
+    fn test_recenter_cycles_center_top_bottom() {
+        let content = (0..200).map(|i| format!("line {}\n", i)).collect::<String>();
+        let mut state = make_state(&content);
+        state.set_dimensions(80, 24);
+
+        // Put point on line 100 via C-l's own dispatch path so
+        // last-command tracking kicks in on repeats.
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .set_position(CharOffset(content.find("line 100").unwrap()));
+
+        // 24 rows minus the minibuffer leave a 23-row window, whose
+        // bottom row is the modeline.
+        let text_rows = 22usize;
+        state.run_command("recenter-top-bottom");
+        assert_eq!(
+            state.windows.current().unwrap().scroll_line,
+            100 - text_rows / 2
+        );
+
+        state.run_command("recenter-top-bottom");
+        assert_eq!(state.windows.current().unwrap().scroll_line, 100);
+
+        state.run_command("recenter-top-bottom");
+        assert_eq!(
+            state.windows.current().unwrap().scroll_line,
+            100 - (text_rows - 1)
+        );
+
+        // A fourth C-l wraps back to center; an intervening command
+        // resets the cycle too.
+        state.run_command("recenter-top-bottom");
+        assert_eq!(
+            state.windows.current().unwrap().scroll_line,
+            100 - text_rows / 2
+        );
+
+        state.run_command("recenter-top-bottom");
+        state.run_command("forward-char");
+        state.run_command("recenter-top-bottom");
+        assert_eq!(
+            state.windows.current().unwrap().scroll_line,
+            100 - text_rows / 2
+        );
+    }
+
+    #[test]
+    fn test_move_to_window_line_cycles_top_bottom_middle() {
+        use crate::core::rope_ext::RopeExt;
+
+        let content = (0..200).map(|i| format!("line {}\n", i)).collect::<String>();
+        let mut state = make_state(&content);
+        state.set_dimensions(80, 24);
+        state.windows.current_mut().unwrap().scroll_line = 50;
+
+        let line_of = |state: &EditorState| {
+            let window = state.windows.current().unwrap();
+            let buffer = state.buffers.get(window.buffer_id).unwrap();
+            buffer
+                .text
+                .char_to_position(window.cursors.primary.position)
+                .line
+        };
+
+        // 22 text rows: lines 50..=71 are visible.
+        state.run_command("move-to-window-line-top-bottom");
+        assert_eq!(line_of(&state), 50);
+
+        state.run_command("move-to-window-line-top-bottom");
+        assert_eq!(line_of(&state), 71);
+
+        state.run_command("move-to-window-line-top-bottom");
+        assert_eq!(line_of(&state), 60);
+
+        // The view itself never moved.
+        assert_eq!(state.windows.current().unwrap().scroll_line, 50);
+
+        // An intervening command restarts the cycle at the top.
+        state.run_command("forward-char");
+        state.run_command("move-to-window-line-top-bottom");
+        assert_eq!(line_of(&state), 50);
+    }
+
+    #[test]
+    fn test_visual_row_col_maps_wrapped_offsets() {
+        assert_eq!(visual_row_col(3, 10), (0, 3));
+        assert_eq!(visual_row_col(10, 10), (1, 0));
+        assert_eq!(visual_row_col(25, 10), (2, 5));
+        assert_eq!(visual_row_col(7, 0), (0, 7));
+    }
+
+    #[test]
+    fn test_visual_line_edge_motion() {
+        let mut state = make_state("abcdefghijklmnop\nshort\n");
+        state.set_dimensions(10, 24);
+        state.visual_line_mode = true;
+        let ctx = CommandContext::new();
+
+        // Point on the first screen row of the wrapped line; the last
+        // column is reserved for the wrap indicator, so rows hold 9.
+        state.windows.current_mut().unwrap().cursors.primary.position = CharOffset(3);
+        end_of_visual_line(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(9)
+        );
+
+        state.windows.current_mut().unwrap().cursors.primary.position = CharOffset(12);
+        beginning_of_visual_line(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(9)
+        );
+    }
+
+    #[test]
+    fn test_next_line_moves_by_visual_row_when_wrapping() {
+        let mut state = make_state("abcdefghijklmnop\nshort\n");
+        state.set_dimensions(10, 24);
+        state.visual_line_mode = true;
+        let ctx = CommandContext::new();
+
+        state.windows.current_mut().unwrap().cursors.primary.position = CharOffset(2);
+        next_line(&mut state, &ctx).unwrap();
+        // Down one screen row (9 columns wide) within the same logical line
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(11)
+        );
+
+        next_line(&mut state, &ctx).unwrap();
+        // Off the end of the wrapped line and into the next logical line
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(19)
+        );
+
+        previous_line(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(11)
+        );
+    }
+
+    #[test]
+    fn test_set_goal_column_survives_short_lines() {
+        use crate::commands::registry::PrefixArg;
+
+        let mut state = make_state("abcdef\nab\nabcdef\n");
+        let ctx = CommandContext::new();
+
+        state.windows.current_mut().unwrap().cursors.primary.position = CharOffset(4);
+        set_goal_column(&mut state, &ctx).unwrap();
+        assert_eq!(state.message.as_deref(), Some("Goal column set to 4"));
+
+        // Horizontal motion clears the transient goal but not the pin
+        backward_char(&mut state, &ctx).unwrap();
+        next_line(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(9)
+        );
+        next_line(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(14)
+        );
+
+        let clear = CommandContext::with_prefix(PrefixArg::Universal(4));
+        set_goal_column(&mut state, &clear).unwrap();
+        assert_eq!(state.message.as_deref(), Some("No goal column"));
+        assert!(state
+            .windows
+            .current()
+            .unwrap()
+            .cursors
+            .primary
+            .pinned_goal
+            .is_none());
+    }
+
     #[test]
     fn test_word_movement() {
         let mut state = make_state("hello world foo");
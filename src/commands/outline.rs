@@ -0,0 +1,234 @@
+use crate::core::position::CharOffset;
+use crate::core::{Buffer, BufferId};
+use crate::keybinding::key::{Key, Modifiers};
+use crate::keybinding::KeyEvent;
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandError, CommandResult};
+
+const OUTLINE_BUFFER: &str = "*Outline*";
+
+/// One heading in the outline: its depth, display title and where it
+/// starts in the source buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutlineEntry {
+    pub level: usize,
+    pub title: String,
+    pub offset: CharOffset,
+}
+
+/// Headings extracted from the buffer being outlined, plus enough
+/// context to jump back to it.
+pub struct OutlineState {
+    pub source: BufferId,
+    pub entries: Vec<OutlineEntry>,
+}
+
+/// Extracts markdown `#` headings (and `// ===`-style code section
+/// comments) with their level and char offset of the heading line.
+pub fn extract_headings(source: &str) -> Vec<OutlineEntry> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+
+    for line in source.split_inclusive('\n') {
+        let trimmed = line.trim_end_matches('\n').trim_start();
+        let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+
+        if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+            entries.push(OutlineEntry {
+                level: hashes,
+                title: trimmed[hashes + 1..].trim().to_string(),
+                offset: CharOffset(offset),
+            });
+        } else if let Some(rest) = trimmed
+            .strip_prefix("// ===")
+            .or_else(|| trimmed.strip_prefix(";; ==="))
+        {
+            let title = rest.trim_matches(['=', ' ']).to_string();
+            if !title.is_empty() {
+                entries.push(OutlineEntry {
+                    level: 1,
+                    title,
+                    offset: CharOffset(offset),
+                });
+            }
+        }
+
+        offset += line.chars().count();
+    }
+
+    entries
+}
+
+pub fn outline(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let source = match state.windows.current_buffer_id() {
+        Some(id) => id,
+        None => return Ok(()),
+    };
+    let source_text = match state.buffers.get(source) {
+        Some(b) => b.text.to_string(),
+        None => return Ok(()),
+    };
+
+    let entries = extract_headings(&source_text);
+    if entries.is_empty() {
+        return Err(CommandError::Other("No headings found".to_string()));
+    }
+
+    if let Some(id) = state.buffers.find_by_name(OUTLINE_BUFFER) {
+        state.buffers.kill(id);
+    }
+
+    let mut contents = String::new();
+    for entry in &entries {
+        for _ in 1..entry.level {
+            contents.push_str("  ");
+        }
+        contents.push_str(&entry.title);
+        contents.push('\n');
+    }
+
+    let mut buffer = Buffer::from_string(OUTLINE_BUFFER, &contents);
+    buffer.read_only = true;
+    let id = state.buffers.add(buffer);
+    state.buffers.set_current(id);
+    state.windows.set_current_buffer(id);
+
+    state.outline = Some(OutlineState { source, entries });
+    state.message = Some("RET to jump to heading, C-g to quit".to_string());
+    Ok(())
+}
+
+/// Intercepts RET (jump to the heading at point) and C-g/Escape (quit)
+/// while the outline buffer is current. Returns true when consumed.
+pub fn handle_outline_key(state: &mut EditorState, key: KeyEvent) -> bool {
+    if !in_outline(state) {
+        return false;
+    }
+
+    match (key.key, key.modifiers) {
+        (Key::Enter, Modifiers::NONE) => {
+            let target = entry_at_point(state);
+            let outline = state.outline.take();
+            close_outline(state, outline.as_ref().map(|o| o.source));
+            if let Some(offset) = target {
+                if let Some(window) = state.windows.current_mut() {
+                    window.cursors.remove_secondary_cursors();
+                    window.cursors.primary.position = offset;
+                    window.cursors.primary.clear_mark();
+                }
+            }
+            true
+        }
+        (Key::Char('g'), Modifiers::CTRL) | (Key::Escape, _) => {
+            let outline = state.outline.take();
+            close_outline(state, outline.as_ref().map(|o| o.source));
+            state.message = Some("Quit".to_string());
+            true
+        }
+        _ => false,
+    }
+}
+
+fn in_outline(state: &EditorState) -> bool {
+    state.outline.is_some()
+        && state
+            .current_buffer()
+            .map(|b| b.name == OUTLINE_BUFFER)
+            .unwrap_or(false)
+}
+
+/// The source offset of the heading on the outline line at point.
+fn entry_at_point(state: &EditorState) -> Option<CharOffset> {
+    use crate::core::rope_ext::RopeExt;
+
+    let window = state.current_window()?;
+    let buffer = state.buffers.get(window.buffer_id)?;
+    let line = buffer
+        .text
+        .char_to_position(window.cursors.primary.position)
+        .line;
+    state
+        .outline
+        .as_ref()
+        .and_then(|o| o.entries.get(line))
+        .map(|e| e.offset)
+}
+
+fn close_outline(state: &mut EditorState, source: Option<BufferId>) {
+    let source = source.filter(|id| state.buffers.get(*id).is_some());
+
+    if let Some(id) = source {
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+    }
+
+    if let Some(id) = state.buffers.find_by_name(OUTLINE_BUFFER) {
+        state.buffers.kill(id);
+    }
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![Command::new("outline", outline)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test.md", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    #[test]
+    fn test_extract_headings_levels_and_offsets() {
+        let doc = "# Top\ntext\n## Middle\nmore\n### Deep\n";
+        let entries = extract_headings(doc);
+
+        assert_eq!(
+            entries,
+            vec![
+                OutlineEntry {
+                    level: 1,
+                    title: "Top".to_string(),
+                    offset: CharOffset(0),
+                },
+                OutlineEntry {
+                    level: 2,
+                    title: "Middle".to_string(),
+                    offset: CharOffset(11),
+                },
+                OutlineEntry {
+                    level: 3,
+                    title: "Deep".to_string(),
+                    offset: CharOffset(26),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ret_jumps_to_heading_in_source() {
+        let mut state = make_state("# Top\ntext\n## Middle\n");
+        let ctx = CommandContext::new();
+
+        outline(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().name, OUTLINE_BUFFER);
+
+        state.handle_key(KeyEvent::ctrl('n'));
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+
+        assert_eq!(state.current_buffer().unwrap().name, "test.md");
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(11)
+        );
+        assert!(state.outline.is_none());
+        assert!(state.buffers.find_by_name(OUTLINE_BUFFER).is_none());
+    }
+}
@@ -0,0 +1,179 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandError, CommandResult};
+
+/// Markers that identify a project root, checked at each ancestor.
+const ROOT_MARKERS: &[&str] = &[".git", "Cargo.toml"];
+
+/// Directory recursion limit for the project file listing.
+const MAX_DEPTH: usize = 16;
+
+/// The nearest ancestor of `path` (the directory itself, or the parent
+/// for files) containing a root marker.
+pub fn find_root(path: &Path) -> Option<PathBuf> {
+    let start = if path.is_dir() { path } else { path.parent()? };
+    start
+        .ancestors()
+        .find(|dir| ROOT_MARKERS.iter().any(|m| dir.join(m).exists()))
+        .map(Path::to_path_buf)
+}
+
+/// The project root for the current buffer's file, falling back to the
+/// working directory for buffers without one.
+pub fn current_root(state: &EditorState) -> Option<PathBuf> {
+    match state.current_buffer().and_then(|b| b.file_path.clone()) {
+        Some(path) => find_root(&path),
+        None => std::env::current_dir().ok().and_then(|d| find_root(&d)),
+    }
+}
+
+/// Lines from the root `.gitignore`, normalized to bare names.
+fn load_gitignore(root: &Path) -> Vec<String> {
+    fs::read_to_string(root.join(".gitignore"))
+        .map(|s| {
+            s.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(|l| l.trim_start_matches('/').trim_end_matches('/').to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Minimal `.gitignore` matching: exact component names and `*.ext`
+/// suffix patterns.
+fn is_ignored(name: &str, ignores: &[String]) -> bool {
+    ignores.iter().any(|pat| match pat.strip_prefix('*') {
+        Some(suffix) => name.ends_with(suffix),
+        None => name == pat,
+    })
+}
+
+fn walk(root: &Path, dir: &Path, ignores: &[String], files: &mut Vec<String>, depth: usize) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let entries = match fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == ".git" || is_ignored(&name, ignores) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            walk(root, &path, ignores, files, depth + 1);
+        } else if let Ok(rel) = path.strip_prefix(root) {
+            files.push(rel.to_string_lossy().to_string());
+        }
+    }
+}
+
+/// Files under `root` as sorted root-relative paths, skipping `.git`
+/// and anything the root `.gitignore` names.
+pub fn list_files(root: &Path) -> Vec<String> {
+    let ignores = load_gitignore(root);
+    let mut files = Vec::new();
+    walk(root, root, &ignores, &mut files, 0);
+    files.sort();
+    files
+}
+
+pub fn project_root(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    match current_root(state) {
+        Some(root) => {
+            state.message = Some(format!("Project root: {}", root.display()));
+            Ok(())
+        }
+        None => Err(CommandError::Other("No project root found".to_string())),
+    }
+}
+
+pub fn find_file_in_project(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if current_root(state).is_none() {
+        return Err(CommandError::Other("No project root found".to_string()));
+    }
+    state.start_minibuffer_prompt("Find file in project: ", "find-file-in-project");
+    Ok(())
+}
+
+/// Completion source for `find-file-in-project`: root-relative paths.
+pub fn complete_project_file(state: &EditorState, input: &str) -> Vec<String> {
+    let root = match current_root(state) {
+        Some(r) => r,
+        None => return Vec::new(),
+    };
+    list_files(&root)
+        .into_iter()
+        .filter(|f| f.starts_with(input))
+        .collect()
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![
+        Command::new("project-root", project_root),
+        Command::new("find-file-in-project", find_file_in_project),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a throwaway project tree and removes it on drop.
+    struct TempTree {
+        root: PathBuf,
+    }
+
+    impl TempTree {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("enacs-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            Self { root }
+        }
+    }
+
+    impl Drop for TempTree {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    #[test]
+    fn test_find_root_walks_up_to_cargo_toml() {
+        let tree = TempTree::new("root");
+        fs::write(tree.root.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::create_dir_all(tree.root.join("src/deep")).unwrap();
+        fs::write(tree.root.join("src/deep/mod.rs"), "").unwrap();
+
+        assert_eq!(
+            find_root(&tree.root.join("src/deep/mod.rs")),
+            Some(tree.root.clone())
+        );
+        assert_eq!(find_root(&tree.root.join("src/deep")), Some(tree.root.clone()));
+    }
+
+    #[test]
+    fn test_list_files_skips_gitignored_entries() {
+        let tree = TempTree::new("list");
+        fs::write(tree.root.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(tree.root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        fs::create_dir_all(tree.root.join("src")).unwrap();
+        fs::write(tree.root.join("src/main.rs"), "").unwrap();
+        fs::create_dir_all(tree.root.join("target")).unwrap();
+        fs::write(tree.root.join("target/out.o"), "").unwrap();
+        fs::write(tree.root.join("debug.log"), "").unwrap();
+
+        let files = list_files(&tree.root);
+        assert!(files.contains(&"src/main.rs".to_string()));
+        assert!(files.contains(&"Cargo.toml".to_string()));
+        assert!(!files.iter().any(|f| f.starts_with("target")));
+        assert!(!files.iter().any(|f| f.ends_with(".log")));
+    }
+}
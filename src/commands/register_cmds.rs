@@ -0,0 +1,187 @@
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandResult};
+
+pub fn point_to_register(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_char_capture("Point to register: ", "point-to-register");
+    Ok(())
+}
+
+pub fn jump_to_register(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_char_capture("Jump to register: ", "jump-to-register");
+    Ok(())
+}
+
+pub fn copy_to_register(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_char_capture("Copy to register: ", "copy-to-register");
+    Ok(())
+}
+
+pub fn insert_register(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    let callback = if ctx.prefix_arg.is_set() {
+        "insert-register-before"
+    } else {
+        "insert-register"
+    };
+    state.start_char_capture("Insert register: ", callback);
+    Ok(())
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![
+        Command::new("point-to-register", point_to_register),
+        Command::motion("jump-to-register", jump_to_register),
+        Command::new("copy-to-register", copy_to_register),
+        Command::new("insert-register", insert_register),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::position::CharOffset;
+    use crate::core::Buffer;
+    use crate::keybinding::KeyEvent;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    #[test]
+    fn test_point_and_jump_to_register() {
+        let mut state = make_state("hello\nworld\n");
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(8);
+
+        let ctx = CommandContext::new();
+        point_to_register(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::char('a'));
+
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(0);
+
+        jump_to_register(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::char('a'));
+
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            CharOffset(8)
+        );
+    }
+
+    #[test]
+    fn test_jump_to_register_with_killed_buffer() {
+        let mut state = make_state("hello");
+        let buffer_id = state.buffers.current_id().unwrap();
+
+        let ctx = CommandContext::new();
+        point_to_register(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::char('a'));
+
+        state.switch_buffer("*scratch*");
+        state.buffers.kill(buffer_id);
+
+        jump_to_register(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::char('a'));
+
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Register points to a deleted buffer")
+        );
+    }
+
+    #[test]
+    fn test_copy_and_insert_register() {
+        let mut state = make_state("hello world\n");
+        {
+            let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+            cursor.set_mark(CharOffset(0));
+            cursor.position = CharOffset(5);
+        }
+
+        let ctx = CommandContext::new();
+        copy_to_register(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::char('a'));
+
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(12);
+
+        insert_register(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::char('a'));
+
+        let buffer = state.current_buffer().unwrap();
+        assert_eq!(buffer.text.to_string(), "hello world\nhello");
+    }
+
+    #[test]
+    fn test_insert_register_with_prefix_leaves_point_before() {
+        use crate::commands::PrefixArg;
+
+        let mut state = make_state("abc");
+        {
+            let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+            cursor.set_mark(CharOffset(0));
+            cursor.position = CharOffset(3);
+        }
+
+        let ctx = CommandContext::new();
+        copy_to_register(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::char('x'));
+
+        let prefix_ctx = CommandContext::with_prefix(PrefixArg::Universal(4));
+        insert_register(&mut state, &prefix_ctx).unwrap();
+        state.handle_key(KeyEvent::char('x'));
+
+        let cursor = &state.windows.current().unwrap().cursors.primary;
+        assert_eq!(cursor.position, CharOffset(3));
+        assert_eq!(cursor.mark, Some(CharOffset(6)));
+    }
+
+    #[test]
+    fn test_insert_register_from_point_register() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        point_to_register(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::char('p'));
+
+        insert_register(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::char('p'));
+
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Register does not contain text")
+        );
+    }
+
+    #[test]
+    fn test_jump_to_empty_register() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        jump_to_register(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::char('q'));
+
+        assert_eq!(state.message.as_deref(), Some("Register q is empty"));
+    }
+}
@@ -196,6 +196,13 @@ impl CommandRegistry {
         self.commands.keys()
     }
 
+    /// Command names in sorted order, for completion and listings.
+    pub fn sorted_names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.commands.keys().copied().collect();
+        names.sort_unstable();
+        names
+    }
+
     pub fn len(&self) -> usize {
         self.commands.len()
     }
@@ -228,10 +235,58 @@ pub fn build_default_registry() -> CommandRegistry {
         registry.register(cmd);
     }
 
+    for cmd in super::help::all_commands() {
+        registry.register(cmd);
+    }
+
     for cmd in super::window_cmds::all_commands() {
         registry.register(cmd);
     }
 
+    for cmd in super::register_cmds::all_commands() {
+        registry.register(cmd);
+    }
+
+    for cmd in super::macro_cmds::all_commands() {
+        registry.register(cmd);
+    }
+
+    for cmd in super::markdown::all_commands() {
+        registry.register(cmd);
+    }
+
+    for cmd in super::outline::all_commands() {
+        registry.register(cmd);
+    }
+
+    for cmd in super::theme_cmds::all_commands() {
+        registry.register(cmd);
+    }
+
+    for cmd in super::info::all_commands() {
+        registry.register(cmd);
+    }
+
+    for cmd in super::whitespace::all_commands() {
+        registry.register(cmd);
+    }
+
+    for cmd in super::search::all_commands() {
+        registry.register(cmd);
+    }
+
+    for cmd in super::grep::all_commands() {
+        registry.register(cmd);
+    }
+
+    for cmd in super::project::all_commands() {
+        registry.register(cmd);
+    }
+
+    for cmd in super::shell::all_commands() {
+        registry.register(cmd);
+    }
+
     registry
 }
 
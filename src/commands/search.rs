@@ -0,0 +1,422 @@
+use ropey::Rope;
+
+use crate::core::position::CharOffset;
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandResult};
+
+/// A search query: a literal string, or — behind the `regexp` feature —
+/// a compiled regular expression. Shared by the search commands,
+/// `replace-regexp`, and `keep-lines`/`flush-lines`.
+#[derive(Debug)]
+pub enum SearchPattern {
+    Literal(String),
+    #[cfg(feature = "regexp")]
+    Regex(regex::Regex),
+}
+
+impl SearchPattern {
+    /// Compiles `input` as a regexp when `regexp` is set, reporting the
+    /// compile error instead of panicking; otherwise wraps it as a
+    /// literal.
+    pub fn parse(input: &str, regexp: bool) -> Result<Self, String> {
+        if !regexp {
+            return Ok(SearchPattern::Literal(input.to_string()));
+        }
+        #[cfg(feature = "regexp")]
+        {
+            regex::Regex::new(input)
+                .map(SearchPattern::Regex)
+                .map_err(|e| format!("Invalid regexp: {}", e))
+        }
+        #[cfg(not(feature = "regexp"))]
+        {
+            let _ = input;
+            Err("Regexp support requires the `regexp` feature".to_string())
+        }
+    }
+
+    /// Char ranges of every match whose start lies in `[start, end)` of
+    /// `rope`. Literals stream over the rope's chunks; regexps need
+    /// contiguous text, so that slice is materialized.
+    pub fn find_ranges(
+        &self,
+        rope: &Rope,
+        start: CharOffset,
+        end: CharOffset,
+    ) -> Vec<(CharOffset, CharOffset)> {
+        let len = rope.len_chars();
+        let start = start.0.min(len);
+        let end = end.0.min(len).max(start);
+
+        match self {
+            SearchPattern::Literal(needle) => {
+                let needle_len = needle.chars().count();
+                crate::core::rope_ext::search_all(rope, needle)
+                    .into_iter()
+                    .filter(|m| m.0 >= start && m.0 + needle_len <= end)
+                    .map(|m| (m, CharOffset(m.0 + needle_len)))
+                    .collect()
+            }
+            #[cfg(feature = "regexp")]
+            SearchPattern::Regex(re) => {
+                let text = rope.slice(start..end).to_string();
+                let mut ranges = Vec::new();
+                let mut chars = start;
+                let mut bytes = 0;
+                for m in re.find_iter(&text) {
+                    chars += text[bytes..m.start()].chars().count();
+                    let match_chars = text[m.start()..m.end()].chars().count();
+                    ranges.push((CharOffset(chars), CharOffset(chars + match_chars)));
+                    chars += match_chars;
+                    bytes = m.end();
+                }
+                ranges
+            }
+        }
+    }
+
+    /// Whether `line` contains a match, for line filtering.
+    pub fn matches(&self, line: &str) -> bool {
+        match self {
+            SearchPattern::Literal(needle) => line.contains(needle),
+            #[cfg(feature = "regexp")]
+            SearchPattern::Regex(re) => re.is_match(line),
+        }
+    }
+
+    /// Replaces every match in `haystack`, returning the result and the
+    /// match count. In regexp mode `\N` in `replacement` substitutes
+    /// capture group N and `\\` a literal backslash.
+    pub fn replace_all(&self, haystack: &str, replacement: &str) -> (String, usize) {
+        match self {
+            SearchPattern::Literal(needle) => {
+                if needle.is_empty() {
+                    return (haystack.to_string(), 0);
+                }
+                let count = haystack.matches(needle.as_str()).count();
+                (haystack.replace(needle.as_str(), replacement), count)
+            }
+            #[cfg(feature = "regexp")]
+            SearchPattern::Regex(re) => {
+                let count = re.find_iter(haystack).count();
+                let expanded = convert_backrefs(replacement);
+                (re.replace_all(haystack, expanded.as_str()).into_owned(), count)
+            }
+        }
+    }
+}
+
+/// Rewrites Emacs-style `\N` backreferences into the `${N}` form the
+/// `regex` crate expands, escaping any literal `$`.
+#[cfg(feature = "regexp")]
+fn convert_backrefs(replacement: &str) -> String {
+    let mut out = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '$' => out.push_str("$$"),
+            '\\' => match chars.peek() {
+                Some(d) if d.is_ascii_digit() => {
+                    out.push_str(&format!("${{{}}}", d));
+                    chars.next();
+                }
+                Some('\\') => {
+                    out.push('\\');
+                    chars.next();
+                }
+                _ => out.push('\\'),
+            },
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Finds `pattern` starting from `from`, returning where point should
+/// land: the end of the match searching forward, its start searching
+/// backward (matching Emacs `search-forward`/`search-backward`).
+pub fn find_pattern_in_rope(
+    text: &Rope,
+    pattern: &SearchPattern,
+    from: CharOffset,
+    forward: bool,
+) -> Option<CharOffset> {
+    let ranges = pattern.find_ranges(text, CharOffset(0), CharOffset(text.len_chars()));
+    if forward {
+        ranges.iter().find(|(s, _)| s.0 >= from.0).map(|&(_, e)| e)
+    } else {
+        ranges
+            .iter()
+            .rev()
+            .find(|(_, e)| e.0 <= from.0)
+            .map(|&(s, _)| s)
+    }
+}
+
+/// [`find_pattern_in_rope`] specialized to a literal `query`.
+pub fn find_in_rope(text: &Rope, query: &str, from: CharOffset, forward: bool) -> Option<CharOffset> {
+    if query.is_empty() {
+        return None;
+    }
+    find_pattern_in_rope(text, &SearchPattern::Literal(query.to_string()), from, forward)
+}
+
+/// Non-interactive search for use inside keyboard macros: reads the
+/// query once from the minibuffer and jumps to the next match.
+pub fn search_forward(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Search: ", "search-forward-complete");
+    Ok(())
+}
+
+pub fn search_backward(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Search backward: ", "search-backward-complete");
+    Ok(())
+}
+
+/// Regexp-mode searches. Registered regardless of the `regexp` feature;
+/// without it the callback reports that support is not compiled in.
+pub fn search_forward_regexp(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Regexp search: ", "search-regexp-forward-complete");
+    Ok(())
+}
+
+pub fn search_backward_regexp(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Regexp search backward: ", "search-regexp-backward-complete");
+    Ok(())
+}
+
+/// Replaces every regexp match from point to the end of the buffer,
+/// without querying. The replacement may use `\N` backreferences.
+pub fn replace_regexp(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Replace regexp: ", "replace-regexp-pattern");
+    Ok(())
+}
+
+/// Second half of `replace-regexp`, once both minibuffer reads are in.
+pub fn replace_regexp_from_point(state: &mut EditorState, pattern: &str, replacement: &str) {
+    let pattern = match SearchPattern::parse(pattern, true) {
+        Ok(p) => p,
+        Err(e) => {
+            state.message = Some(e);
+            return;
+        }
+    };
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return,
+    };
+    let read_only = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.read_only)
+        .unwrap_or(false);
+    if read_only {
+        state.message = Some("Buffer is read-only".to_string());
+        return;
+    }
+
+    let (from, end, new_text, count) = {
+        let window = state.windows.current().unwrap();
+        let buffer = state.buffers.get(buffer_id).unwrap();
+        let from = window.cursors.primary.position;
+        let end = CharOffset(buffer.text.len_chars());
+        let old = buffer.slice(from, end);
+        let (new_text, count) = pattern.replace_all(&old, replacement);
+        (from, end, new_text, count)
+    };
+
+    if count == 0 {
+        state.message = Some("No matches".to_string());
+        return;
+    }
+
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.replace_region(cursors, from, end, &new_text);
+    }
+    if let Some(window) = state.windows.current_mut() {
+        window.cursors.deactivate_all_marks();
+    }
+    state.message = Some(format!("Replaced {} occurrence(s)", count));
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![
+        Command::motion("search-forward", search_forward),
+        Command::motion("search-backward", search_backward),
+        Command::motion("search-forward-regexp", search_forward_regexp),
+        Command::motion("search-backward-regexp", search_backward_regexp),
+        Command::new("replace-regexp", replace_regexp),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Buffer;
+    use crate::keybinding::key::{Key, Modifiers};
+    use crate::keybinding::KeyEvent;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    fn type_query(state: &mut EditorState, query: &str) {
+        for c in query.chars() {
+            state.handle_key(KeyEvent::char(c));
+        }
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+    }
+
+    #[test]
+    fn test_search_forward_moves_to_match_end() {
+        let mut state = make_state("hello world hello");
+        let ctx = CommandContext::new();
+
+        search_forward(&mut state, &ctx).unwrap();
+        type_query(&mut state, "world");
+
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(11)
+        );
+        assert!(!state.search_failed);
+        assert_eq!(state.last_search.as_deref(), Some("world"));
+    }
+
+    #[test]
+    fn test_search_forward_failure_sets_flag() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        search_forward(&mut state, &ctx).unwrap();
+        type_query(&mut state, "zzz");
+
+        assert!(state.search_failed);
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(0)
+        );
+        assert_eq!(state.message.as_deref(), Some("Search failed: \"zzz\""));
+    }
+
+    #[test]
+    fn test_search_backward_moves_to_match_start() {
+        let mut state = make_state("hello world hello");
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(17);
+
+        let ctx = CommandContext::new();
+        search_backward(&mut state, &ctx).unwrap();
+        type_query(&mut state, "hello");
+
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(12)
+        );
+    }
+
+    #[test]
+    fn test_literal_pattern_ranges_and_line_matching() {
+        let rope = Rope::from_str("abc abc");
+        let pattern = SearchPattern::parse("abc", false).unwrap();
+        assert_eq!(
+            pattern.find_ranges(&rope, CharOffset(0), CharOffset(7)),
+            vec![(CharOffset(0), CharOffset(3)), (CharOffset(4), CharOffset(7))]
+        );
+        assert!(pattern.matches("xxabcxx"));
+        assert!(!pattern.matches("xyz"));
+    }
+
+    #[cfg(feature = "regexp")]
+    #[test]
+    fn test_regexp_pattern_finds_ranges() {
+        let rope = Rope::from_str("foo fe fooo");
+        let pattern = SearchPattern::parse("fo+", true).unwrap();
+        assert_eq!(
+            pattern.find_ranges(&rope, CharOffset(0), CharOffset(11)),
+            vec![(CharOffset(0), CharOffset(3)), (CharOffset(7), CharOffset(11))]
+        );
+    }
+
+    #[cfg(feature = "regexp")]
+    #[test]
+    fn test_search_forward_regexp_moves_to_match_end() {
+        let mut state = make_state("alpha beta42 gamma");
+        let ctx = CommandContext::new();
+
+        search_forward_regexp(&mut state, &ctx).unwrap();
+        type_query(&mut state, r"\d+");
+
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(12)
+        );
+    }
+
+    #[cfg(feature = "regexp")]
+    #[test]
+    fn test_invalid_regexp_reports_compile_error() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        search_forward_regexp(&mut state, &ctx).unwrap();
+        type_query(&mut state, "foo(");
+
+        assert!(state
+            .message
+            .as_deref()
+            .unwrap()
+            .starts_with("Invalid regexp:"));
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(0)
+        );
+    }
+
+    #[cfg(feature = "regexp")]
+    #[test]
+    fn test_replace_regexp_supports_backreferences() {
+        let mut state = make_state("one1 two2 three3");
+
+        replace_regexp_from_point(&mut state, r"([a-z]+)(\d)", r"\2-\1");
+
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "1-one 2-two 3-three"
+        );
+        assert_eq!(state.message.as_deref(), Some("Replaced 3 occurrence(s)"));
+    }
+
+    #[test]
+    fn test_empty_query_reuses_last_search() {
+        let mut state = make_state("abc abc abc");
+        let ctx = CommandContext::new();
+
+        search_forward(&mut state, &ctx).unwrap();
+        type_query(&mut state, "abc");
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(3)
+        );
+
+        search_forward(&mut state, &ctx).unwrap();
+        type_query(&mut state, "");
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(7)
+        );
+    }
+}
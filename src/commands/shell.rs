@@ -0,0 +1,434 @@
+use std::io::Write;
+use std::process::{Command as ProcessCommand, Stdio};
+
+use crate::core::position::CharOffset;
+use crate::core::Buffer;
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandError, CommandResult};
+
+pub const OUTPUT_BUFFER: &str = "*Shell Command Output*";
+
+/// Output beyond this is dropped so a runaway command can't flood the
+/// buffer.
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Trims `text` to at most `max` bytes on a character boundary,
+/// returning whether anything was cut.
+fn truncate_output(text: &mut String, max: usize) -> bool {
+    if text.len() <= max {
+        return false;
+    }
+    let mut cut = max;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    text.truncate(cut);
+    true
+}
+
+/// Runs `command` through `sh -c` and returns its stdout, truncated to
+/// [`MAX_OUTPUT_BYTES`]. A failing exit with no stdout surfaces stderr
+/// as the error.
+pub fn run_shell_command(command: &str) -> Result<String, CommandError> {
+    let output = ProcessCommand::new("sh").arg("-c").arg(command).output()?;
+
+    let mut stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    truncate_output(&mut stdout, MAX_OUTPUT_BYTES);
+
+    if !output.status.success() && stdout.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr.lines().next().unwrap_or("no output").to_string();
+        return Err(CommandError::Other(format!(
+            "Shell command failed: {}",
+            detail
+        )));
+    }
+
+    Ok(stdout)
+}
+
+/// Prompts for a command line to run synchronously. Without a prefix
+/// arg the output lands in [`OUTPUT_BUFFER`]; with one it is inserted
+/// at point instead.
+pub fn shell_command(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    let callback = if ctx.prefix_arg.is_set() {
+        "shell-command-insert-complete"
+    } else {
+        "shell-command-complete"
+    };
+    state.start_minibuffer_prompt("Shell command: ", callback);
+    Ok(())
+}
+
+/// Minibuffer callback for `shell-command`: runs `command`, capturing
+/// stdout and stderr, and reports the exit status in the echo area.
+pub fn run_command_into(state: &mut EditorState, command: &str, at_point: bool) {
+    if command.trim().is_empty() {
+        state.message = Some("Empty shell command".to_string());
+        return;
+    }
+
+    let output = match ProcessCommand::new("sh").arg("-c").arg(command).output() {
+        Ok(output) => output,
+        Err(e) => {
+            state.message = Some(format!("Error running {}: {}", command, e));
+            return;
+        }
+    };
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.stderr.is_empty() {
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+    }
+    if truncate_output(&mut text, MAX_OUTPUT_BYTES) {
+        text.push_str("\n[output truncated]\n");
+    }
+
+    let status = if output.status.success() {
+        "Shell command succeeded".to_string()
+    } else {
+        match output.status.code() {
+            Some(code) => format!("Shell command exited with status {}", code),
+            None => "Shell command killed by signal".to_string(),
+        }
+    };
+
+    if at_point {
+        let buffer_id = match state.windows.current() {
+            Some(w) => w.buffer_id,
+            None => return,
+        };
+        if state.buffers.get(buffer_id).map(|b| b.read_only) != Some(false) {
+            state.message = Some("Buffer is read-only".to_string());
+            return;
+        }
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        let buffer = state.buffers.get_mut(buffer_id).unwrap();
+        buffer.insert_string(cursors, &text);
+    } else if text.is_empty() {
+        state.message = Some(format!("{} (no output)", status));
+        return;
+    } else {
+        if let Some(id) = state.buffers.find_by_name(OUTPUT_BUFFER) {
+            state.buffers.kill(id);
+        }
+        let mut buffer = Buffer::from_string(OUTPUT_BUFFER, &text);
+        buffer.read_only = true;
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+    }
+
+    state.message = Some(status);
+}
+
+/// Prompts for a program to pipe the active region through. With a
+/// prefix arg the region is replaced by the program's stdout; without
+/// one the output goes to [`OUTPUT_BUFFER`] and the region is left
+/// alone.
+pub fn shell_command_on_region(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    let has_region = state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region())
+        .is_some();
+    if !has_region {
+        return Err(CommandError::NoMark);
+    }
+
+    let callback = if ctx.prefix_arg.is_set() {
+        "shell-on-region-replace-complete"
+    } else {
+        "shell-on-region-complete"
+    };
+    state.start_minibuffer_prompt("Shell command on region: ", callback);
+    Ok(())
+}
+
+/// Minibuffer callback for `shell-command-on-region`: feeds the region
+/// to `command`'s stdin. A non-zero exit leaves the buffer unchanged
+/// and reports stderr instead.
+pub fn filter_region_through(state: &mut EditorState, command: &str, replace: bool) {
+    if command.trim().is_empty() {
+        state.message = Some("Empty shell command".to_string());
+        return;
+    }
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return,
+    };
+    let region = state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region());
+    let (input, start, end) = match (region, state.buffers.get(buffer_id)) {
+        (Some((start, end)), Some(buffer)) => (buffer.slice(start, end), start, end),
+        _ => {
+            state.message = Some("No mark set".to_string());
+            return;
+        }
+    };
+
+    let output = ProcessCommand::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            child
+                .stdin
+                .take()
+                .expect("stdin was piped")
+                .write_all(input.as_bytes())?;
+            child.wait_with_output()
+        });
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            state.message = Some(format!("Error running {}: {}", command, e));
+            return;
+        }
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr.lines().next().unwrap_or("no output").to_string();
+        state.message = Some(format!("Shell command failed: {}", detail));
+        return;
+    }
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    if truncate_output(&mut text, MAX_OUTPUT_BYTES) {
+        text.push_str("\n[output truncated]\n");
+    }
+
+    if replace {
+        if state.buffers.get(buffer_id).map(|b| b.read_only) != Some(false) {
+            state.message = Some("Buffer is read-only".to_string());
+            return;
+        }
+        let char_count = text.chars().count();
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        let buffer = state.buffers.get_mut(buffer_id).unwrap();
+        buffer.replace_region(cursors, start, end, &text);
+        cursors.deactivate_all_marks();
+        cursors.primary.position = CharOffset(start.0 + char_count);
+        state.message = Some(format!("Filtered region through {}", command));
+    } else if text.is_empty() {
+        state.message = Some("Shell command succeeded (no output)".to_string());
+    } else {
+        if let Some(id) = state.buffers.find_by_name(OUTPUT_BUFFER) {
+            state.buffers.kill(id);
+        }
+        let mut buffer = Buffer::from_string(OUTPUT_BUFFER, &text);
+        buffer.read_only = true;
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state.message = Some("Shell command succeeded".to_string());
+    }
+}
+
+/// Evaluates the active region as a shell command and inserts its output
+/// right after the region. Unlike `shell-command-on-region`, the region
+/// *is* the command, not its stdin.
+pub fn shell_eval_region_insert(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let (start, end) = state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region())
+        .ok_or(CommandError::NoMark)?;
+
+    let command = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.slice(start, end))
+        .unwrap_or_default();
+    if command.trim().is_empty() {
+        return Err(CommandError::Other("Region is empty".to_string()));
+    }
+
+    let output = run_shell_command(&command)?;
+
+    let insertion = if command.ends_with('\n') {
+        output
+    } else {
+        format!("\n{}", output)
+    };
+
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        if buffer.read_only {
+            return Err(CommandError::ReadOnly);
+        }
+        buffer.replace_region(cursors, end, end, &insertion);
+    }
+
+    if let Some(window) = state.windows.current_mut() {
+        window.cursors.deactivate_all_marks();
+        window.cursors.primary.position = CharOffset(end.0 + insertion.chars().count());
+    }
+
+    Ok(())
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![
+        Command::new("shell-command", shell_command),
+        Command::mark("shell-command-on-region", shell_command_on_region),
+        Command::new("shell-eval-region-insert", shell_eval_region_insert),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Buffer;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    #[test]
+    fn test_shell_command_output_goes_to_its_buffer() {
+        let mut state = make_state("unchanged");
+        run_command_into(&mut state, "printf hi", false);
+
+        let buffer = state.current_buffer().unwrap();
+        assert_eq!(buffer.name, OUTPUT_BUFFER);
+        assert!(buffer.read_only);
+        assert_eq!(buffer.text.to_string(), "hi");
+        assert_eq!(state.message.as_deref(), Some("Shell command succeeded"));
+    }
+
+    #[test]
+    fn test_shell_command_inserts_at_point_with_prefix() {
+        let mut state = make_state("ab");
+        {
+            let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+            cursor.position = CharOffset(1);
+        }
+        run_command_into(&mut state, "printf X", true);
+
+        let buffer = state.current_buffer().unwrap();
+        assert_eq!(buffer.name, "test");
+        assert_eq!(buffer.text.to_string(), "aXb");
+    }
+
+    #[test]
+    fn test_shell_command_reports_exit_status() {
+        let mut state = make_state("");
+        run_command_into(&mut state, "exit 3", false);
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Shell command exited with status 3 (no output)")
+        );
+    }
+
+    #[test]
+    fn test_filter_region_replaces_with_stdout() {
+        let mut state = make_state("b\na\nrest");
+        {
+            let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+            cursor.mark = Some(CharOffset(0));
+            cursor.mark_active = true;
+            cursor.position = CharOffset(4);
+        }
+        filter_region_through(&mut state, "sort", true);
+
+        let buffer = state.current_buffer().unwrap();
+        assert_eq!(buffer.text.to_string(), "a\nb\nrest");
+        let cursor = &state.windows.current().unwrap().cursors.primary;
+        assert_eq!(cursor.position, CharOffset(4));
+        assert!(!cursor.mark_active);
+    }
+
+    #[test]
+    fn test_filter_region_failure_leaves_buffer_unchanged() {
+        let mut state = make_state("hello");
+        {
+            let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+            cursor.mark = Some(CharOffset(0));
+            cursor.mark_active = true;
+            cursor.position = CharOffset(5);
+        }
+        filter_region_through(&mut state, "echo oops >&2; exit 1", true);
+
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "hello");
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Shell command failed: oops")
+        );
+    }
+
+    #[test]
+    fn test_filter_region_without_prefix_uses_output_buffer() {
+        let mut state = make_state("b\na\n");
+        {
+            let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+            cursor.mark = Some(CharOffset(0));
+            cursor.mark_active = true;
+            cursor.position = CharOffset(4);
+        }
+        filter_region_through(&mut state, "sort", false);
+
+        let buffer = state.current_buffer().unwrap();
+        assert_eq!(buffer.name, OUTPUT_BUFFER);
+        assert_eq!(buffer.text.to_string(), "a\nb\n");
+    }
+
+    #[test]
+    fn test_shell_eval_region_inserts_output_after_region() {
+        let mut state = make_state("echo hi");
+        {
+            let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+            cursor.mark = Some(CharOffset(0));
+            cursor.mark_active = true;
+            cursor.position = CharOffset(7);
+        }
+
+        let ctx = CommandContext::new();
+        shell_eval_region_insert(&mut state, &ctx).unwrap();
+
+        let buffer = state.current_buffer().unwrap();
+        assert_eq!(buffer.text.to_string(), "echo hi\nhi\n");
+    }
+
+    #[test]
+    fn test_shell_eval_without_region_errors() {
+        let mut state = make_state("echo hi");
+        let ctx = CommandContext::new();
+
+        let result = shell_eval_region_insert(&mut state, &ctx);
+        assert!(matches!(result, Err(CommandError::NoMark)));
+    }
+
+    #[test]
+    fn test_failing_command_reports_error() {
+        let mut state = make_state("exit 3");
+        {
+            let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+            cursor.mark = Some(CharOffset(0));
+            cursor.mark_active = true;
+            cursor.position = CharOffset(6);
+        }
+
+        let ctx = CommandContext::new();
+        let result = shell_eval_region_insert(&mut state, &ctx);
+        assert!(matches!(result, Err(CommandError::Other(_))));
+    }
+}
@@ -0,0 +1,499 @@
+use crate::core::Buffer;
+use crate::keybinding::key::{Key, Modifiers};
+use crate::keybinding::KeyEvent;
+use crate::state::editor::ThemePreview;
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandResult};
+
+const THEME_LIST_BUFFER: &str = "*Themes*";
+
+/// Names of the themes that ship with the editor.
+pub fn available_themes() -> &'static [&'static str] {
+    &["modus-operandi", "modus-vivendi"]
+}
+
+/// Chrome colors loaded from a theme file, as `0xRRGGBB` values. The
+/// GUI converts them into its render formats; the terminal keeps its
+/// ANSI palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemeSpec {
+    pub background: u32,
+    pub foreground: u32,
+    pub cursor: u32,
+    pub selection: u32,
+    pub modeline_bg: u32,
+    pub modeline_fg: u32,
+}
+
+/// Font requested by `set-font`, as stated by the user. The GUI applies
+/// it on its next frame; a missing half keeps the current value and the
+/// terminal ignores it entirely.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontSpec {
+    pub family: Option<String>,
+    pub size: Option<f32>,
+}
+
+impl FontSpec {
+    /// Parses `set-font` input: a family name, a point size, or both
+    /// (`"Iosevka 16"`). A trailing number is the size; the rest is the
+    /// family.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err("Empty font spec".to_string());
+        }
+
+        let (family_part, size) = match input.rsplit_once(char::is_whitespace) {
+            Some((rest, last)) if last.parse::<f32>().is_ok() => {
+                (rest.trim(), last.parse::<f32>().ok())
+            }
+            _ => match input.parse::<f32>() {
+                Ok(size) => ("", Some(size)),
+                Err(_) => (input, None),
+            },
+        };
+
+        if let Some(size) = size {
+            if !(6.0..=96.0).contains(&size) {
+                return Err(format!("Font size {} out of range (6-96)", size));
+            }
+        }
+
+        Ok(Self {
+            family: (!family_part.is_empty()).then(|| family_part.to_string()),
+            size,
+        })
+    }
+}
+
+fn parse_hex_color(s: &str) -> Option<u32> {
+    let s = s.strip_prefix('#')?;
+    if s.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(s, 16).ok()
+}
+
+/// Reads a theme file of TOML-style `key = "#rrggbb"` lines into a
+/// named [`ThemeSpec`]; the theme is named after the file stem. Blank
+/// lines, `#` comments and section headers are skipped. Errors name the
+/// offending line or the missing key.
+pub fn load_theme_file(path: &std::path::Path) -> Result<(String, ThemeSpec), String> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut values = std::collections::HashMap::new();
+    for (idx, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+            continue;
+        }
+        let (key, value) = line.split_once('=').ok_or_else(|| {
+            format!(
+                "{}:{}: expected `key = \"#rrggbb\"`",
+                path.display(),
+                idx + 1
+            )
+        })?;
+        let value = value.trim().trim_matches('"');
+        let color = parse_hex_color(value)
+            .ok_or_else(|| format!("{}:{}: invalid color {}", path.display(), idx + 1, value))?;
+        values.insert(key.trim().to_string(), color);
+    }
+
+    let get = |key: &str| {
+        values
+            .get(key)
+            .copied()
+            .ok_or_else(|| format!("{}: missing key {}", path.display(), key))
+    };
+    let spec = ThemeSpec {
+        background: get("background")?,
+        foreground: get("foreground")?,
+        cursor: get("cursor")?,
+        selection: get("selection")?,
+        modeline_bg: get("modeline_bg")?,
+        modeline_fg: get("modeline_fg")?,
+    };
+
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("custom")
+        .to_string();
+    Ok((name, spec))
+}
+
+pub fn load_theme(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Load theme: ", "load-theme");
+    Ok(())
+}
+
+pub fn set_font(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Set font (family and/or size): ", "set-font");
+    Ok(())
+}
+
+/// Minibuffer callback for `set-font`: records the request for the GUI
+/// to apply on its next frame.
+pub fn apply_font(state: &mut EditorState, input: &str) {
+    match FontSpec::parse(input) {
+        Ok(spec) => {
+            state.message = Some(match (&spec.family, spec.size) {
+                (Some(family), Some(size)) => format!("Font set to {} {}", family, size),
+                (Some(family), None) => format!("Font set to {}", family),
+                (None, Some(size)) => format!("Font size set to {}", size),
+                (None, None) => unreachable!("parse rejects empty specs"),
+            });
+            state.font = Some(spec);
+        }
+        Err(e) => state.message = Some(e),
+    }
+}
+
+/// Minibuffer callback for `load-theme`: applies a built-in or already
+/// loaded theme by name, or loads a theme file when given a path.
+pub fn apply_theme(state: &mut EditorState, name: &str) {
+    let name = name.trim();
+    if available_themes().contains(&name) || state.custom_themes.contains_key(name) {
+        state.active_theme = name.to_string();
+        state.message = Some(format!("Theme {} enabled", name));
+        return;
+    }
+
+    let path = std::path::Path::new(name);
+    if path.exists() {
+        match load_theme_file(path) {
+            Ok((theme_name, spec)) => {
+                state.custom_themes.insert(theme_name.clone(), spec);
+                state.message = Some(format!("Theme {} enabled", theme_name));
+                state.active_theme = theme_name;
+            }
+            Err(e) => state.message = Some(e),
+        }
+    } else {
+        state.message = Some(format!("Unknown theme: {}", name));
+    }
+}
+
+/// TAB completion over the built-in and loaded theme names.
+pub fn complete_theme(state: &EditorState, input: &str) -> Vec<String> {
+    available_themes()
+        .iter()
+        .map(|name| name.to_string())
+        .chain(state.custom_themes.keys().cloned())
+        .filter(|name| name.starts_with(input))
+        .collect()
+}
+
+pub fn list_themes(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let saved_theme = state.active_theme.clone();
+    let return_buffer = state.windows.current_buffer_id();
+
+    if let Some(id) = state.buffers.find_by_name(THEME_LIST_BUFFER) {
+        state.buffers.kill(id);
+    }
+
+    let mut contents = String::new();
+    for name in available_themes() {
+        contents.push_str(name);
+        contents.push('\n');
+    }
+    let mut custom: Vec<&String> = state.custom_themes.keys().collect();
+    custom.sort();
+    for name in custom {
+        contents.push_str(name);
+        contents.push('\n');
+    }
+
+    let mut buffer = Buffer::from_string(THEME_LIST_BUFFER, &contents);
+    buffer.read_only = true;
+    let id = state.buffers.add(buffer);
+    state.buffers.set_current(id);
+    state.windows.set_current_buffer(id);
+
+    state.theme_preview = Some(ThemePreview {
+        saved_theme,
+        return_buffer,
+    });
+    state.message = Some("Move to preview a theme; RET to apply, C-g to cancel".to_string());
+
+    Ok(())
+}
+
+/// Applies the theme under point while the preview list is current.
+/// Called from `post_command` so plain motion updates the preview.
+pub fn sync_preview(state: &mut EditorState) {
+    if !in_theme_list(state) {
+        return;
+    }
+
+    if let Some(name) = theme_at_point(state) {
+        if available_themes().contains(&name.as_str()) || state.custom_themes.contains_key(&name) {
+            state.active_theme = name;
+        }
+    }
+}
+
+/// Intercepts RET (commit) and C-g/Escape (cancel) while the theme
+/// list buffer is current. Returns true when the key was consumed.
+pub fn handle_preview_key(state: &mut EditorState, key: KeyEvent) -> bool {
+    if !in_theme_list(state) {
+        return false;
+    }
+
+    match (key.key, key.modifiers) {
+        (Key::Enter, Modifiers::NONE) => {
+            let name = state.active_theme.clone();
+            let preview = state.theme_preview.take();
+            close_theme_list(state, preview.and_then(|p| p.return_buffer));
+            state.message = Some(format!("Theme {} enabled", name));
+            true
+        }
+        (Key::Char('g'), Modifiers::CTRL) | (Key::Escape, _) => {
+            let preview = state.theme_preview.take();
+            let return_buffer = preview.map(|p| {
+                state.active_theme = p.saved_theme;
+                p.return_buffer
+            });
+            close_theme_list(state, return_buffer.flatten());
+            state.message = Some("Quit".to_string());
+            true
+        }
+        _ => false,
+    }
+}
+
+fn in_theme_list(state: &EditorState) -> bool {
+    state
+        .current_buffer()
+        .map(|b| b.name == THEME_LIST_BUFFER)
+        .unwrap_or(false)
+}
+
+fn theme_at_point(state: &EditorState) -> Option<String> {
+    use crate::core::rope_ext::RopeExt;
+
+    let window = state.current_window()?;
+    let buffer = state.buffers.get(window.buffer_id)?;
+    let line = buffer
+        .text
+        .char_to_position(window.cursors.primary.position)
+        .line;
+    if line >= buffer.text.len_lines() {
+        return None;
+    }
+    let name = buffer.text.line(line).to_string();
+    let name = name.trim();
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+fn close_theme_list(state: &mut EditorState, return_buffer: Option<crate::core::BufferId>) {
+    let return_buffer = return_buffer.filter(|id| state.buffers.get(*id).is_some());
+
+    if let Some(id) = return_buffer {
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+    }
+
+    if let Some(id) = state.buffers.find_by_name(THEME_LIST_BUFFER) {
+        state.buffers.kill(id);
+    }
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![
+        Command::new("load-theme", load_theme),
+        Command::new("list-themes", list_themes),
+        Command::new("set-font", set_font),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    fn write_theme_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}.toml", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_theme_file_parses_hex_colors() {
+        let path = write_theme_file(
+            "enacs-gruvbox",
+            "# a theme\n\
+             background = \"#282828\"\n\
+             foreground = \"#ebdbb2\"\n\
+             cursor = \"#fe8019\"\n\
+             selection = \"#504945\"\n\
+             modeline_bg = \"#3c3836\"\n\
+             modeline_fg = \"#ebdbb2\"\n",
+        );
+
+        let (name, spec) = load_theme_file(&path).unwrap();
+        assert_eq!(name, format!("enacs-gruvbox-{}", std::process::id()));
+        assert_eq!(spec.background, 0x282828);
+        assert_eq!(spec.cursor, 0xfe8019);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_load_theme_file_reports_missing_key() {
+        let path = write_theme_file("enacs-partial", "background = \"#282828\"\n");
+
+        let err = load_theme_file(&path).unwrap_err();
+        assert!(err.contains("missing key foreground"), "got: {}", err);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_load_theme_accepts_a_path() {
+        let path = write_theme_file(
+            "enacs-custom",
+            "background = \"#111111\"\n\
+             foreground = \"#eeeeee\"\n\
+             cursor = \"#ff0000\"\n\
+             selection = \"#333333\"\n\
+             modeline_bg = \"#222222\"\n\
+             modeline_fg = \"#eeeeee\"\n",
+        );
+
+        let mut state = make_state("hello");
+        apply_theme(&mut state, path.to_str().unwrap());
+
+        let name = format!("enacs-custom-{}", std::process::id());
+        assert_eq!(state.active_theme, name);
+        assert!(state.custom_themes.contains_key(&name));
+        assert!(complete_theme(&state, "enacs-custom").contains(&name));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_load_theme_applies_known_name() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        load_theme(&mut state, &ctx).unwrap();
+        for c in "modus-vivendi".chars() {
+            state.handle_key(KeyEvent::char(c));
+        }
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+
+        assert_eq!(state.active_theme, "modus-vivendi");
+
+        apply_theme(&mut state, "no-such-theme");
+        assert_eq!(state.active_theme, "modus-vivendi");
+        assert_eq!(
+            state.message,
+            Some("Unknown theme: no-such-theme".to_string())
+        );
+    }
+
+    #[test]
+    fn test_font_spec_parses_family_size_or_both() {
+        let both = FontSpec::parse("Iosevka Term 16").unwrap();
+        assert_eq!(both.family.as_deref(), Some("Iosevka Term"));
+        assert_eq!(both.size, Some(16.0));
+
+        let family_only = FontSpec::parse("Fira Code").unwrap();
+        assert_eq!(family_only.family.as_deref(), Some("Fira Code"));
+        assert_eq!(family_only.size, None);
+
+        let size_only = FontSpec::parse("14").unwrap();
+        assert_eq!(size_only.family, None);
+        assert_eq!(size_only.size, Some(14.0));
+
+        assert!(FontSpec::parse("").is_err());
+        assert!(FontSpec::parse("Iosevka 200").is_err());
+    }
+
+    #[test]
+    fn test_set_font_records_request_for_the_gui() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        set_font(&mut state, &ctx).unwrap();
+        for c in "Iosevka 16".chars() {
+            state.handle_key(KeyEvent::char(c));
+        }
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+
+        let spec = state.font.as_ref().unwrap();
+        assert_eq!(spec.family.as_deref(), Some("Iosevka"));
+        assert_eq!(spec.size, Some(16.0));
+        assert_eq!(state.message, Some("Font set to Iosevka 16".to_string()));
+
+        apply_font(&mut state, "  ");
+        assert_eq!(state.message, Some("Empty font spec".to_string()));
+    }
+
+    #[test]
+    fn test_complete_theme_filters_by_prefix() {
+        let state = make_state("hello");
+        assert_eq!(
+            complete_theme(&state, "modus-o"),
+            vec!["modus-operandi".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_moving_over_entry_previews_theme() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        list_themes(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().name, THEME_LIST_BUFFER);
+
+        state.handle_key(KeyEvent::ctrl('n'));
+        assert_eq!(state.active_theme, "modus-vivendi");
+    }
+
+    #[test]
+    fn test_cancel_restores_original_theme() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        list_themes(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::ctrl('n'));
+        state.handle_key(KeyEvent::ctrl('g'));
+
+        assert_eq!(state.active_theme, "modus-operandi");
+        assert!(state.theme_preview.is_none());
+        assert!(state.buffers.find_by_name(THEME_LIST_BUFFER).is_none());
+        assert_eq!(state.current_buffer().unwrap().name, "test");
+    }
+
+    #[test]
+    fn test_ret_commits_previewed_theme() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        list_themes(&mut state, &ctx).unwrap();
+        state.handle_key(KeyEvent::ctrl('n'));
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+
+        assert_eq!(state.active_theme, "modus-vivendi");
+        assert!(state.theme_preview.is_none());
+        assert_eq!(state.current_buffer().unwrap().name, "test");
+    }
+}
@@ -0,0 +1,903 @@
+use crate::core::position::CharOffset;
+use crate::state::EditorState;
+
+use super::registry::{Command, CommandContext, CommandError, CommandResult};
+
+const TAB_WIDTH: usize = 4;
+
+/// Which passes `whitespace-cleanup-region` runs; all on by default.
+#[derive(Debug, Clone)]
+pub struct CleanupOptions {
+    pub untabify: bool,
+    pub strip_trailing: bool,
+    pub collapse_blank_lines: bool,
+    pub ensure_final_newline: bool,
+}
+
+impl Default for CleanupOptions {
+    fn default() -> Self {
+        Self {
+            untabify: true,
+            strip_trailing: true,
+            collapse_blank_lines: true,
+            ensure_final_newline: true,
+        }
+    }
+}
+
+fn expand_line_tabs(line: &str, tab_width: usize) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut col = 0;
+    for ch in line.chars() {
+        if ch == '\t' {
+            let spaces = tab_width - (col % tab_width);
+            for _ in 0..spaces {
+                result.push(' ');
+            }
+            col += spaces;
+        } else {
+            result.push(ch);
+            col += 1;
+        }
+    }
+    result
+}
+
+/// Expands every tab in `text` to spaces; tabs past the line start
+/// expand to their visual column, not a fixed run.
+pub fn untabify_string(text: &str, tab_width: usize) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            let (body, newline) = match line.strip_suffix('\n') {
+                Some(body) => (body, "\n"),
+                None => (line, ""),
+            };
+            if body.contains('\t') {
+                format!("{}{}", expand_line_tabs(body, tab_width), newline)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Collapses each line's leading whitespace into tabs plus a space
+/// remainder. Interior runs of spaces are left alone so aligned code
+/// isn't mangled.
+pub fn tabify_string(text: &str, tab_width: usize) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            let rest = line.trim_start_matches([' ', '\t']);
+            let lead = &line[..line.len() - rest.len()];
+
+            let mut col = 0;
+            for ch in lead.chars() {
+                col += match ch {
+                    '\t' => tab_width - (col % tab_width),
+                    _ => 1,
+                };
+            }
+
+            let mut result = "\t".repeat(col / tab_width);
+            result.push_str(&" ".repeat(col % tab_width));
+            result.push_str(rest);
+            result
+        })
+        .collect()
+}
+
+/// Normalizes whitespace in `text` according to `options`. `use_tabs`
+/// (indent-tabs-mode) suppresses the tab-to-space conversion.
+pub fn cleanup_string(text: &str, options: &CleanupOptions, use_tabs: bool) -> String {
+    let had_final_newline = text.ends_with('\n');
+    let body = text.strip_suffix('\n').unwrap_or(text);
+
+    let mut lines: Vec<String> = body.split('\n').map(|s| s.to_string()).collect();
+
+    if options.untabify && !use_tabs {
+        for line in &mut lines {
+            if line.contains('\t') {
+                *line = expand_line_tabs(line, TAB_WIDTH);
+            }
+        }
+    }
+
+    if options.strip_trailing {
+        for line in &mut lines {
+            let stripped = line.trim_end_matches([' ', '\t']);
+            line.truncate(stripped.len());
+        }
+    }
+
+    if options.collapse_blank_lines {
+        let mut collapsed: Vec<String> = Vec::with_capacity(lines.len());
+        let mut blank_run = 0;
+        for line in lines {
+            if line.is_empty() {
+                blank_run += 1;
+            } else {
+                if blank_run >= 3 {
+                    collapsed.push(String::new());
+                } else {
+                    for _ in 0..blank_run {
+                        collapsed.push(String::new());
+                    }
+                }
+                blank_run = 0;
+                collapsed.push(line);
+            }
+        }
+        if blank_run >= 3 {
+            collapsed.push(String::new());
+        } else {
+            for _ in 0..blank_run {
+                collapsed.push(String::new());
+            }
+        }
+        lines = collapsed;
+    }
+
+    let mut result = lines.join("\n");
+    if had_final_newline || (options.ensure_final_newline && !result.is_empty()) {
+        result.push('\n');
+    }
+    result
+}
+
+pub fn whitespace_cleanup_region(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let len = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.len_chars())
+        .unwrap_or(0);
+
+    let (start, end) = state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region())
+        .unwrap_or((CharOffset(0), CharOffset(len)));
+
+    // Only force a final newline when the cleanup reaches buffer end.
+    let mut options = state.whitespace_cleanup.clone();
+    options.ensure_final_newline &= end.0 >= len;
+
+    let use_tabs = state.indent_tabs_mode;
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        let old = buffer.slice(start, end);
+        let new = cleanup_string(&old, &options, use_tabs);
+        buffer.replace_region(cursors, start, end, &new);
+    }
+
+    if let Some(window) = state.windows.current_mut() {
+        window.cursors.deactivate_all_marks();
+    }
+    state.message = Some("Whitespace cleaned up".to_string());
+
+    Ok(())
+}
+
+/// Rewrites the region (or the whole buffer when no mark is active)
+/// through `transform` as a single undo group.
+fn transform_region(state: &mut EditorState, transform: fn(&str, usize) -> String) {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return,
+    };
+
+    let len = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.len_chars())
+        .unwrap_or(0);
+
+    let (start, end) = state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region())
+        .unwrap_or((CharOffset(0), CharOffset(len)));
+
+    let tab_width = state.tab_width;
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        let old = buffer.slice(start, end);
+        let new = transform(&old, tab_width);
+        buffer.replace_region(cursors, start, end, &new);
+    }
+
+    if let Some(window) = state.windows.current_mut() {
+        window.cursors.deactivate_all_marks();
+    }
+}
+
+pub fn untabify(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    transform_region(state, untabify_string);
+    state.message = Some("Untabified".to_string());
+    Ok(())
+}
+
+pub fn tabify(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    transform_region(state, tabify_string);
+    state.message = Some("Tabified".to_string());
+    Ok(())
+}
+
+/// Shifts every line of `text` by `cols` columns: right inserts spaces
+/// (blank lines excluded), left removes leading whitespace down to
+/// column zero at most, padding when a tab overshoots the target.
+pub fn shift_lines(text: &str, cols: i32, tab_width: usize) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            if cols >= 0 {
+                if line.trim().is_empty() {
+                    line.to_string()
+                } else {
+                    format!("{}{}", " ".repeat(cols as usize), line)
+                }
+            } else {
+                let remove = cols.unsigned_abs() as usize;
+                let mut col = 0;
+                let mut idx = line.len();
+                for (i, ch) in line.char_indices() {
+                    if col >= remove || !matches!(ch, ' ' | '\t') {
+                        idx = i;
+                        break;
+                    }
+                    col += match ch {
+                        '\t' => tab_width - (col % tab_width),
+                        _ => 1,
+                    };
+                }
+                let pad = col.saturating_sub(remove);
+                format!("{}{}", " ".repeat(pad), &line[idx..])
+            }
+        })
+        .collect()
+}
+
+/// Shifts the lines of the active region by the numeric prefix (right,
+/// or left when negative) as one undo batch; the region stays active.
+pub fn indent_rigidly(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    use crate::core::rope_ext::RopeExt;
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let read_only = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.read_only)
+        .unwrap_or(false);
+    if read_only {
+        return Err(CommandError::ReadOnly);
+    }
+
+    let (region_start, region_end) = match state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region())
+    {
+        Some(region) => region,
+        None => return Err(CommandError::NoMark),
+    };
+
+    let tab_width = state.tab_width;
+    let (start, end, new_text) = {
+        let buffer = state.buffers.get(buffer_id).unwrap();
+        let start_line = buffer.text.char_to_position(region_start).line;
+        let end_pos = buffer.text.char_to_position(region_end);
+        // A region ending at a line start does not include that line
+        let end_line = if end_pos.line > start_line && end_pos.column == 0 {
+            end_pos.line - 1
+        } else {
+            end_pos.line
+        };
+
+        let start = buffer.text.line_start_char(start_line);
+        let end = if end_line + 1 < buffer.text.total_lines() {
+            buffer.text.line_start_char(end_line + 1)
+        } else {
+            CharOffset(buffer.len_chars())
+        };
+        let old = buffer.slice(start, end);
+        (start, end, shift_lines(&old, ctx.count(), tab_width))
+    };
+
+    let new_len = new_text.chars().count();
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.replace_region(cursors, start, end, &new_text);
+    }
+
+    let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+    cursor.set_mark(start);
+    cursor.set_position(CharOffset(start.0 + new_len));
+    Ok(())
+}
+
+/// The leading space/tab run of `line`.
+fn leading_indent(line: &str) -> &str {
+    let rest = line.trim_start_matches([' ', '\t']);
+    &line[..line.len() - rest.len()]
+}
+
+/// Re-indents every non-blank line of `text` to `initial`, the
+/// indentation of the closest non-blank line above it. Blank lines are
+/// left alone.
+pub fn reindent_string(text: &str, initial: &str) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            if line.trim().is_empty() {
+                line.to_string()
+            } else {
+                let (body, newline) = match line.strip_suffix('\n') {
+                    Some(body) => (body, "\n"),
+                    None => (line, ""),
+                };
+                format!(
+                    "{}{}{}",
+                    initial,
+                    body.trim_start_matches([' ', '\t']),
+                    newline
+                )
+            }
+        })
+        .collect()
+}
+
+/// The indentation of the closest non-blank line above `line`, or empty
+/// at the top of the buffer.
+fn previous_nonblank_indent(buffer: &crate::core::Buffer, line: usize) -> String {
+    use crate::core::rope_ext::RopeExt;
+
+    let mut above = line;
+    while above > 0 {
+        above -= 1;
+        let text = buffer.slice(
+            buffer.text.line_start_char(above),
+            buffer.text.line_end_char(above),
+        );
+        if !text.trim().is_empty() {
+            return leading_indent(&text).to_string();
+        }
+    }
+    String::new()
+}
+
+/// TAB (`indent-for-tab-command`): in a code mode re-indents the
+/// current line — or every line of the active region — to match the
+/// closest non-blank line above; in a text mode inserts spaces up to
+/// the major mode's next tab stop.
+pub fn indent_for_tab(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    use crate::core::rope_ext::RopeExt;
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let mode = match state.buffers.get(buffer_id) {
+        Some(b) => {
+            if b.read_only {
+                return Err(CommandError::ReadOnly);
+            }
+            b.major_mode
+        }
+        None => return Ok(()),
+    };
+
+    if matches!(mode.language, "text" | "markdown") {
+        let width = mode.indent_width.max(1);
+        let column = {
+            let window = state.windows.current().unwrap();
+            let buffer = state.buffers.get(buffer_id).unwrap();
+            buffer
+                .text
+                .char_to_position(window.cursors.primary.position)
+                .column
+        };
+        let spaces = " ".repeat(width - column % width);
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+            buffer.insert_string(cursors, &spaces);
+        }
+        return Ok(());
+    }
+
+    let region = state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region());
+    match region {
+        Some((region_start, region_end)) => {
+            let (start, end, new_text) = {
+                let buffer = state.buffers.get(buffer_id).unwrap();
+                let start_line = buffer.text.char_to_position(region_start).line;
+                let end_pos = buffer.text.char_to_position(region_end);
+                // A region ending at a line start does not include that line
+                let end_line = if end_pos.line > start_line && end_pos.column == 0 {
+                    end_pos.line - 1
+                } else {
+                    end_pos.line
+                };
+
+                let start = buffer.text.line_start_char(start_line);
+                let end = if end_line + 1 < buffer.text.total_lines() {
+                    buffer.text.line_start_char(end_line + 1)
+                } else {
+                    CharOffset(buffer.len_chars())
+                };
+                let initial = previous_nonblank_indent(buffer, start_line);
+                let old = buffer.slice(start, end);
+                (start, end, reindent_string(&old, &initial))
+            };
+
+            let new_len = new_text.chars().count();
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+                buffer.replace_region(cursors, start, end, &new_text);
+            }
+
+            let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+            cursor.set_mark(start);
+            cursor.set_position(CharOffset(start.0 + new_len));
+        }
+        None => {
+            let (start, end, new_text, indent_len) = {
+                let window = state.windows.current().unwrap();
+                let buffer = state.buffers.get(buffer_id).unwrap();
+                let line = buffer
+                    .text
+                    .char_to_position(window.cursors.primary.position)
+                    .line;
+                let start = buffer.text.line_start_char(line);
+                let end = buffer.text.line_end_char(line);
+                let old = buffer.slice(start, end);
+                let target = previous_nonblank_indent(buffer, line);
+                let new_text = format!("{}{}", target, old.trim_start_matches([' ', '\t']));
+                (start, end, new_text, target.chars().count())
+            };
+
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+                buffer.replace_region(cursors, start, end, &new_text);
+            }
+
+            // Point lands at the indentation, as in Emacs
+            let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+            if cursor.position.0 < start.0 + indent_len {
+                cursor.set_position(CharOffset(start.0 + indent_len));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Sorts the lines of the active region lexicographically (reversed
+/// with a negative or C-u prefix other than 1) as one undo operation;
+/// the region stays active.
+pub fn sort_lines(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    use crate::core::rope_ext::RopeExt;
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return Ok(()),
+    };
+
+    let read_only = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.read_only)
+        .unwrap_or(false);
+    if read_only {
+        return Err(CommandError::ReadOnly);
+    }
+
+    let (region_start, region_end) = match state
+        .windows
+        .current()
+        .and_then(|w| w.cursors.primary.region())
+    {
+        Some(region) => region,
+        None => return Err(CommandError::NoMark),
+    };
+
+    let reverse = ctx.prefix_arg.is_set();
+    let (start, end, new_text) = {
+        let buffer = state.buffers.get(buffer_id).unwrap();
+        let start_line = buffer.text.char_to_position(region_start).line;
+        let end_pos = buffer.text.char_to_position(region_end);
+        // A region ending at a line start does not include that line
+        let end_line = if end_pos.line > start_line && end_pos.column == 0 {
+            end_pos.line - 1
+        } else {
+            end_pos.line
+        };
+
+        let start = buffer.text.line_start_char(start_line);
+        let end = if end_line + 1 < buffer.text.total_lines() {
+            buffer.text.line_start_char(end_line + 1)
+        } else {
+            CharOffset(buffer.len_chars())
+        };
+        let old = buffer.slice(start, end);
+
+        // Sort the bodies; the trailing newline stays where it was
+        let had_final_newline = old.ends_with('\n');
+        let body = old.strip_suffix('\n').unwrap_or(&old);
+        let mut lines: Vec<&str> = body.split('\n').collect();
+        lines.sort_unstable();
+        if reverse {
+            lines.reverse();
+        }
+        let mut new_text = lines.join("\n");
+        if had_final_newline {
+            new_text.push('\n');
+        }
+        (start, end, new_text)
+    };
+
+    let new_len = new_text.chars().count();
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.replace_region(cursors, start, end, &new_text);
+    }
+
+    let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+    cursor.set_mark(start);
+    cursor.set_position(CharOffset(start.0 + new_len));
+    Ok(())
+}
+
+pub fn keep_lines(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Keep lines containing: ", "keep-lines");
+    Ok(())
+}
+
+pub fn flush_lines(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.start_minibuffer_prompt("Flush lines containing: ", "flush-lines");
+    Ok(())
+}
+
+/// Minibuffer callback for `keep-lines`/`flush-lines`: within the
+/// region (or from point to buffer end) keeps or deletes the lines
+/// matching `pattern` (a regexp with the `regexp` feature, a literal
+/// otherwise), as one undo batch, and reports the removals.
+pub fn filter_lines_with(state: &mut EditorState, pattern: &str, keep: bool) {
+    use crate::core::rope_ext::RopeExt;
+
+    if pattern.is_empty() {
+        return;
+    }
+    let pattern = match super::search::SearchPattern::parse(pattern, cfg!(feature = "regexp")) {
+        Ok(pattern) => pattern,
+        Err(e) => {
+            state.message = Some(e);
+            return;
+        }
+    };
+
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return,
+    };
+
+    let read_only = state
+        .buffers
+        .get(buffer_id)
+        .map(|b| b.read_only)
+        .unwrap_or(false);
+    if read_only {
+        state.message = Some("Buffer is read-only".to_string());
+        return;
+    }
+
+    let (start, end, new_text, removed) = {
+        let window = state.windows.current().unwrap();
+        let buffer = state.buffers.get(buffer_id).unwrap();
+        let (start, end) = match window.cursors.primary.region() {
+            Some((region_start, region_end)) => {
+                let start_line = buffer.text.char_to_position(region_start).line;
+                let end_pos = buffer.text.char_to_position(region_end);
+                let end_line = if end_pos.line > start_line && end_pos.column == 0 {
+                    end_pos.line - 1
+                } else {
+                    end_pos.line
+                };
+                let start = buffer.text.line_start_char(start_line);
+                let end = if end_line + 1 < buffer.text.total_lines() {
+                    buffer.text.line_start_char(end_line + 1)
+                } else {
+                    CharOffset(buffer.len_chars())
+                };
+                (start, end)
+            }
+            None => {
+                let point_line = buffer
+                    .text
+                    .char_to_position(window.cursors.primary.position)
+                    .line;
+                (
+                    buffer.text.line_start_char(point_line),
+                    CharOffset(buffer.len_chars()),
+                )
+            }
+        };
+
+        let old = buffer.slice(start, end);
+        let total = old.split_inclusive('\n').count();
+        let new_text: String = old
+            .split_inclusive('\n')
+            .filter(|line| pattern.matches(line) == keep)
+            .collect();
+        let kept = new_text.split_inclusive('\n').count();
+        (start, end, new_text, total - kept)
+    };
+
+    let cursors = &mut state.windows.current_mut().unwrap().cursors;
+    if let Some(buffer) = state.buffers.get_mut(buffer_id) {
+        buffer.replace_region(cursors, start, end, &new_text);
+    }
+
+    if let Some(window) = state.windows.current_mut() {
+        window.cursors.deactivate_all_marks();
+    }
+    state.message = Some(format!("Removed {} lines", removed));
+}
+
+pub fn all_commands() -> Vec<Command> {
+    vec![
+        Command::new("whitespace-cleanup-region", whitespace_cleanup_region),
+        Command::mark("indent-rigidly", indent_rigidly),
+        Command::mark("indent-for-tab-command", indent_for_tab),
+        Command::mark("sort-lines", sort_lines),
+        Command::new("keep-lines", keep_lines),
+        Command::new("flush-lines", flush_lines),
+        Command::new("untabify", untabify),
+        Command::new("tabify", tabify),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Buffer;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    #[test]
+    fn test_flush_lines_from_point_reports_removals() {
+        let mut state = make_state("keep\ndrop me\nkeep too\ndrop me\n");
+
+        filter_lines_with(&mut state, "drop", false);
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "keep\nkeep too\n"
+        );
+        assert_eq!(state.message.as_deref(), Some("Removed 2 lines"));
+
+        // One undo restores both deleted lines
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        let buffer = state.buffers.current_mut().unwrap();
+        assert!(buffer.undo(cursors));
+        assert_eq!(buffer.text.to_string(), "keep\ndrop me\nkeep too\ndrop me\n");
+    }
+
+    #[test]
+    fn test_keep_lines_respects_region() {
+        let mut state = make_state("a\nb\na\nb\n");
+        {
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.primary.set_mark(CharOffset(0));
+            cursors.primary.position = CharOffset(4);
+        }
+
+        filter_lines_with(&mut state, "a", true);
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "a\na\nb\n");
+    }
+
+    #[test]
+    fn test_tab_matches_previous_line_indent_in_code_mode() {
+        let mut state = make_state("    foo\nbar\n");
+        state.buffers.current_mut().unwrap().major_mode =
+            crate::core::detect_mode(std::path::Path::new("t.rs"));
+        state.windows.current_mut().unwrap().cursors.primary.position = CharOffset(8);
+
+        let ctx = CommandContext::new();
+        indent_for_tab(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "    foo\n    bar\n"
+        );
+        // Point lands at the indentation
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(12)
+        );
+    }
+
+    #[test]
+    fn test_tab_reindents_the_whole_region() {
+        let mut state = make_state("    a\nb\n        c\n");
+        state.buffers.current_mut().unwrap().major_mode =
+            crate::core::detect_mode(std::path::Path::new("t.rs"));
+        {
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.primary.set_mark(CharOffset(6));
+            cursors.primary.position = CharOffset(18);
+        }
+
+        let ctx = CommandContext::new();
+        indent_for_tab(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "    a\n    b\n    c\n"
+        );
+    }
+
+    #[test]
+    fn test_tab_inserts_to_the_next_tab_stop_in_text_mode() {
+        let mut state = make_state("ab");
+        state.windows.current_mut().unwrap().cursors.primary.position = CharOffset(2);
+
+        let ctx = CommandContext::new();
+        indent_for_tab(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "ab  ");
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(4)
+        );
+    }
+
+    #[test]
+    fn test_sort_lines_keeps_trailing_newline() {
+        let mut state = make_state("banana\napple\ncherry\n");
+        {
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.primary.set_mark(CharOffset(0));
+            cursors.primary.position = CharOffset(20);
+        }
+
+        let ctx = CommandContext::new();
+        sort_lines(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "apple\nbanana\ncherry\n"
+        );
+        assert!(state.current_window().unwrap().cursors.primary.mark_active);
+    }
+
+    #[test]
+    fn test_sort_lines_prefix_reverses() {
+        let mut state = make_state("b\na\nc");
+        {
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.primary.set_mark(CharOffset(0));
+            cursors.primary.position = CharOffset(5);
+        }
+
+        let mut ctx = CommandContext::new();
+        ctx.prefix_arg = crate::commands::registry::PrefixArg::Universal(4);
+        sort_lines(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "c\nb\na");
+    }
+
+    #[test]
+    fn test_shift_lines_left_stops_at_column_zero() {
+        assert_eq!(shift_lines("  a\nb\n", -4, 4), "a\nb\n");
+        // A tab overshooting the shift pads the difference back
+        assert_eq!(shift_lines("\ta\n", -2, 4), "  a\n");
+        assert_eq!(shift_lines("a\n\nb\n", 2, 4), "  a\n\n  b\n");
+    }
+
+    #[test]
+    fn test_indent_rigidly_keeps_region_active() {
+        let mut state = make_state("foo\nbar\n");
+        {
+            let cursors = &mut state.windows.current_mut().unwrap().cursors;
+            cursors.primary.set_mark(CharOffset(0));
+            cursors.primary.position = CharOffset(7);
+        }
+
+        let mut ctx = CommandContext::new();
+        ctx.prefix_arg = crate::commands::registry::PrefixArg::Raw(2);
+        indent_rigidly(&mut state, &ctx).unwrap();
+
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "  foo\n  bar\n"
+        );
+        assert!(state.current_window().unwrap().cursors.primary.mark_active);
+    }
+
+    #[test]
+    fn test_untabify_expands_to_visual_columns() {
+        // The mid-line tab reaches the next stop, not a fixed 4 spaces
+        assert_eq!(untabify_string("ab\tc\n", 4), "ab  c\n");
+        assert_eq!(untabify_string("\tx\n", 4), "    x\n");
+    }
+
+    #[test]
+    fn test_tabify_only_collapses_leading_whitespace() {
+        assert_eq!(tabify_string("        foo\n", 4), "\t\tfoo\n");
+        assert_eq!(tabify_string("      foo\n", 4), "\t  foo\n");
+        // Interior alignment spaces are untouched
+        assert_eq!(tabify_string("    a    b\n", 4), "\ta    b\n");
+    }
+
+    #[test]
+    fn test_untabify_region_is_one_undo_group() {
+        let mut state = make_state("\ta\n\tb\n");
+        let ctx = CommandContext::new();
+
+        untabify(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "    a\n    b\n"
+        );
+
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        let buffer = state.buffers.current_mut().unwrap();
+        assert!(buffer.undo(cursors));
+        assert_eq!(buffer.text.to_string(), "\ta\n\tb\n");
+    }
+
+    #[test]
+    fn test_cleanup_string_normalizes_messy_text() {
+        let options = CleanupOptions::default();
+        let messy = "foo\t \nbar   \n\n\n\n\nbaz";
+        assert_eq!(
+            cleanup_string(messy, &options, false),
+            "foo\nbar\n\nbaz\n"
+        );
+    }
+
+    #[test]
+    fn test_cleanup_string_keeps_tabs_with_indent_tabs_mode() {
+        let options = CleanupOptions::default();
+        assert_eq!(cleanup_string("\tfoo\n", &options, true), "\tfoo\n");
+        assert_eq!(cleanup_string("\tfoo\n", &options, false), "    foo\n");
+    }
+
+    #[test]
+    fn test_cleanup_region_is_one_undo_group() {
+        let mut state = make_state("foo   \n\n\n\n\nbar");
+        let ctx = CommandContext::new();
+
+        whitespace_cleanup_region(&mut state, &ctx).unwrap();
+        assert_eq!(
+            state.current_buffer().unwrap().text.to_string(),
+            "foo\n\nbar\n"
+        );
+
+        let cursors = &mut state.windows.current_mut().unwrap().cursors;
+        let buffer = state.buffers.current_mut().unwrap();
+        assert!(buffer.undo(cursors));
+        assert_eq!(buffer.text.to_string(), "foo   \n\n\n\n\nbar");
+    }
+
+    #[test]
+    fn test_cleanup_sub_operations_toggleable() {
+        let mut state = make_state("foo   ");
+        state.whitespace_cleanup.strip_trailing = false;
+        state.whitespace_cleanup.ensure_final_newline = false;
+
+        let ctx = CommandContext::new();
+        whitespace_cleanup_region(&mut state, &ctx).unwrap();
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "foo   ");
+    }
+}
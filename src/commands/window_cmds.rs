@@ -1,13 +1,16 @@
 use super::registry::{Command, CommandContext, CommandResult};
+use crate::state::window_mgr::LineNumberStyle;
 use crate::state::EditorState;
 
 pub fn split_window_below(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.record_window_config();
     state.windows.split_vertical();
     state.message = Some("Window split vertically".to_string());
     Ok(())
 }
 
 pub fn split_window_right(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.record_window_config();
     state.windows.split_horizontal();
     state.message = Some("Window split horizontally".to_string());
     Ok(())
@@ -15,6 +18,7 @@ pub fn split_window_right(state: &mut EditorState, _ctx: &CommandContext) -> Com
 
 pub fn delete_window(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
     if state.windows.count() > 1 {
+        state.record_window_config();
         state.windows.delete_current();
         state.message = Some("Window deleted".to_string());
     } else {
@@ -24,16 +28,182 @@ pub fn delete_window(state: &mut EditorState, _ctx: &CommandContext) -> CommandR
 }
 
 pub fn delete_other_windows(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if state.windows.count() > 1 {
+        state.record_window_config();
+    }
     state.windows.delete_others();
     state.message = Some("Deleted other windows".to_string());
     Ok(())
 }
 
+/// Restores the window configuration at `idx`, pointing any window
+/// whose buffer has since been killed at the current buffer instead.
+fn apply_window_config(state: &mut EditorState, idx: usize) {
+    let config = state.window_configs[idx].clone();
+    state.windows.restore_configuration(&config);
+
+    let fallback = state.buffers.current().map(|b| b.id);
+    for window in state.windows.iter_mut() {
+        if state.buffers.get(window.buffer_id).is_none() {
+            if let Some(id) = fallback {
+                window.buffer_id = id;
+                window.scroll_line = 0;
+                window.scroll_column = 0;
+            }
+        }
+    }
+    if let Some(id) = state.windows.current_buffer_id() {
+        state.buffers.set_current(id);
+    }
+    state.window_config_index = Some(idx);
+}
+
+/// Steps back to the previous saved window arrangement, like Emacs
+/// winner-mode.
+pub fn winner_undo(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    use super::registry::CommandError;
+
+    let idx = match state.window_config_index {
+        None => {
+            if state.window_configs.is_empty() {
+                return Err(CommandError::Other(
+                    "No window configuration to restore".to_string(),
+                ));
+            }
+            // Save the live arrangement so winner-redo can come back.
+            state.window_configs.push(state.windows.save_configuration());
+            state.window_configs.len() - 2
+        }
+        Some(0) => {
+            return Err(CommandError::Other(
+                "No earlier window configuration".to_string(),
+            ))
+        }
+        Some(i) => i - 1,
+    };
+    apply_window_config(state, idx);
+    state.message = Some(format!(
+        "Window configuration {}/{}",
+        idx + 1,
+        state.window_configs.len()
+    ));
+    Ok(())
+}
+
+/// Steps forward again after winner-undo.
+pub fn winner_redo(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    use super::registry::CommandError;
+
+    let idx = match state.window_config_index {
+        Some(i) if i + 1 < state.window_configs.len() => i + 1,
+        Some(_) => {
+            return Err(CommandError::Other(
+                "No later window configuration".to_string(),
+            ))
+        }
+        None => {
+            return Err(CommandError::Other(
+                "Not undoing window configurations".to_string(),
+            ))
+        }
+    };
+    apply_window_config(state, idx);
+    state.message = Some(format!(
+        "Window configuration {}/{}",
+        idx + 1,
+        state.window_configs.len()
+    ));
+    Ok(())
+}
+
 pub fn other_window(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
     state.windows.cycle_next();
     Ok(())
 }
 
+/// Toggles follow-mode: side-by-side windows on the same buffer become
+/// one continuous two-column view, the second continuing where the
+/// first leaves off.
+pub fn follow_mode(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.follow_mode = !state.follow_mode;
+    if state.follow_mode {
+        state.windows.sync_follow();
+        state.message = Some("Follow mode enabled".to_string());
+    } else {
+        state.message = Some("Follow mode disabled".to_string());
+    }
+    Ok(())
+}
+
+/// Cycles the gutter through off, absolute, relative and visual.
+pub fn display_line_numbers_mode(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    if let Some(window) = state.windows.current_mut() {
+        let (next, label) = match window.display_line_numbers {
+            None => (Some(LineNumberStyle::Absolute), "absolute"),
+            Some(LineNumberStyle::Absolute) => (Some(LineNumberStyle::Relative), "relative"),
+            Some(LineNumberStyle::Relative) => (Some(LineNumberStyle::Visual), "visual"),
+            Some(LineNumberStyle::Visual) => (None, "off"),
+        };
+        window.display_line_numbers = next;
+        state.message = Some(format!("Display-Line-Numbers: {}", label));
+    }
+    Ok(())
+}
+
+pub fn hl_line_mode(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.hl_line = !state.hl_line;
+    state.message = Some(if state.hl_line {
+        "Hl-line mode enabled".to_string()
+    } else {
+        "Hl-line mode disabled".to_string()
+    });
+    Ok(())
+}
+
+pub fn rainbow_delimiters_mode(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.rainbow_delimiters = !state.rainbow_delimiters;
+    state.message = Some(if state.rainbow_delimiters {
+        "Rainbow-delimiters mode enabled".to_string()
+    } else {
+        "Rainbow-delimiters mode disabled".to_string()
+    });
+    Ok(())
+}
+
+pub fn visual_line_mode(state: &mut EditorState, _ctx: &CommandContext) -> CommandResult {
+    state.visual_line_mode = !state.visual_line_mode;
+    state.message = Some(if state.visual_line_mode {
+        "Visual-line mode enabled".to_string()
+    } else {
+        "Visual-line mode disabled".to_string()
+    });
+    Ok(())
+}
+
+/// Adjusts the current window's text-scale step count. Only the GUI
+/// frontend renders the scale; the terminal shows the message and
+/// otherwise ignores it.
+fn adjust_text_scale(state: &mut EditorState, delta: i32) {
+    if let Some(window) = state.windows.current_mut() {
+        window.text_scale = (window.text_scale + delta).clamp(-8, 8);
+        state.message = Some(if window.text_scale == 0 {
+            "Text scale: default".to_string()
+        } else {
+            format!("Text scale: {:+}", window.text_scale)
+        });
+    }
+}
+
+pub fn text_scale_increase(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    adjust_text_scale(state, ctx.repeat_count() as i32);
+    Ok(())
+}
+
+pub fn text_scale_decrease(state: &mut EditorState, ctx: &CommandContext) -> CommandResult {
+    adjust_text_scale(state, -(ctx.repeat_count() as i32));
+    Ok(())
+}
+
 pub fn all_commands() -> Vec<Command> {
     vec![
         Command::new("split-window-below", split_window_below),
@@ -41,5 +211,108 @@ pub fn all_commands() -> Vec<Command> {
         Command::new("delete-window", delete_window),
         Command::new("delete-other-windows", delete_other_windows),
         Command::new("other-window", other_window),
+        Command::new("winner-undo", winner_undo),
+        Command::new("winner-redo", winner_redo),
+        Command::new("follow-mode", follow_mode),
+        Command::new("display-line-numbers-mode", display_line_numbers_mode),
+        Command::new("hl-line-mode", hl_line_mode),
+        Command::new("rainbow-delimiters-mode", rainbow_delimiters_mode),
+        Command::new("visual-line-mode", visual_line_mode),
+        Command::new("text-scale-increase", text_scale_increase),
+        Command::new("text-scale-decrease", text_scale_decrease),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Buffer;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    #[test]
+    fn test_winner_undo_restores_the_previous_layout() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        split_window_below(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.count(), 2);
+
+        winner_undo(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.count(), 1);
+
+        winner_redo(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.count(), 2);
+    }
+
+    #[test]
+    fn test_winner_undo_brings_back_deleted_splits() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        split_window_below(&mut state, &ctx).unwrap();
+        split_window_right(&mut state, &ctx).unwrap();
+        delete_other_windows(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.count(), 1);
+
+        winner_undo(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.count(), 3);
+
+        // Stepping back twice more reaches the original single window.
+        winner_undo(&mut state, &ctx).unwrap();
+        winner_undo(&mut state, &ctx).unwrap();
+        assert_eq!(state.windows.count(), 1);
+        assert!(winner_undo(&mut state, &ctx).is_err());
+    }
+
+    #[test]
+    fn test_follow_mode_chains_windows_over_one_buffer() {
+        let content = (0..200).map(|i| format!("line {}\n", i)).collect::<String>();
+        let mut state = make_state(&content);
+        let ctx = CommandContext::new();
+        state.set_dimensions(80, 24);
+
+        split_window_right(&mut state, &ctx).unwrap();
+        follow_mode(&mut state, &ctx).unwrap();
+
+        let windows: Vec<_> = state.windows.iter().collect();
+        // The right window continues where the left leaves off.
+        assert_eq!(windows[0].scroll_line, 0);
+        assert_eq!(
+            windows[1].scroll_line,
+            windows[0].height.saturating_sub(1) as usize
+        );
+
+        // Scrolling the current window drags the other one along.
+        {
+            let window = state.windows.current_mut().unwrap();
+            window.scroll_line = 10;
+            // Line 15 ("line 15" starts at char 110) stays visible, so
+            // ensure_cursor_visible keeps the scroll where we put it.
+            window.cursors.primary.set_position(crate::core::CharOffset(110));
+        }
+        state.run_command("forward-char");
+        let windows: Vec<_> = state.windows.iter().collect();
+        assert_eq!(windows[0].scroll_line, 10);
+        assert_eq!(
+            windows[1].scroll_line,
+            10 + windows[0].height.saturating_sub(1) as usize
+        );
+    }
+
+    #[test]
+    fn test_winner_undo_without_history_reports_an_error() {
+        let mut state = make_state("hello");
+        let ctx = CommandContext::new();
+
+        assert!(winner_undo(&mut state, &ctx).is_err());
+        assert!(winner_redo(&mut state, &ctx).is_err());
+    }
+}
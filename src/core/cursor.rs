@@ -25,6 +25,9 @@ pub struct Cursor {
     pub id: CursorId,
     pub position: CharOffset,
     pub goal_column: Option<usize>,
+    /// Column pinned by `set-goal-column`; unlike the transient
+    /// `goal_column` it survives non-vertical motion until cleared.
+    pub pinned_goal: Option<usize>,
     pub mark: Option<CharOffset>,
     pub mark_active: bool,
     pub kill_ring: KillRing,
@@ -36,6 +39,7 @@ impl Default for Cursor {
             id: CursorId::new(),
             position: CharOffset(0),
             goal_column: None,
+            pinned_goal: None,
             mark: None,
             mark_active: false,
             kill_ring: KillRing::default(),
@@ -49,6 +53,7 @@ impl Cursor {
             id: CursorId::new(),
             position,
             goal_column: None,
+            pinned_goal: None,
             mark: None,
             mark_active: false,
             kill_ring: KillRing::default(),
@@ -101,10 +106,22 @@ impl Cursor {
     }
 }
 
+/// Bookkeeping for incremental `mark-next-like-this`: the anchor word,
+/// the point's offset inside it, and the cursors added so far (so the
+/// last one can be unmarked).
+#[derive(Debug, Clone)]
+pub struct MarkNextState {
+    pub word: String,
+    pub cursor_offset: usize,
+    pub added: Vec<CursorId>,
+}
+
 #[derive(Debug, Clone)]
 pub struct CursorSet {
     pub primary: Cursor,
     pub secondary: Vec<Cursor>,
+    /// Boxed so cursor-set clones (undo snapshots) stay small.
+    pub mark_next: Option<Box<MarkNextState>>,
 }
 
 impl Default for CursorSet {
@@ -112,6 +129,7 @@ impl Default for CursorSet {
         Self {
             primary: Cursor::default(),
             secondary: Vec::new(),
+            mark_next: None,
         }
     }
 }
@@ -125,6 +143,7 @@ impl CursorSet {
         Self {
             primary: Cursor::new(position),
             secondary: Vec::new(),
+            mark_next: None,
         }
     }
 
@@ -140,21 +159,30 @@ impl CursorSet {
         1 + self.secondary.len()
     }
 
-    pub fn add_cursor(&mut self, position: CharOffset) {
+    pub fn add_cursor(&mut self, position: CharOffset) -> Option<CursorId> {
         for cursor in self.all_cursors() {
             if cursor.position == position {
-                return;
+                return None;
             }
         }
 
         let mut cursor = Cursor::new(position);
         cursor.kill_ring = self.primary.kill_ring.clone();
+        let id = cursor.id;
         self.secondary.push(cursor);
         self.sort();
+        Some(id)
+    }
+
+    /// Drops the secondary cursor with `id`; the primary is never
+    /// removed.
+    pub fn remove_cursor(&mut self, id: CursorId) {
+        self.secondary.retain(|c| c.id != id);
     }
 
     pub fn remove_secondary_cursors(&mut self) {
         self.secondary.clear();
+        self.mark_next = None;
     }
 
     pub fn sort(&mut self) {
@@ -6,7 +6,7 @@ pub mod position;
 pub mod rope_ext;
 pub mod undo;
 
-pub use buffer::{Buffer, BufferId, BufferMode};
+pub use buffer::{detect_mode, Buffer, BufferId, BufferMode, FileEncoding, LineEnding, MajorMode};
 pub use cursor::{Cursor, CursorId, CursorSet};
 pub use kill_ring::KillRing;
 pub use mark::{Mark, MarkRing};
@@ -128,10 +128,255 @@ pub fn find_word_boundary_backward(rope: &Rope, start: CharOffset) -> CharOffset
     }
 }
 
+/// Every occurrence of `needle` in `rope` (including overlapping ones),
+/// as char offsets of the match starts. Streams over the rope's chunks
+/// instead of materializing the whole text, keeping a `needle.len() - 1`
+/// byte tail across chunk boundaries so spanning matches are found.
+pub fn search_all(rope: &Rope, needle: &str) -> Vec<CharOffset> {
+    let mut matches = Vec::new();
+    if needle.is_empty() {
+        return matches;
+    }
+
+    let overlap = needle.len() - 1;
+    let first_char_len = needle.chars().next().map(char::len_utf8).unwrap_or(1);
+    let mut window = String::new();
+    // Char offset of the window's first char within the rope.
+    let mut window_start = 0usize;
+
+    for chunk in rope.chunks() {
+        window.push_str(chunk);
+
+        let mut from = 0;
+        while let Some(found) = window[from..].find(needle) {
+            let start = from + found;
+            matches.push(CharOffset(window_start + window[..start].chars().count()));
+            from = start + first_char_len;
+        }
+
+        // Keep just enough tail to complete a match spanning into the
+        // next chunk. The tail is shorter than the needle, so a match
+        // found there next round cannot be a duplicate.
+        let mut keep_from = window.len().saturating_sub(overlap);
+        while !window.is_char_boundary(keep_from) {
+            keep_from -= 1;
+        }
+        window_start += window[..keep_from].chars().count();
+        window.drain(..keep_from);
+    }
+
+    matches
+}
+
+fn matching_close_bracket(c: char) -> Option<char> {
+    match c {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+fn matching_open_bracket(c: char) -> Option<char> {
+    match c {
+        ')' => Some('('),
+        ']' => Some('['),
+        '}' => Some('{'),
+        _ => None,
+    }
+}
+
+/// Scans forward over one balanced expression from `start`: a bracketed
+/// group (nesting respected, string contents skipped), a string, or a
+/// word atom. Returns `None` at a mismatched or unbalanced bracket.
+pub fn find_sexp_forward(rope: &Rope, start: CharOffset) -> Option<CharOffset> {
+    let len = rope.len_chars();
+    let mut pos = start.0.min(len);
+
+    while pos < len && rope.char(pos).is_whitespace() {
+        pos += 1;
+    }
+    if pos >= len {
+        return None;
+    }
+
+    let c = rope.char(pos);
+    if matching_close_bracket(c).is_some() {
+        let mut stack: Vec<char> = Vec::new();
+        let mut in_string = false;
+        while pos < len {
+            let ch = rope.char(pos);
+            pos += 1;
+            if in_string {
+                if ch == '"' {
+                    in_string = false;
+                }
+                continue;
+            }
+            if ch == '"' {
+                in_string = true;
+            } else if let Some(close) = matching_close_bracket(ch) {
+                stack.push(close);
+            } else if matching_open_bracket(ch).is_some() {
+                if stack.pop() != Some(ch) {
+                    return None;
+                }
+                if stack.is_empty() {
+                    return Some(CharOffset(pos));
+                }
+            }
+        }
+        return None;
+    }
+    if c == '"' {
+        pos += 1;
+        while pos < len {
+            if rope.char(pos) == '"' {
+                return Some(CharOffset(pos + 1));
+            }
+            pos += 1;
+        }
+        return None;
+    }
+    // A bare closer means point is at the end of its expression
+    if matching_open_bracket(c).is_some() {
+        return None;
+    }
+    if Rope::is_word_char(c) {
+        while pos < len && Rope::is_word_char(rope.char(pos)) {
+            pos += 1;
+        }
+        return Some(CharOffset(pos));
+    }
+    Some(CharOffset(pos + 1))
+}
+
+/// The mirror of [`find_sexp_forward`]: scans backward over one
+/// balanced expression ending just before `start`.
+pub fn find_sexp_backward(rope: &Rope, start: CharOffset) -> Option<CharOffset> {
+    let len = rope.len_chars();
+    let mut pos = start.0.min(len);
+
+    while pos > 0 && rope.char(pos - 1).is_whitespace() {
+        pos -= 1;
+    }
+    if pos == 0 {
+        return None;
+    }
+
+    let c = rope.char(pos - 1);
+    if matching_open_bracket(c).is_some() {
+        let mut stack: Vec<char> = Vec::new();
+        let mut in_string = false;
+        while pos > 0 {
+            pos -= 1;
+            let ch = rope.char(pos);
+            if in_string {
+                if ch == '"' {
+                    in_string = false;
+                }
+                continue;
+            }
+            if ch == '"' {
+                in_string = true;
+            } else if let Some(open) = matching_open_bracket(ch) {
+                stack.push(open);
+            } else if matching_close_bracket(ch).is_some() {
+                if stack.pop() != Some(ch) {
+                    return None;
+                }
+                if stack.is_empty() {
+                    return Some(CharOffset(pos));
+                }
+            }
+        }
+        return None;
+    }
+    if c == '"' {
+        pos -= 1;
+        while pos > 0 {
+            pos -= 1;
+            if rope.char(pos) == '"' {
+                return Some(CharOffset(pos));
+            }
+        }
+        return None;
+    }
+    if matching_close_bracket(c).is_some() {
+        return None;
+    }
+    if Rope::is_word_char(c) {
+        while pos > 0 && Rope::is_word_char(rope.char(pos - 1)) {
+            pos -= 1;
+        }
+        return Some(CharOffset(pos));
+    }
+    Some(CharOffset(pos - 1))
+}
+
+/// Nesting depth of every bracket in `rope`, for rainbow-delimiter
+/// rendering. An opener and its matching closer share a depth (0 at the
+/// top level); string contents are skipped like the sexp scanners, and
+/// unmatched closers report depth 0.
+pub fn bracket_depths(rope: &Rope) -> std::collections::HashMap<CharOffset, usize> {
+    let mut depths = std::collections::HashMap::new();
+    let mut stack: Vec<char> = Vec::new();
+    let mut in_string = false;
+
+    for (pos, ch) in rope.chars().enumerate() {
+        if in_string {
+            if ch == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        if ch == '"' {
+            in_string = true;
+        } else if matching_close_bracket(ch).is_some() {
+            depths.insert(CharOffset(pos), stack.len());
+            stack.push(ch);
+        } else if matching_open_bracket(ch).is_some() {
+            if stack.last().copied() == matching_open_bracket(ch) {
+                stack.pop();
+            }
+            depths.insert(CharOffset(pos), stack.len());
+        }
+    }
+    depths
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sexp_forward_over_nested_group() {
+        let rope = Rope::from_str("(foo [bar] \"(\") tail");
+        // The string content hides its bracket from the scanner
+        assert_eq!(find_sexp_forward(&rope, CharOffset(0)), Some(CharOffset(15)));
+        // From inside, an atom is a single sexp
+        assert_eq!(find_sexp_forward(&rope, CharOffset(1)), Some(CharOffset(4)));
+    }
+
+    #[test]
+    fn test_sexp_forward_detects_imbalance() {
+        let rope = Rope::from_str("(foo [bar)");
+        assert_eq!(find_sexp_forward(&rope, CharOffset(0)), None);
+
+        let rope = Rope::from_str("(never closed");
+        assert_eq!(find_sexp_forward(&rope, CharOffset(0)), None);
+    }
+
+    #[test]
+    fn test_sexp_backward_over_group_and_atom() {
+        let rope = Rope::from_str("head (foo [bar])");
+        assert_eq!(
+            find_sexp_backward(&rope, CharOffset(16)),
+            Some(CharOffset(5))
+        );
+        assert_eq!(find_sexp_backward(&rope, CharOffset(4)), Some(CharOffset(0)));
+    }
+
     #[test]
     fn test_char_to_position() {
         let rope = Rope::from_str("hello\nworld\n");
@@ -167,6 +412,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_search_all_finds_overlapping_matches() {
+        let rope = Rope::from_str("aaaa");
+        assert_eq!(
+            search_all(&rope, "aa"),
+            vec![CharOffset(0), CharOffset(1), CharOffset(2)]
+        );
+        assert_eq!(search_all(&rope, ""), vec![]);
+    }
+
+    #[test]
+    fn test_search_all_counts_chars_not_bytes() {
+        let rope = Rope::from_str("héllo héllo");
+        assert_eq!(search_all(&rope, "él"), vec![CharOffset(1), CharOffset(7)]);
+    }
+
+    #[test]
+    fn test_search_all_agrees_with_naive_scan_across_chunks() {
+        // Large enough to span many rope chunks; the 17-char unit keeps
+        // the needle landing on arbitrary chunk boundaries.
+        let unit = "padding: needle; ";
+        let text = unit.repeat(3000);
+        let rope = Rope::from_str(&text);
+
+        let found = search_all(&rope, "needle");
+        assert_eq!(found.len(), 3000);
+        for (i, offset) in found.iter().enumerate() {
+            assert_eq!(offset.0, 9 + i * unit.len());
+        }
+    }
+
+    #[test]
+    fn test_bracket_depths_cycle_with_nesting() {
+        let rope = Rope::from_str("(a [b] \"(\" c)");
+        let depths = bracket_depths(&rope);
+
+        assert_eq!(depths.get(&CharOffset(0)), Some(&0));
+        assert_eq!(depths.get(&CharOffset(3)), Some(&1));
+        assert_eq!(depths.get(&CharOffset(5)), Some(&1));
+        assert_eq!(depths.get(&CharOffset(12)), Some(&0));
+        // The paren inside the string is not a delimiter.
+        assert_eq!(depths.get(&CharOffset(8)), None);
+    }
+
     #[test]
     fn test_word_boundary_backward() {
         let rope = Rope::from_str("hello world foo");
@@ -26,6 +26,8 @@ impl Edit {
 pub struct UndoEntry {
     pub edits: Vec<Edit>,
     pub cursors_before: Option<CursorSet>,
+    /// True for entries pushed by `undo` itself (redo records).
+    pub from_undo: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -398,6 +400,7 @@ impl UndoTree {
         let entry = UndoEntry {
             edits: std::mem::take(&mut self.pending_edits),
             cursors_before: self.pending_cursors.take(),
+            from_undo: false,
         };
         self.entries.push(entry);
 
@@ -426,6 +429,7 @@ impl UndoTree {
         self.entries.push(UndoEntry {
             edits: inverse_edits.clone(),
             cursors_before: None,
+            from_undo: true,
         });
 
         self.undo_index = Some(idx - 1);
@@ -445,17 +449,70 @@ impl UndoTree {
         }
     }
 
-    pub fn redo(&mut self) -> UndoResult {
+    /// Like `undo`, but never re-applies an undone edit: redo records in
+    /// the ring are skipped over instead of being walked back through.
+    pub fn undo_only(&mut self) -> UndoResult {
+        self.flush_pending();
+
+        let mut idx = self.undo_index.unwrap_or(self.entries.len());
+        while idx > 0 && self.entries[idx - 1].from_undo {
+            idx -= 1;
+        }
+        if idx == 0 {
+            return UndoResult::Nothing;
+        }
+
+        self.undo_index = Some(idx);
         self.undo()
     }
 
+    /// Re-applies the edit most recently undone by `undo`, following
+    /// `undo_index` forward. Pops the redo record that undo pushed so
+    /// the ring ends up as it was before the undo.
+    pub fn redo(&mut self) -> UndoResult {
+        self.flush_pending();
+
+        let idx = match self.undo_index {
+            Some(i) if i < self.entries.len() => i,
+            _ => return UndoResult::Nothing,
+        };
+
+        if self.entries.len() > idx + 1 && self.entries.last().map(|e| e.from_undo) == Some(true) {
+            self.entries.pop();
+        }
+
+        let entry = &self.entries[idx];
+        let edits = entry.edits.clone();
+
+        self.undo_index = if idx + 1 < self.entries.len() {
+            Some(idx + 1)
+        } else {
+            None
+        };
+
+        UndoResult::Apply {
+            edits: edits
+                .into_iter()
+                .map(|e| match e {
+                    Edit::Insert { position, text } => UndoEdit::Insert { position, text },
+                    Edit::Delete { position, text } => UndoEdit::Delete {
+                        position,
+                        len: text.chars().count(),
+                    },
+                })
+                .collect(),
+            restore_cursors: None,
+        }
+    }
+
     pub fn can_undo(&self) -> bool {
         let idx = self.undo_index.unwrap_or(self.entries.len());
         idx > 0 || !self.pending_edits.is_empty()
     }
 
     pub fn can_redo(&self) -> bool {
-        self.can_undo()
+        matches!(self.undo_index, Some(i) if i < self.entries.len())
+            && self.pending_edits.is_empty()
     }
 
     fn gc_if_needed(&mut self) {
@@ -617,6 +674,102 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_redo_restores_undone_edit() {
+        let mut tree = UndoTree::new(100);
+        tree.record_insert(CharOffset(0), "hello".into());
+        tree.add_boundary();
+
+        assert!(!tree.can_redo());
+        match tree.undo() {
+            UndoResult::Apply { edits, .. } => {
+                assert!(matches!(edits[0], UndoEdit::Delete { .. }));
+            }
+            _ => panic!("Expected Apply"),
+        }
+
+        assert!(tree.can_redo());
+        match tree.redo() {
+            UndoResult::Apply { edits, .. } => match &edits[0] {
+                UndoEdit::Insert { position, text } => {
+                    assert_eq!(*position, CharOffset(0));
+                    assert_eq!(text, "hello");
+                }
+                _ => panic!("Expected insert"),
+            },
+            _ => panic!("Expected Apply"),
+        }
+
+        assert!(!tree.can_redo());
+        assert!(matches!(tree.redo(), UndoResult::Nothing));
+    }
+
+    #[test]
+    fn test_redo_walks_forward_through_multiple_undos() {
+        let mut tree = UndoTree::new(100);
+        tree.record_insert(CharOffset(0), "aa".into());
+        tree.add_boundary();
+        tree.record_insert(CharOffset(2), "bb".into());
+        tree.add_boundary();
+
+        tree.undo();
+        tree.undo();
+
+        match tree.redo() {
+            UndoResult::Apply { edits, .. } => match &edits[0] {
+                UndoEdit::Insert { text, .. } => assert_eq!(text, "aa"),
+                _ => panic!("Expected insert"),
+            },
+            _ => panic!("Expected Apply"),
+        }
+        match tree.redo() {
+            UndoResult::Apply { edits, .. } => match &edits[0] {
+                UndoEdit::Insert { text, .. } => assert_eq!(text, "bb"),
+                _ => panic!("Expected insert"),
+            },
+            _ => panic!("Expected Apply"),
+        }
+        assert!(!tree.can_redo());
+    }
+
+    #[test]
+    fn test_new_edit_clears_redo() {
+        let mut tree = UndoTree::new(100);
+        tree.record_insert(CharOffset(0), "hello".into());
+        tree.add_boundary();
+        tree.undo();
+
+        tree.record_insert(CharOffset(0), "x".into());
+        tree.add_boundary();
+
+        assert!(!tree.can_redo());
+    }
+
+    #[test]
+    fn test_undo_only_skips_redo_records() {
+        let mut tree = UndoTree::new(100);
+        tree.record_insert(CharOffset(0), "aa".into());
+        tree.add_boundary();
+        tree.record_insert(CharOffset(2), "bb".into());
+        tree.add_boundary();
+
+        // A plain undo followed by undo_only should keep walking back
+        // to "aa" instead of re-applying "bb".
+        tree.undo();
+        match tree.undo_only() {
+            UndoResult::Apply { edits, .. } => match &edits[0] {
+                UndoEdit::Delete { position, len } => {
+                    assert_eq!(*position, CharOffset(0));
+                    assert_eq!(*len, 2);
+                }
+                _ => panic!("Expected delete"),
+            },
+            _ => panic!("Expected Apply"),
+        }
+
+        assert!(matches!(tree.undo_only(), UndoResult::Nothing));
+    }
+
     #[test]
     fn test_emacs_style_undo_traversal() {
         let mut tree = UndoTree::new(100);
@@ -0,0 +1,150 @@
+use crate::core::rope_ext::RopeExt;
+use crate::state::EditorState;
+
+/// Access to the system clipboard and, where available, the primary
+/// selection (the X11/Wayland select-to-copy, middle-click-to-paste buffer).
+pub trait ClipboardProvider {
+    /// Whether this platform exposes a primary selection at all.
+    fn supports_primary(&self) -> bool;
+
+    fn set_primary(&mut self, text: &str);
+
+    fn get_primary(&mut self) -> Option<String>;
+}
+
+/// Provider for platforms without clipboard integration. All operations
+/// are no-ops.
+#[derive(Debug, Default)]
+pub struct NoClipboard;
+
+impl ClipboardProvider for NoClipboard {
+    fn supports_primary(&self) -> bool {
+        false
+    }
+
+    fn set_primary(&mut self, _text: &str) {}
+
+    fn get_primary(&mut self) -> Option<String> {
+        None
+    }
+}
+
+/// In-memory provider used on platforms with a primary selection until a
+/// real system integration is wired up, and by tests.
+#[derive(Debug, Default)]
+pub struct LocalClipboard {
+    primary: Option<String>,
+}
+
+impl LocalClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ClipboardProvider for LocalClipboard {
+    fn supports_primary(&self) -> bool {
+        true
+    }
+
+    fn set_primary(&mut self, text: &str) {
+        self.primary = Some(text.to_string());
+    }
+
+    fn get_primary(&mut self) -> Option<String> {
+        self.primary.clone()
+    }
+}
+
+/// Returns the platform-appropriate clipboard provider.
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd"))]
+pub fn platform_clipboard() -> Box<dyn ClipboardProvider> {
+    Box::new(LocalClipboard::new())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd")))]
+pub fn platform_clipboard() -> Box<dyn ClipboardProvider> {
+    Box::new(NoClipboard)
+}
+
+/// Copies the primary cursor's active region into the primary selection,
+/// mirroring the X11 select-to-copy convention. Returns true if the
+/// selection was updated.
+pub fn sync_primary_selection(
+    state: &EditorState,
+    clipboard: &mut dyn ClipboardProvider,
+) -> bool {
+    if !clipboard.supports_primary() {
+        return false;
+    }
+
+    let window = match state.current_window() {
+        Some(w) => w,
+        None => return false,
+    };
+
+    let buffer = match state.buffers.get(window.buffer_id) {
+        Some(b) => b,
+        None => return false,
+    };
+
+    let (start, end) = match window.cursors.primary.region() {
+        Some(region) => region,
+        None => return false,
+    };
+
+    if start.0 >= buffer.text.total_chars() {
+        return false;
+    }
+
+    let text = buffer.slice(start, end);
+    if text.is_empty() {
+        return false;
+    }
+
+    clipboard.set_primary(&text);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::position::CharOffset;
+    use crate::core::Buffer;
+
+    fn make_state(content: &str) -> EditorState {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", content);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+    }
+
+    #[test]
+    fn test_primary_selection_updated_on_active_region() {
+        let mut state = make_state("hello world");
+        let mut clipboard = LocalClipboard::new();
+
+        assert!(!sync_primary_selection(&state, &mut clipboard));
+
+        let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+        cursor.position = CharOffset(5);
+        cursor.set_mark(CharOffset(0));
+
+        assert!(sync_primary_selection(&state, &mut clipboard));
+        assert_eq!(clipboard.get_primary().as_deref(), Some("hello"));
+    }
+
+    #[test]
+    fn test_no_clipboard_ignores_region() {
+        let mut state = make_state("hello");
+        let mut clipboard = NoClipboard;
+
+        let cursor = &mut state.windows.current_mut().unwrap().cursors.primary;
+        cursor.position = CharOffset(5);
+        cursor.set_mark(CharOffset(0));
+
+        assert!(!sync_primary_selection(&state, &mut clipboard));
+    }
+}
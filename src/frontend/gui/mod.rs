@@ -14,6 +14,7 @@ use wgpu::{
     ShaderStages, StoreOp, Surface, SurfaceConfiguration, TextureFormat, TextureUsages,
     TextureViewDescriptor, VertexState,
 };
+use unicode_width::UnicodeWidthChar;
 use winit::{
     application::ApplicationHandler,
     dpi::{LogicalSize, PhysicalSize},
@@ -23,16 +24,162 @@ use winit::{
     window::{Window, WindowAttributes, WindowId},
 };
 
+use crate::commands::markdown::MdStyle;
 use crate::keybinding::key::Modifiers;
 use crate::keybinding::KeyEvent;
 use crate::state::EditorState;
 
+use super::clipboard::{platform_clipboard, sync_primary_selection, ClipboardProvider};
 use super::traits::{Frontend, FrontendCapabilities, FrontendError};
 
+// Defaults; `set-font` can change both at runtime.
 const FONT_SIZE: f32 = 28.0;
 const CELL_HEIGHT: f32 = FONT_SIZE;
 const FONT_FAMILY: &str = "Comic Mono";
 
+/// True when any installed face belongs to the named family, so a typo
+/// in `set-font` falls back to a system monospace instead of rendering
+/// nothing.
+fn family_available(font_system: &FontSystem, name: &str) -> bool {
+    font_system
+        .db()
+        .faces()
+        .any(|face| {
+            face.families
+                .iter()
+                .any(|(family, _)| family.eq_ignore_ascii_case(name))
+        })
+}
+
+/// Appearance settings for the GUI window.
+#[derive(Debug, Clone)]
+pub struct GuiConfig {
+    /// Window opacity in `0.0..=1.0`; anything below 1.0 requests a
+    /// translucent window when the platform supports it.
+    pub window_opacity: f32,
+    /// Path to an image drawn behind the text. Not rendered yet;
+    /// reserved so configs can set it ahead of time.
+    pub background_image: Option<std::path::PathBuf>,
+    /// Shape drawn for the primary cursor.
+    pub cursor_shape: CursorShape,
+    /// Time between blink phases; `None` keeps the cursor solid.
+    pub cursor_blink_interval: Option<std::time::Duration>,
+}
+
+impl Default for GuiConfig {
+    fn default() -> Self {
+        Self {
+            window_opacity: 1.0,
+            background_image: None,
+            cursor_shape: CursorShape::Block,
+            cursor_blink_interval: Some(std::time::Duration::from_millis(500)),
+        }
+    }
+}
+
+impl GuiConfig {
+    /// Builds a config from the environment (`ENACS_OPACITY`,
+    /// `ENACS_CURSOR_SHAPE`, `ENACS_CURSOR_BLINK`).
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(raw) = std::env::var("ENACS_OPACITY") {
+            if let Some(opacity) = parse_opacity(&raw) {
+                config.window_opacity = opacity;
+            }
+        }
+        if let Ok(raw) = std::env::var("ENACS_CURSOR_SHAPE") {
+            if let Some(shape) = CursorShape::parse(&raw) {
+                config.cursor_shape = shape;
+            }
+        }
+        if let Ok(raw) = std::env::var("ENACS_CURSOR_BLINK") {
+            if let Some(interval) = parse_blink_interval(&raw) {
+                config.cursor_blink_interval = interval;
+            }
+        }
+        config
+    }
+}
+
+/// Shape of the primary cursor; secondary cursors stay solid blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorShape {
+    Block,
+    Bar,
+    Underline,
+}
+
+impl CursorShape {
+    /// Parses a shape name as used by `ENACS_CURSOR_SHAPE`.
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "block" => Some(Self::Block),
+            "bar" => Some(Self::Bar),
+            "underline" => Some(Self::Underline),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a blink interval in milliseconds; `0` disables blinking.
+fn parse_blink_interval(raw: &str) -> Option<Option<std::time::Duration>> {
+    let ms: u64 = raw.trim().parse().ok()?;
+    if ms == 0 {
+        Some(None)
+    } else {
+        Some(Some(std::time::Duration::from_millis(ms)))
+    }
+}
+
+/// Thickness of bar and underline cursors and of the hollow outline.
+const CURSOR_LINE_THICKNESS: f32 = 2.0;
+
+/// Shrinks a full-cell rect to the requested cursor shape.
+fn cursor_shape_rect(shape: CursorShape, cell: [f32; 4]) -> [f32; 4] {
+    let [x, y, w, h] = cell;
+    match shape {
+        CursorShape::Block => cell,
+        CursorShape::Bar => [x, y, CURSOR_LINE_THICKNESS.min(w), h],
+        CursorShape::Underline => {
+            let t = CURSOR_LINE_THICKNESS.min(h);
+            [x, y + h - t, w, t]
+        }
+    }
+}
+
+/// Splits a cell rect into the four strips of a hollow box, drawn for
+/// the primary cursor while the window is unfocused.
+fn hollow_cursor_rects(cell: [f32; 4]) -> [[f32; 4]; 4] {
+    let [x, y, w, h] = cell;
+    let t = CURSOR_LINE_THICKNESS.min(w).min(h);
+    [
+        [x, y, w, t],
+        [x, y + h - t, w, t],
+        [x, y, t, h],
+        [x + w - t, y, t, h],
+    ]
+}
+
+/// Parses an opacity value, clamping it into `0.0..=1.0`.
+fn parse_opacity(raw: &str) -> Option<f32> {
+    let value: f32 = raw.trim().parse().ok()?;
+    if value.is_finite() {
+        Some(value.clamp(0.0, 1.0))
+    } else {
+        None
+    }
+}
+
+/// Chooses a composite alpha mode for the requested opacity, falling
+/// back to opaque when the surface can't do transparency.
+fn select_alpha_mode(opacity: f32, supported: &[CompositeAlphaMode]) -> CompositeAlphaMode {
+    if opacity < 1.0 && supported.contains(&CompositeAlphaMode::PreMultiplied) {
+        CompositeAlphaMode::PreMultiplied
+    } else {
+        CompositeAlphaMode::Opaque
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 #[allow(dead_code)]
 struct Theme {
@@ -40,11 +187,25 @@ struct Theme {
     foreground: GlyphonColor,
     cursor_bg: [f32; 4],
     cursor_fg: GlyphonColor,
+    cursor_region_bg: [f32; 4],
+    cursor_overwrite_bg: [f32; 4],
     selection: [f32; 4],
     modeline_bg: [f32; 4],
     modeline_fg: GlyphonColor,
 }
 
+/// Picks the cursor color for the current editor state so the cursor
+/// doubles as a subtle mode indicator.
+fn cursor_color(theme: &Theme, region_active: bool, overwrite: bool) -> [f32; 4] {
+    if overwrite {
+        theme.cursor_overwrite_bg
+    } else if region_active {
+        theme.cursor_region_bg
+    } else {
+        theme.cursor_bg
+    }
+}
+
 fn hex_to_rgba(hex: u32) -> [f32; 4] {
     let r = ((hex >> 16) & 0xFF) as f32 / 255.0;
     let g = ((hex >> 8) & 0xFF) as f32 / 255.0;
@@ -78,7 +239,7 @@ fn expand_tabs(s: &str, tab_width: usize) -> String {
             col += spaces;
         } else {
             result.push(ch);
-            col += 1;
+            col += ch.width().unwrap_or(1);
         }
     }
     result
@@ -104,12 +265,43 @@ fn char_col_to_visual_col(line: &str, char_col: usize) -> usize {
         if ch == '\t' {
             visual_col += TAB_WIDTH - (visual_col % TAB_WIDTH);
         } else {
-            visual_col += 1;
+            visual_col += ch.width().unwrap_or(1);
         }
     }
     visual_col
 }
 
+/// Like [`char_col_to_visual_col`], but in a window horizontally
+/// scrolled by `scroll_col` chars. Returns `None` when the char is
+/// hidden behind the left edge; the first visible cell holds the `$`
+/// truncation marker when scrolled.
+fn scrolled_visual_col(line: &str, char_col: usize, scroll_col: usize) -> Option<usize> {
+    if scroll_col == 0 {
+        return Some(char_col_to_visual_col(line, char_col));
+    }
+    let skip = scroll_col + 1;
+    if char_col < skip {
+        return None;
+    }
+    let rest: String = line.chars().skip(skip).collect();
+    Some(1 + char_col_to_visual_col(&rest, char_col - skip))
+}
+
+fn visual_col_to_char_col(line: &str, visual_col: usize) -> usize {
+    let mut current = 0;
+    for (i, ch) in line.chars().enumerate() {
+        if current >= visual_col {
+            return i;
+        }
+        if ch == '\t' {
+            current += TAB_WIDTH - (current % TAB_WIDTH);
+        } else {
+            current += ch.width().unwrap_or(1);
+        }
+    }
+    line.chars().count()
+}
+
 impl Default for Theme {
     fn default() -> Self {
         // Modus Operandi - light theme
@@ -118,6 +310,8 @@ impl Default for Theme {
             foreground: hex_to_color(0x000000),
             cursor_bg: hex_to_rgba(0x000000),
             cursor_fg: hex_to_color(0xffffff),
+            cursor_region_bg: hex_to_rgba(0x531ab6),
+            cursor_overwrite_bg: hex_to_rgba(0xa60000),
             selection: hex_to_rgba_alpha(0xbdbdbd, 0.8),
             modeline_bg: hex_to_rgba(0xc4c4c4),
             modeline_fg: hex_to_color(0x000000),
@@ -125,6 +319,42 @@ impl Default for Theme {
     }
 }
 
+/// Looks up a built-in theme by the name stored in the editor state.
+fn theme_by_name(name: &str) -> Option<Theme> {
+    match name {
+        "modus-operandi" => Some(Theme::default()),
+        // Modus Vivendi - dark counterpart
+        "modus-vivendi" => Some(Theme {
+            background: hex_to_rgba(0x000000),
+            foreground: hex_to_color(0xffffff),
+            cursor_bg: hex_to_rgba(0xffffff),
+            cursor_fg: hex_to_color(0x000000),
+            cursor_region_bg: hex_to_rgba(0xb6a0ff),
+            cursor_overwrite_bg: hex_to_rgba(0xff5f59),
+            selection: hex_to_rgba_alpha(0x535353, 0.8),
+            modeline_bg: hex_to_rgba(0x303030),
+            modeline_fg: hex_to_color(0xffffff),
+        }),
+        _ => None,
+    }
+}
+
+/// Chrome theme built from an externally loaded spec; colors the spec
+/// doesn't carry are derived from the ones it does.
+fn theme_from_spec(spec: &crate::commands::theme_cmds::ThemeSpec) -> Theme {
+    Theme {
+        background: hex_to_rgba(spec.background),
+        foreground: hex_to_color(spec.foreground),
+        cursor_bg: hex_to_rgba(spec.cursor),
+        cursor_fg: hex_to_color(spec.background),
+        cursor_region_bg: hex_to_rgba(spec.cursor),
+        cursor_overwrite_bg: hex_to_rgba(spec.cursor),
+        selection: hex_to_rgba_alpha(spec.selection, 0.8),
+        modeline_bg: hex_to_rgba(spec.modeline_bg),
+        modeline_fg: hex_to_color(spec.modeline_fg),
+    }
+}
+
 pub struct GuiFrontend {
     initialized: bool,
 }
@@ -225,6 +455,27 @@ struct GuiApp {
     rows: u16,
     cell_width: f32,
     cell_height: f32,
+    clipboard: Box<dyn ClipboardProvider>,
+    pointer_pos: (f64, f64),
+    config: GuiConfig,
+    /// Whether the window has keyboard focus; unfocused windows draw a
+    /// hollow cursor instead of a filled one.
+    focused: bool,
+    /// Current blink phase of the primary cursor.
+    cursor_visible: bool,
+    last_blink: std::time::Instant,
+    /// Font in use; `set-font` replaces the compiled-in defaults.
+    font_family: String,
+    font_size: f32,
+    /// Whether `font_family` matched an installed face; when false the
+    /// renderer substitutes the system monospace.
+    font_ok: bool,
+    /// DPI scale of the monitor the window is on; font metrics are
+    /// multiplied by it so text stays crisp across displays.
+    scale_factor: f64,
+    /// Title pushed to the window last; skips the syscall when nothing
+    /// changed.
+    last_title: Option<String>,
 }
 
 impl GuiApp {
@@ -240,6 +491,17 @@ impl GuiApp {
             rows: 24,
             cell_width: FONT_SIZE * 0.6, // Placeholder, will be measured
             cell_height: CELL_HEIGHT,
+            clipboard: platform_clipboard(),
+            pointer_pos: (0.0, 0.0),
+            config: GuiConfig::from_env(),
+            focused: true,
+            cursor_visible: true,
+            last_blink: std::time::Instant::now(),
+            font_family: FONT_FAMILY.to_string(),
+            font_size: FONT_SIZE,
+            font_ok: true,
+            scale_factor: 1.0,
+            last_title: None,
         }
     }
 
@@ -275,13 +537,18 @@ impl GuiApp {
 
         let swapchain_format = TextureFormat::Bgra8UnormSrgb;
 
+        let alpha_mode = select_alpha_mode(
+            self.config.window_opacity,
+            &surface.get_capabilities(&adapter).alpha_modes,
+        );
+
         let config = SurfaceConfiguration {
             usage: TextureUsages::RENDER_ATTACHMENT,
             format: swapchain_format,
             width: size.width,
             height: size.height,
             present_mode: PresentMode::Fifo,
-            alpha_mode: CompositeAlphaMode::Opaque,
+            alpha_mode,
             view_formats: vec![],
             desired_maximum_frame_latency: 2,
         };
@@ -360,8 +627,17 @@ impl GuiApp {
 
         let viewport = Viewport::new(&gpu.device, &cache);
 
-        // Measure actual character width from the font
-        self.cell_width = Self::measure_char_width(&mut font_system);
+        // Measure actual character width from the font at the current
+        // DPI scale.
+        self.font_ok = family_available(&font_system, &self.font_family);
+        let family = if self.font_ok {
+            Family::Name(self.font_family.as_str())
+        } else {
+            Family::Monospace
+        };
+        let size = self.font_size * self.scale_factor as f32;
+        self.cell_height = size;
+        self.cell_width = Self::measure_char_width(&mut font_system, family, size);
 
         self.text = Some(TextState {
             font_system,
@@ -372,14 +648,14 @@ impl GuiApp {
         });
     }
 
-    fn measure_char_width(font_system: &mut FontSystem) -> f32 {
-        let metrics = Metrics::new(FONT_SIZE, CELL_HEIGHT);
+    fn measure_char_width(font_system: &mut FontSystem, family: Family, font_size: f32) -> f32 {
+        let metrics = Metrics::new(font_size, font_size);
         let mut buffer = GlyphonBuffer::new(font_system, metrics);
-        buffer.set_size(font_system, Some(1000.0), Some(CELL_HEIGHT));
+        buffer.set_size(font_system, Some(1000.0), Some(font_size));
         buffer.set_text(
             font_system,
             "M",
-            Attrs::new().family(Family::Name(FONT_FAMILY)),
+            Attrs::new().family(family),
             Shaping::Advanced,
         );
 
@@ -392,7 +668,52 @@ impl GuiApp {
         }
 
         // Fallback if measurement fails
-        FONT_SIZE * 0.6
+        font_size * 0.6
+    }
+
+    /// Applies a pending `set-font` request: updates the font fields,
+    /// then re-measures the cell grid and the editor layout.
+    fn apply_font_changes(&mut self) {
+        let Some(spec) = self.state.font.take() else {
+            return;
+        };
+
+        let family = spec.family.unwrap_or_else(|| self.font_family.clone());
+        let size = spec.size.unwrap_or(self.font_size);
+
+        if let Some(text) = self.text.as_ref() {
+            self.font_ok = family_available(&text.font_system, &family);
+            if !self.font_ok {
+                self.state.message = Some(format!(
+                    "Font family {} not found; using monospace",
+                    family
+                ));
+            }
+        }
+        self.font_family = family;
+        self.font_size = size;
+        self.refresh_metrics();
+    }
+
+    /// Re-measures the cell grid for the current font and DPI scale and
+    /// pushes the resulting dimensions into the editor state.
+    fn refresh_metrics(&mut self) {
+        let size = self.font_size * self.scale_factor as f32;
+        self.cell_height = size;
+        if let Some(text) = self.text.as_mut() {
+            let family = if self.font_ok {
+                Family::Name(self.font_family.as_str())
+            } else {
+                Family::Monospace
+            };
+            self.cell_width = Self::measure_char_width(&mut text.font_system, family, size);
+        }
+        if let Some(window) = &self.window {
+            let px = window.inner_size();
+            self.cols = (px.width as f32 / self.cell_width) as u16;
+            self.rows = (px.height as f32 / self.cell_height) as u16;
+            self.state.set_dimensions(self.cols, self.rows);
+        }
     }
 
     fn resize(&mut self, size: PhysicalSize<u32>) {
@@ -410,7 +731,7 @@ impl GuiApp {
         self.rows = (size.height as f32 / self.cell_height) as u16;
         
         // Content area is rows - 2 (modeline at row-2, minibuffer at row-1)
-        self.state.set_dimensions(self.cols, self.rows.saturating_sub(2));
+        self.state.set_dimensions(self.cols, self.rows);
     }
 
     fn create_rect_bind_group(gpu: &GpuState, uniforms: RectUniforms) -> BindGroup {
@@ -450,21 +771,46 @@ impl GuiApp {
         let gpu_width = gpu.config.width;
         let gpu_height = gpu.config.height;
 
+        if let Some(t) = theme_by_name(&self.state.active_theme).or_else(|| {
+            self.state
+                .custom_themes
+                .get(&self.state.active_theme)
+                .map(theme_from_spec)
+        }) {
+            self.theme = t;
+        }
         let theme = self.theme;
+        let opacity = self.config.window_opacity as f64;
         
-        // Grid layout (like terminal):
-        // - rows 0 to (rows-3): content area
-        // - row (rows-2): modeline
-        // - row (rows-1): minibuffer
-        let content_rows = self.rows.saturating_sub(2) as usize;
-        let modeline_row = self.rows.saturating_sub(2);
+        // Grid layout (like terminal): each window owns its x/y/width/height
+        // rectangle, with its bottom row reserved for its modeline; the
+        // last screen row is the minibuffer.
         let minibuffer_row = self.rows.saturating_sub(1);
-
-        // Collect render data before borrowing text mutably
-        let mut content_text = String::new();
-        let mut primary_cursor_pos: Option<(u16, u16)> = None;
-        let mut secondary_cursor_positions: Vec<(u16, u16)> = Vec::new();
-        let mut selection_rects: Vec<(u16, u16, u16)> = Vec::new(); // (col, row, width)
+        let active_id = self.state.windows.current().map(|w| w.id);
+
+        // Collect render data before borrowing text mutably. Grid
+        // coordinates are absolute (already offset by the window origin).
+        struct WindowText {
+            content: String,
+            origin: (u16, u16),
+            size: (u16, u16),
+            /// Styled runs for markdown preview windows; `None` renders
+            /// `content` with the default attributes.
+            rich: Option<Vec<(String, MdStyle)>>,
+            /// Per-window text-scale factor applied to the font metrics.
+            scale: f32,
+        }
+        let mut window_texts: Vec<WindowText> = Vec::new();
+        let mut modeline_texts: Vec<(String, (u16, u16))> = Vec::new();
+        // Cursor and selection rects are in pixels because scaled windows
+        // no longer align with the global cell grid.
+        let mut primary_cursor_rect: Option<[f32; 4]> = None;
+        let mut secondary_cursor_rects: Vec<[f32; 4]> = Vec::new();
+        let mut selection_rects: Vec<[f32; 4]> = Vec::new();
+        let mut paren_rects: Vec<[f32; 4]> = Vec::new();
+        let mut hl_line_rect: Option<[f32; 4]> = None;
+        let mut modeline_rects: Vec<(u16, u16, u16)> = Vec::new(); // (col, row, width)
+        let mut separator_rects: Vec<(u16, u16, u16)> = Vec::new(); // (col, row, height)
 
         for window in self.state.windows.iter() {
             let buffer = match self.state.buffers.get(window.buffer_id) {
@@ -474,19 +820,190 @@ impl GuiApp {
 
             use crate::core::rope_ext::RopeExt;
 
-            // Build content text line by line
-            for row in 0..content_rows {
+            let is_active = active_id == Some(window.id);
+            let has_right_neighbor = window.x + window.width < self.cols;
+            let gutter = if window.display_line_numbers.is_some() {
+                crate::state::window_mgr::LineNumberStyle::gutter_width(buffer.text.len_lines())
+            } else {
+                0
+            };
+            let text_width = if has_right_neighbor {
+                window.width.saturating_sub(1) as usize
+            } else {
+                window.width as usize
+            }
+            .saturating_sub(gutter);
+            // A scaled window fits fewer cells in the same rect, so its
+            // grid shrinks by the scale factor.
+            let scale = window.text_scale_factor();
+            let text_width = (text_width as f32 / scale) as usize;
+            let text_rows = (window.height.saturating_sub(1) as f32 / scale) as usize;
+            let origin_px = (
+                window.x as f32 * self.cell_width,
+                window.y as f32 * self.cell_height,
+            );
+            let current_line = {
+                use crate::core::rope_ext::RopeExt;
+                buffer
+                    .text
+                    .char_to_position(window.cursors.primary.position)
+                    .line
+            };
+
+            let preview_lines = self
+                .state
+                .markdown_preview
+                .as_ref()
+                .filter(|p| p.preview == window.buffer_id)
+                .map(|p| &p.lines);
+
+            // Lines outside a narrowed range render like past-EOF space.
+            let (narrow_first, narrow_last) = {
+                let (start, end) = buffer.narrow_bounds();
+                (
+                    buffer.text.char_to_position(start).line,
+                    buffer.text.char_to_position(end).line,
+                )
+            };
+
+            // Build this window's content text line by line
+            let mut content_text = String::new();
+            let mut rich: Vec<(String, MdStyle)> = Vec::new();
+            for row in 0..text_rows {
                 let line_idx = window.scroll_line + row;
-                if line_idx < buffer.text.len_lines() {
+                if line_idx < buffer.text.len_lines()
+                    && (narrow_first..=narrow_last).contains(&line_idx)
+                {
+                    if let Some(style) = window.display_line_numbers {
+                        content_text.push_str(&format!(
+                            "{:>width$} ",
+                            style.label(line_idx, current_line),
+                            width = gutter.saturating_sub(1)
+                        ));
+                    }
                     let line = buffer.text.line(line_idx);
-                    let line_str: String = line.chars().take(self.cols as usize).collect();
+                    // Columns scrolled off the left edge collapse into a
+                    // `$` truncation marker in the first cell, like the
+                    // terminal renderer.
+                    let truncated_left =
+                        window.scroll_column > 0 && buffer.text.line_len_chars(line_idx) > 0;
+                    let skip = window.scroll_column + truncated_left as usize;
+                    let mut line_str = String::new();
+                    if truncated_left {
+                        line_str.push('$');
+                    }
+                    line_str.extend(
+                        line.chars()
+                            .skip(skip)
+                            .take(text_width.saturating_sub(truncated_left as usize)),
+                    );
                     let trimmed = line_str.trim_end_matches('\n');
                     // Expand tabs to spaces
                     let expanded = expand_tabs(trimmed, TAB_WIDTH);
                     content_text.push_str(&expanded);
                     content_text.push('\n');
+
+                    if let Some(lines) = preview_lines {
+                        let mut budget = text_width;
+                        for span in lines.get(line_idx).map(|l| l.as_slice()).unwrap_or(&[]) {
+                            if budget == 0 {
+                                break;
+                            }
+                            let clipped: String = span.text.chars().take(budget).collect();
+                            budget -= clipped.chars().count();
+                            rich.push((clipped, span.style));
+                        }
+                        rich.push(("\n".to_string(), MdStyle::Text));
+
+                        // Code-block rows get a background rect
+                        if lines
+                            .get(line_idx)
+                            .map(|l| l.iter().any(|s| s.style == MdStyle::Code))
+                            .unwrap_or(false)
+                        {
+                            selection_rects.push([
+                                origin_px.0,
+                                origin_px.1 + row as f32 * self.cell_height * scale,
+                                text_width as f32 * self.cell_width * scale,
+                                self.cell_height * scale,
+                            ]);
+                        }
+                    }
                 } else {
+                    for _ in 0..gutter {
+                        content_text.push(' ');
+                    }
                     content_text.push_str("~\n");
+                    if preview_lines.is_some() {
+                        rich.push(("~\n".to_string(), MdStyle::Text));
+                    }
+                }
+            }
+            window_texts.push(WindowText {
+                content: content_text,
+                origin: (window.x, window.y),
+                size: (text_width as u16, text_rows as u16),
+                rich: preview_lines.is_some().then_some(rich),
+                scale,
+            });
+
+            let modeline_row = window.y + window.height.saturating_sub(1);
+            modeline_texts.push((self.build_modeline_text(window), (window.x, modeline_row)));
+            modeline_rects.push((window.x, modeline_row, window.width));
+
+            if has_right_neighbor {
+                separator_rects.push((
+                    window.x + window.width.saturating_sub(1),
+                    window.y,
+                    window.height,
+                ));
+            }
+
+            // hl-line: faint band behind the primary cursor's row
+            if self.state.hl_line && is_active {
+                if let Some(rel_row) = current_line
+                    .checked_sub(window.scroll_line)
+                    .filter(|r| *r < text_rows)
+                {
+                    hl_line_rect = Some([
+                        origin_px.0,
+                        origin_px.1 + rel_row as f32 * self.cell_height * scale,
+                        (gutter + text_width) as f32 * self.cell_width * scale,
+                        self.cell_height * scale,
+                    ]);
+                }
+            }
+
+            // Boxes on a matched bracket pair at point (show-paren)
+            if is_active {
+                if let Some((open, close)) = self.state.matching_bracket_pair() {
+                    for offset in [open, close] {
+                        let pos = buffer.text.char_to_position(offset);
+                        if pos.line < window.scroll_line
+                            || pos.line >= window.scroll_line + text_rows
+                        {
+                            continue;
+                        }
+                        let rel_row = pos.line - window.scroll_line;
+                        let line_text: String = buffer.text.line(pos.line).chars().collect();
+                        let visual_col = match scrolled_visual_col(
+                            &line_text,
+                            pos.column,
+                            window.scroll_column,
+                        ) {
+                            Some(col) => col,
+                            None => continue,
+                        };
+                        if visual_col < text_width {
+                            paren_rects.push([
+                                origin_px.0
+                                    + (gutter + visual_col) as f32 * self.cell_width * scale,
+                                origin_px.1 + rel_row as f32 * self.cell_height * scale,
+                                self.cell_width * scale,
+                                self.cell_height * scale,
+                            ]);
+                        }
+                    }
                 }
             }
 
@@ -498,18 +1015,27 @@ impl GuiApp {
 
                 // Check if cursor is visible
                 if cursor_line >= window.scroll_line
-                    && cursor_line < window.scroll_line + content_rows
+                    && cursor_line < window.scroll_line + text_rows
                 {
-                    let visual_row = (cursor_line - window.scroll_line) as u16;
+                    let rel_row = cursor_line - window.scroll_line;
                     // Convert char column to visual column (accounting for tabs)
                     let line_text: String = buffer.text.line(cursor_line).chars().collect();
-                    let visual_col = char_col_to_visual_col(&line_text, cursor_char_col) as u16;
-                    let grid_pos = (visual_col, visual_row);
-                    
-                    if i == 0 {
-                        primary_cursor_pos = Some(grid_pos);
-                    } else {
-                        secondary_cursor_positions.push(grid_pos);
+                    let visual_col =
+                        scrolled_visual_col(&line_text, cursor_char_col, window.scroll_column);
+
+                    if let Some(visual_col) = visual_col.filter(|&col| col < text_width) {
+                        let rect = [
+                            origin_px.0
+                                + (gutter + visual_col) as f32 * self.cell_width * scale,
+                            origin_px.1 + rel_row as f32 * self.cell_height * scale,
+                            self.cell_width * scale,
+                            self.cell_height * scale,
+                        ];
+                        if i == 0 && is_active {
+                            primary_cursor_rect = Some(rect);
+                        } else {
+                            secondary_cursor_rects.push(rect);
+                        }
                     }
                 }
 
@@ -518,35 +1044,68 @@ impl GuiApp {
                     let start_pos = buffer.text.char_to_position(start);
                     let end_pos = buffer.text.char_to_position(end);
 
+                    // In rectangle-mark-mode only the columnar
+                    // intersection of each line is selected
+                    let rect_cols = window.rectangle_mark.then(|| {
+                        (
+                            start_pos.column.min(end_pos.column),
+                            start_pos.column.max(end_pos.column),
+                        )
+                    });
+
                     // For each visible line, calculate selection rectangle
                     for line in start_pos.line..=end_pos.line {
-                        if line < window.scroll_line || line >= window.scroll_line + content_rows {
+                        if line < window.scroll_line || line >= window.scroll_line + text_rows {
                             continue;
                         }
 
-                        let visual_row = (line - window.scroll_line) as u16;
+                        let rel_row = line - window.scroll_line;
                         let line_text: String = buffer.text.line(line).chars().collect();
                         let line_len = line_text.chars().count().saturating_sub(1); // Exclude newline
 
-                        let sel_start_char_col = if line == start_pos.line {
-                            start_pos.column
-                        } else {
-                            0
+                        let sel_start_char_col = match rect_cols {
+                            Some((left, _)) => left.min(line_len),
+                            None if line == start_pos.line => start_pos.column,
+                            None => 0,
                         };
 
-                        let sel_end_char_col = if line == end_pos.line {
-                            end_pos.column
-                        } else {
-                            line_len
+                        let sel_end_char_col = match rect_cols {
+                            Some((_, right)) => right.min(line_len),
+                            None if line == end_pos.line => end_pos.column,
+                            None => line_len,
                         };
 
                         if sel_end_char_col > sel_start_char_col {
-                            // Convert char columns to visual columns
-                            let visual_start = char_col_to_visual_col(&line_text, sel_start_char_col) as u16;
-                            let visual_end = char_col_to_visual_col(&line_text, sel_end_char_col) as u16;
-                            let width = visual_end.saturating_sub(visual_start);
-                            if width > 0 {
-                                selection_rects.push((visual_start, visual_row, width));
+                            // Convert char columns to visual columns,
+                            // clipped to the window's text area
+                            // A start hidden behind the left edge clamps
+                            // to just right of the `$` marker.
+                            let visual_start = scrolled_visual_col(
+                                &line_text,
+                                sel_start_char_col,
+                                window.scroll_column,
+                            )
+                            .unwrap_or(1)
+                            .min(text_width);
+                            let visual_end = scrolled_visual_col(
+                                &line_text,
+                                sel_end_char_col,
+                                window.scroll_column,
+                            )
+                            .unwrap_or(0)
+                            .min(text_width);
+                            if visual_end > visual_start {
+                                selection_rects.push([
+                                    origin_px.0
+                                        + (gutter + visual_start) as f32
+                                            * self.cell_width
+                                            * scale,
+                                    origin_px.1 + rel_row as f32 * self.cell_height * scale,
+                                    (visual_end - visual_start) as f32
+                                        * self.cell_width
+                                        * scale,
+                                    self.cell_height * scale,
+                                ]);
                             }
                         }
                     }
@@ -554,9 +1113,6 @@ impl GuiApp {
             }
         }
 
-        // Build modeline text
-        let modeline_text = self.build_modeline_text();
-
         // Build minibuffer text  
         let minibuffer_text = if self.state.minibuffer.is_active() {
             self.state.minibuffer.display()
@@ -568,15 +1124,27 @@ impl GuiApp {
             String::new()
         };
 
+        // Faint current-line band (hl-line), behind the selection
+        let hl_line_bind_group = hl_line_rect.map(|rect| {
+            Self::create_rect_bind_group(
+                gpu,
+                RectUniforms {
+                    rect,
+                    color: [0.5, 0.5, 0.5, 0.12],
+                    screen_size: [pixel_width, pixel_height],
+                    _padding: [0.0, 0.0],
+                },
+            )
+        });
+
         // Create selection rectangle bind groups
         let selection_bind_groups: Vec<_> = selection_rects
             .iter()
-            .map(|&(col, row, width)| {
-                let (x, y) = self.grid_to_pixel(col, row);
+            .map(|&rect| {
                 Self::create_rect_bind_group(
                     gpu,
                     RectUniforms {
-                        rect: [x, y, width as f32 * self.cell_width, self.cell_height],
+                        rect,
                         color: theme.selection,
                         screen_size: [pixel_width, pixel_height],
                         _padding: [0.0, 0.0],
@@ -585,30 +1153,79 @@ impl GuiApp {
             })
             .collect();
 
-        // Create primary cursor bind group
-        let primary_cursor_bind_group = primary_cursor_pos.map(|(col, row)| {
-            let (x, y) = self.grid_to_pixel(col, row);
-            Self::create_rect_bind_group(
-                gpu,
-                RectUniforms {
-                    rect: [x, y, self.cell_width, self.cell_height],
-                    color: theme.cursor_bg,
-                    screen_size: [pixel_width, pixel_height],
-                    _padding: [0.0, 0.0],
-                },
-            )
-        });
+        // Subtle boxes on both halves of a matched bracket pair
+        let paren_match_color = [0.55, 0.55, 0.3, 0.5];
+        let paren_bind_groups: Vec<_> = paren_rects
+            .iter()
+            .map(|&rect| {
+                Self::create_rect_bind_group(
+                    gpu,
+                    RectUniforms {
+                        rect,
+                        color: paren_match_color,
+                        screen_size: [pixel_width, pixel_height],
+                        _padding: [0.0, 0.0],
+                    },
+                )
+            })
+            .collect();
+
+        // Create primary cursor bind group, colored by the current state
+        let region_active = self
+            .state
+            .current_window()
+            .map(|w| w.cursors.primary.mark_active)
+            .unwrap_or(false);
+        let overwrite = self
+            .state
+            .current_buffer()
+            .map(|b| b.overwrite)
+            .unwrap_or(false);
+        let primary_cursor_color = cursor_color(&theme, region_active, overwrite);
+        // A focused cursor blinks and takes the configured shape; an
+        // unfocused window keeps a steady hollow box at the point.
+        let (primary_cursor_bind_group, hollow_cursor_bind_groups) = match primary_cursor_rect {
+            Some(rect) if !self.focused => {
+                let groups: Vec<_> = hollow_cursor_rects(rect)
+                    .iter()
+                    .map(|&strip| {
+                        Self::create_rect_bind_group(
+                            gpu,
+                            RectUniforms {
+                                rect: strip,
+                                color: primary_cursor_color,
+                                screen_size: [pixel_width, pixel_height],
+                                _padding: [0.0, 0.0],
+                            },
+                        )
+                    })
+                    .collect();
+                (None, groups)
+            }
+            Some(rect) if self.cursor_visible => {
+                let bind_group = Self::create_rect_bind_group(
+                    gpu,
+                    RectUniforms {
+                        rect: cursor_shape_rect(self.config.cursor_shape, rect),
+                        color: primary_cursor_color,
+                        screen_size: [pixel_width, pixel_height],
+                        _padding: [0.0, 0.0],
+                    },
+                );
+                (Some(bind_group), Vec::new())
+            }
+            _ => (None, Vec::new()),
+        };
 
         // Create secondary cursor bind groups (different color)
         let secondary_cursor_color = [0.5, 0.5, 0.55, 1.0]; // Gray for secondary cursors
-        let secondary_cursor_bind_groups: Vec<_> = secondary_cursor_positions
+        let secondary_cursor_bind_groups: Vec<_> = secondary_cursor_rects
             .iter()
-            .map(|&(col, row)| {
-                let (x, y) = self.grid_to_pixel(col, row);
+            .map(|&rect| {
                 Self::create_rect_bind_group(
                     gpu,
                     RectUniforms {
-                        rect: [x, y, self.cell_width, self.cell_height],
+                        rect,
                         color: secondary_cursor_color,
                         screen_size: [pixel_width, pixel_height],
                         _padding: [0.0, 0.0],
@@ -617,19 +1234,54 @@ impl GuiApp {
             })
             .collect();
 
-        // Create modeline background bind group
-        let (modeline_x, modeline_y) = self.grid_to_pixel(0, modeline_row);
-        let modeline_bg_bind_group = Self::create_rect_bind_group(
-            gpu,
-            RectUniforms {
-                rect: [modeline_x, modeline_y, pixel_width, self.cell_height],
-                color: theme.modeline_bg,
-                screen_size: [pixel_width, pixel_height],
-                _padding: [0.0, 0.0],
-            },
-        );
+        // Create one modeline background bind group per window
+        let modeline_bg_bind_groups: Vec<_> = modeline_rects
+            .iter()
+            .map(|&(col, row, width)| {
+                let (x, y) = self.grid_to_pixel(col, row);
+                Self::create_rect_bind_group(
+                    gpu,
+                    RectUniforms {
+                        rect: [x, y, width as f32 * self.cell_width, self.cell_height],
+                        color: theme.modeline_bg,
+                        screen_size: [pixel_width, pixel_height],
+                        _padding: [0.0, 0.0],
+                    },
+                )
+            })
+            .collect();
+
+        // Thin vertical separators between side-by-side windows
+        let separator_bind_groups: Vec<_> = separator_rects
+            .iter()
+            .map(|&(col, row, height)| {
+                let (x, y) = self.grid_to_pixel(col, row);
+                Self::create_rect_bind_group(
+                    gpu,
+                    RectUniforms {
+                        rect: [
+                            x + self.cell_width / 2.0 - 1.0,
+                            y,
+                            2.0,
+                            height as f32 * self.cell_height,
+                        ],
+                        color: theme.modeline_bg,
+                        screen_size: [pixel_width, pixel_height],
+                        _padding: [0.0, 0.0],
+                    },
+                )
+            })
+            .collect();
 
         // Now borrow text mutably for rendering
+        let cell_width = self.cell_width;
+        let cell_height = self.cell_height;
+        let font_size = self.font_size * self.scale_factor as f32;
+        let family = if self.font_ok {
+            Family::Name(self.font_family.as_str())
+        } else {
+            Family::Monospace
+        };
         let text = match &mut self.text {
             Some(t) => t,
             None => return,
@@ -645,55 +1297,104 @@ impl GuiApp {
             },
         );
 
-        // Prepare text buffers - line_height must match cell_height
-        let metrics = Metrics::new(FONT_SIZE, CELL_HEIGHT);
-        let mut text_buffers: Vec<(GlyphonBuffer, (f32, f32))> = Vec::new();
-
-        // Content buffer at (0, 0)
-        let mut content_buffer = GlyphonBuffer::new(&mut text.font_system, metrics);
-        content_buffer.set_size(
-            &mut text.font_system,
-            Some(pixel_width),
-            Some(content_rows as f32 * self.cell_height),
-        );
-        content_buffer.set_text(
-            &mut text.font_system,
-            &content_text,
-            Attrs::new().family(Family::Name(FONT_FAMILY)),
-            Shaping::Advanced,
-        );
-        text_buffers.push((content_buffer, (0.0, 0.0)));
+        // Prepare text buffers - line_height must match cell_height. Each
+        // buffer carries its pixel origin and clip bounds so window text
+        // can't bleed into a neighboring viewport.
+        let metrics = Metrics::new(font_size, cell_height);
+        let mut text_buffers: Vec<(GlyphonBuffer, (f32, f32), TextBounds)> = Vec::new();
+
+        // One content buffer per window, positioned and bounded by its rect
+        for wt in &window_texts {
+            let (x, y) = (
+                wt.origin.0 as f32 * cell_width,
+                wt.origin.1 as f32 * cell_height,
+            );
+            let width_px = wt.size.0 as f32 * cell_width * wt.scale;
+            let height_px = wt.size.1 as f32 * cell_height * wt.scale;
+
+            // Scaled windows get their own metrics; their grid was shrunk
+            // to match, so the pixel extent still fits the window rect.
+            let window_metrics = Metrics::new(font_size * wt.scale, cell_height * wt.scale);
+            let mut content_buffer = GlyphonBuffer::new(&mut text.font_system, window_metrics);
+            content_buffer.set_size(&mut text.font_system, Some(width_px), Some(height_px));
+            let base_attrs = Attrs::new().family(family);
+            if let Some(rich) = &wt.rich {
+                content_buffer.set_rich_text(
+                    &mut text.font_system,
+                    rich.iter().map(|(run, style)| {
+                        let attrs = match style {
+                            MdStyle::Heading | MdStyle::Bold => {
+                                base_attrs.weight(glyphon::Weight::BOLD)
+                            }
+                            _ => base_attrs,
+                        };
+                        (run.as_str(), attrs)
+                    }),
+                    base_attrs,
+                    Shaping::Advanced,
+                );
+            } else {
+                content_buffer.set_text(
+                    &mut text.font_system,
+                    &wt.content,
+                    base_attrs,
+                    Shaping::Advanced,
+                );
+            }
+            let bounds = TextBounds {
+                left: x as i32,
+                top: y as i32,
+                right: (x + width_px) as i32,
+                bottom: (y + height_px) as i32,
+            };
+            text_buffers.push((content_buffer, (x, y), bounds));
+        }
 
-        // Modeline buffer at modeline_row
-        let mut modeline_buffer = GlyphonBuffer::new(&mut text.font_system, metrics);
-        modeline_buffer.set_size(
-            &mut text.font_system,
-            Some(pixel_width),
-            Some(self.cell_height),
-        );
-        modeline_buffer.set_text(
-            &mut text.font_system,
-            &modeline_text,
-            Attrs::new().family(Family::Name(FONT_FAMILY)).color(theme.modeline_fg),
-            Shaping::Advanced,
-        );
-        text_buffers.push((modeline_buffer, (0.0, modeline_y)));
+        // One modeline buffer per window
+        for (modeline_text, (col, row)) in &modeline_texts {
+            let (x, y) = (*col as f32 * cell_width, *row as f32 * cell_height);
+            let mut modeline_buffer = GlyphonBuffer::new(&mut text.font_system, metrics);
+            modeline_buffer.set_size(
+                &mut text.font_system,
+                Some(pixel_width),
+                Some(cell_height),
+            );
+            modeline_buffer.set_text(
+                &mut text.font_system,
+                modeline_text,
+                Attrs::new().family(family).color(theme.modeline_fg),
+                Shaping::Advanced,
+            );
+            let bounds = TextBounds {
+                left: x as i32,
+                top: y as i32,
+                right: gpu_width as i32,
+                bottom: (y + cell_height) as i32,
+            };
+            text_buffers.push((modeline_buffer, (x, y), bounds));
+        }
 
         // Minibuffer buffer at minibuffer_row
-        let minibuffer_y = minibuffer_row as f32 * self.cell_height;
+        let minibuffer_y = minibuffer_row as f32 * cell_height;
         let mut minibuffer_buffer = GlyphonBuffer::new(&mut text.font_system, metrics);
         minibuffer_buffer.set_size(
             &mut text.font_system,
             Some(pixel_width),
-            Some(self.cell_height),
+            Some(cell_height),
         );
         minibuffer_buffer.set_text(
             &mut text.font_system,
             &minibuffer_text,
-            Attrs::new().family(Family::Name(FONT_FAMILY)),
+            Attrs::new().family(family),
             Shaping::Advanced,
         );
-        text_buffers.push((minibuffer_buffer, (0.0, minibuffer_y)));
+        let minibuffer_bounds = TextBounds {
+            left: 0,
+            top: minibuffer_y as i32,
+            right: gpu_width as i32,
+            bottom: gpu_height as i32,
+        };
+        text_buffers.push((minibuffer_buffer, (0.0, minibuffer_y), minibuffer_bounds));
 
         // Prepare text renderer
         text.text_renderer
@@ -703,17 +1404,12 @@ impl GuiApp {
                 &mut text.font_system,
                 &mut text.atlas,
                 &text.viewport,
-                text_buffers.iter().map(|(buf, pos)| TextArea {
+                text_buffers.iter().map(|(buf, pos, bounds)| TextArea {
                     buffer: buf,
                     left: pos.0,
                     top: pos.1,
                     scale: 1.0,
-                    bounds: TextBounds {
-                        left: 0,
-                        top: 0,
-                        right: gpu_width as i32,
-                        bottom: gpu_height as i32,
-                    },
+                    bounds: *bounds,
                     default_color: theme.foreground,
                     custom_glyphs: &[],
                 }),
@@ -733,11 +1429,13 @@ impl GuiApp {
                     view: &view,
                     resolve_target: None,
                     ops: Operations {
+                        // Premultiplied alpha: scale the color channels
+                        // along with the opacity.
                         load: LoadOp::Clear(wgpu::Color {
-                            r: theme.background[0] as f64,
-                            g: theme.background[1] as f64,
-                            b: theme.background[2] as f64,
-                            a: theme.background[3] as f64,
+                            r: theme.background[0] as f64 * opacity,
+                            g: theme.background[1] as f64 * opacity,
+                            b: theme.background[2] as f64 * opacity,
+                            a: theme.background[3] as f64 * opacity,
                         }),
                         store: StoreOp::Store,
                     },
@@ -750,9 +1448,21 @@ impl GuiApp {
             // Draw rectangles first (behind text)
             pass.set_pipeline(&gpu.rect_pipeline);
             
-            // Modeline background
-            pass.set_bind_group(0, &modeline_bg_bind_group, &[]);
-            pass.draw(0..6, 0..1);
+            // Modeline backgrounds and window separators
+            for bind_group in &modeline_bg_bind_groups {
+                pass.set_bind_group(0, bind_group, &[]);
+                pass.draw(0..6, 0..1);
+            }
+            for bind_group in &separator_bind_groups {
+                pass.set_bind_group(0, bind_group, &[]);
+                pass.draw(0..6, 0..1);
+            }
+
+            // Current-line band (behind the selection)
+            if let Some(ref bind_group) = hl_line_bind_group {
+                pass.set_bind_group(0, bind_group, &[]);
+                pass.draw(0..6, 0..1);
+            }
 
             // Selection regions (behind cursors)
             for bind_group in &selection_bind_groups {
@@ -760,14 +1470,23 @@ impl GuiApp {
                 pass.draw(0..6, 0..1);
             }
 
+            // Matched bracket pair (behind cursors)
+            for bind_group in &paren_bind_groups {
+                pass.set_bind_group(0, bind_group, &[]);
+                pass.draw(0..6, 0..1);
+            }
+
             // Secondary cursors (gray)
             for bind_group in &secondary_cursor_bind_groups {
                 pass.set_bind_group(0, bind_group, &[]);
                 pass.draw(0..6, 0..1);
             }
 
-            // Primary cursor (on top)
-            if let Some(ref bind_group) = primary_cursor_bind_group {
+            // Primary cursor (on top); the hollow variant is four strips
+            for bind_group in primary_cursor_bind_group
+                .iter()
+                .chain(&hollow_cursor_bind_groups)
+            {
                 pass.set_bind_group(0, bind_group, &[]);
                 pass.draw(0..6, 0..1);
             }
@@ -784,11 +1503,8 @@ impl GuiApp {
         text.atlas.trim();
     }
 
-    fn build_modeline_text(&self) -> String {
-        use crate::core::rope_ext::RopeExt;
-
-        let buffer = self.state.current_buffer();
-        let window = self.state.current_window();
+    fn build_modeline_text(&self, window: &crate::state::Window) -> String {
+        let buffer = self.state.buffers.get(window.buffer_id);
 
         let buffer_name = buffer.map(|b| b.name.as_str()).unwrap_or("[No buffer]");
         let modified = buffer
@@ -797,32 +1513,28 @@ impl GuiApp {
         let readonly = buffer
             .map(|b| if b.read_only { "%%" } else { "--" })
             .unwrap_or("--");
-
-        let mark_indicator = window
-            .map(|w| {
-                if w.cursors.primary.mark_active {
-                    " Mark"
-                } else {
-                    ""
-                }
-            })
+        let mode_name = buffer
+            .map(|b| b.major_mode.name)
+            .unwrap_or(crate::core::MajorMode::FUNDAMENTAL.name);
+        let overwrite = buffer
+            .map(|b| if b.overwrite { " Ovwrt" } else { "" })
             .unwrap_or("");
 
-        let (line, col) = match (buffer, window) {
-            (Some(b), Some(w)) => {
-                let pos = b.text.char_to_position(w.cursors.primary.position);
-                (pos.line + 1, pos.column + 1)
-            }
-            _ => (1, 1),
+        let mark_indicator = if window.cursors.primary.mark_active {
+            " Mark"
+        } else {
+            ""
         };
 
+        let (line, col) = self.state.window_position(window);
+
         let left = format!(
-            "-{}:{}- {}{} ",
-            modified, readonly, buffer_name, mark_indicator
+            "-{}:{}- {} ({}){}{} ",
+            modified, readonly, buffer_name, mode_name, overwrite, mark_indicator
         );
         let right = format!(" L{}:C{} ", line, col);
 
-        let padding = (self.cols as usize).saturating_sub(left.len() + right.len());
+        let padding = (window.width as usize).saturating_sub(left.len() + right.len());
         let dashes: String = std::iter::repeat('-').take(padding).collect();
 
         format!("{}{}{}", left, dashes, right)
@@ -833,12 +1545,88 @@ impl GuiApp {
             return;
         }
 
+        // Typing restarts the blink cycle on the visible phase so the
+        // cursor never disappears mid-edit.
+        self.cursor_visible = true;
+        self.last_blink = std::time::Instant::now();
+
         let key_event = match self.convert_key_event(&event) {
             Some(k) => k,
             None => return,
         };
 
         self.state.handle_key(key_event);
+        sync_primary_selection(&self.state, self.clipboard.as_mut());
+    }
+
+    /// Translates a pixel position into a char offset in the current
+    /// window's buffer, accounting for scroll and tab expansion.
+    fn pixel_to_char_offset(&self, x: f64, y: f64) -> Option<crate::core::CharOffset> {
+        use crate::core::rope_ext::RopeExt;
+
+        let col = (x / self.cell_width as f64).max(0.0) as usize;
+        let row = (y / self.cell_height as f64).max(0.0) as usize;
+
+        let window = self.state.current_window()?;
+        let buffer = self.state.buffers.get(window.buffer_id)?;
+
+        // Only clicks inside the current window's text area count;
+        // its bottom row is the modeline and the gutter isn't text.
+        let gutter = if window.display_line_numbers.is_some() {
+            crate::state::window_mgr::LineNumberStyle::gutter_width(buffer.text.len_lines())
+        } else {
+            0
+        };
+        let col = col.checked_sub(window.x as usize + gutter)?;
+        let row = row.checked_sub(window.y as usize)?;
+        if col >= window.width as usize || row >= window.height.saturating_sub(1) as usize {
+            return None;
+        }
+
+        let line_idx = window.scroll_line + row;
+        if line_idx >= buffer.text.total_lines() {
+            return Some(crate::core::CharOffset(buffer.text.total_chars()));
+        }
+
+        let line_text: String = buffer.text.line(line_idx).chars().collect();
+        let trimmed = line_text.trim_end_matches('\n');
+        let char_col = visual_col_to_char_col(trimmed, col);
+        let line_start = buffer.text.line_start_char(line_idx);
+        Some(crate::core::CharOffset(line_start.0 + char_col))
+    }
+
+    /// Middle-click paste: inserts the primary selection at the click
+    /// position, following the X11 convention.
+    fn paste_primary_at_pointer(&mut self) {
+        if !self.clipboard.supports_primary() {
+            return;
+        }
+
+        let text = match self.clipboard.get_primary() {
+            Some(t) if !t.is_empty() => t,
+            _ => return,
+        };
+
+        let offset = match self.pixel_to_char_offset(self.pointer_pos.0, self.pointer_pos.1) {
+            Some(o) => o,
+            None => return,
+        };
+
+        let buffer_id = match self.state.current_window() {
+            Some(w) => w.buffer_id,
+            None => return,
+        };
+
+        if let Some(window) = self.state.windows.current_mut() {
+            window.cursors.remove_secondary_cursors();
+            window.cursors.primary.set_position(offset);
+            window.cursors.primary.clear_mark();
+        }
+
+        let cursors = &mut self.state.windows.current_mut().unwrap().cursors;
+        if let Some(buffer) = self.state.buffers.get_mut(buffer_id) {
+            buffer.insert_string(cursors, &text);
+        }
     }
 
     fn convert_key_event(&self, event: &WinitKeyEvent) -> Option<KeyEvent> {
@@ -930,16 +1718,18 @@ impl ApplicationHandler for GuiApp {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         let attrs = WindowAttributes::default()
             .with_title("Enacs")
-            .with_inner_size(LogicalSize::new(1000, 700));
+            .with_inner_size(LogicalSize::new(1000, 700))
+            .with_transparent(self.config.window_opacity < 1.0);
 
         let window = Arc::new(event_loop.create_window(attrs).unwrap());
 
+        self.scale_factor = window.scale_factor();
         self.init_gpu(window.clone());
 
         let size = window.inner_size();
         self.cols = (size.width as f32 / self.cell_width) as u16;
         self.rows = (size.height as f32 / self.cell_height) as u16;
-        self.state.set_dimensions(self.cols, self.rows.saturating_sub(2));
+        self.state.set_dimensions(self.cols, self.rows);
 
         self.window = Some(window);
     }
@@ -952,6 +1742,7 @@ impl ApplicationHandler for GuiApp {
     ) {
         match event {
             WindowEvent::CloseRequested => {
+                self.state.save_history_file();
                 event_loop.exit();
             }
             WindowEvent::Resized(size) => {
@@ -966,6 +1757,7 @@ impl ApplicationHandler for GuiApp {
             WindowEvent::KeyboardInput { event, .. } => {
                 self.handle_key(event);
                 if self.state.should_quit {
+                    self.state.save_history_file();
                     event_loop.exit();
                 }
                 if let Some(window) = &self.window {
@@ -975,11 +1767,51 @@ impl ApplicationHandler for GuiApp {
             WindowEvent::ModifiersChanged(mods) => {
                 self.modifiers = mods.state();
             }
-            WindowEvent::Focused(focused) => {
-                if focused {
-                    if let Some(window) = &self.window {
-                        window.request_redraw();
+            WindowEvent::CursorMoved { position, .. } => {
+                self.pointer_pos = (position.x, position.y);
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: winit::event::MouseButton::Middle,
+                ..
+            } => {
+                self.paste_primary_at_pointer();
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                // Re-measure the grid at the new DPI; winit follows up
+                // with a Resized event that reconfigures the surface.
+                self.scale_factor = scale_factor;
+                self.refresh_metrics();
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+            }
+            WindowEvent::DroppedFile(path) => {
+                // Winit delivers one event per file in a multi-file
+                // drop, so each opens in order and the last stays
+                // current.
+                let name = path.display().to_string();
+                match self.state.open_file(path) {
+                    Ok(_) => {
+                        self.state.message = Some(format!("Opened {}", name));
                     }
+                    Err(e) => {
+                        self.state.message = Some(format!("Error opening {}: {}", name, e));
+                    }
+                }
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+            }
+            WindowEvent::Focused(focused) => {
+                self.focused = focused;
+                self.cursor_visible = true;
+                self.last_blink = std::time::Instant::now();
+                if let Some(window) = &self.window {
+                    window.request_redraw();
                 }
             }
             _ => {}
@@ -987,8 +1819,144 @@ impl ApplicationHandler for GuiApp {
     }
 
     fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        crate::commands::grep::poll(&mut self.state);
+        self.state.auto_save_tick();
+        self.state.which_key_tick();
+        self.apply_font_changes();
+        // Toggle the blink phase once the interval has elapsed; the
+        // redraw below picks it up.
+        if let Some(interval) = self.config.cursor_blink_interval {
+            if self.focused && self.last_blink.elapsed() >= interval {
+                self.cursor_visible = !self.cursor_visible;
+                self.last_blink = std::time::Instant::now();
+            }
+        }
         if let Some(window) = &self.window {
+            let title = self.state.frame_title();
+            if self.last_title.as_deref() != Some(&title) {
+                window.set_title(&title);
+                self.last_title = Some(title);
+            }
             window.request_redraw();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_color_reflects_mode() {
+        let theme = Theme::default();
+
+        let normal = cursor_color(&theme, false, false);
+        assert_eq!(normal, theme.cursor_bg);
+
+        assert_ne!(cursor_color(&theme, true, false), normal);
+        assert_ne!(cursor_color(&theme, false, true), normal);
+    }
+
+    #[test]
+    fn test_cursor_color_overwrite_wins_over_region() {
+        let theme = Theme::default();
+        assert_eq!(cursor_color(&theme, true, true), theme.cursor_overwrite_bg);
+    }
+
+    #[test]
+    fn test_parse_opacity() {
+        assert_eq!(parse_opacity("0.85"), Some(0.85));
+        assert_eq!(parse_opacity(" 1 "), Some(1.0));
+        assert_eq!(parse_opacity("2.5"), Some(1.0));
+        assert_eq!(parse_opacity("-0.3"), Some(0.0));
+        assert_eq!(parse_opacity("cloudy"), None);
+        assert_eq!(parse_opacity("NaN"), None);
+    }
+
+    #[test]
+    fn test_cursor_shape_parses_env_names() {
+        assert_eq!(CursorShape::parse("block"), Some(CursorShape::Block));
+        assert_eq!(CursorShape::parse(" Bar "), Some(CursorShape::Bar));
+        assert_eq!(CursorShape::parse("underline"), Some(CursorShape::Underline));
+        assert_eq!(CursorShape::parse("wedge"), None);
+    }
+
+    #[test]
+    fn test_parse_blink_interval_zero_disables() {
+        assert_eq!(
+            parse_blink_interval("250"),
+            Some(Some(std::time::Duration::from_millis(250)))
+        );
+        assert_eq!(parse_blink_interval("0"), Some(None));
+        assert_eq!(parse_blink_interval("fast"), None);
+    }
+
+    #[test]
+    fn test_cursor_shape_rect_geometry() {
+        let cell = [10.0, 20.0, 8.0, 16.0];
+        assert_eq!(cursor_shape_rect(CursorShape::Block, cell), cell);
+
+        let bar = cursor_shape_rect(CursorShape::Bar, cell);
+        assert_eq!(bar, [10.0, 20.0, CURSOR_LINE_THICKNESS, 16.0]);
+
+        let underline = cursor_shape_rect(CursorShape::Underline, cell);
+        assert_eq!(
+            underline,
+            [10.0, 36.0 - CURSOR_LINE_THICKNESS, 8.0, CURSOR_LINE_THICKNESS]
+        );
+    }
+
+    #[test]
+    fn test_hollow_cursor_rects_trace_the_cell_border() {
+        let cell = [10.0, 20.0, 8.0, 16.0];
+        let strips = hollow_cursor_rects(cell);
+        let t = CURSOR_LINE_THICKNESS;
+        // Top and bottom edges span the full width; left and right the
+        // full height.
+        assert_eq!(strips[0], [10.0, 20.0, 8.0, t]);
+        assert_eq!(strips[1], [10.0, 36.0 - t, 8.0, t]);
+        assert_eq!(strips[2], [10.0, 20.0, t, 16.0]);
+        assert_eq!(strips[3], [18.0 - t, 20.0, t, 16.0]);
+    }
+
+    #[test]
+    fn test_char_col_to_visual_col_counts_wide_chars() {
+        // Each CJK character occupies two visual columns.
+        assert_eq!(char_col_to_visual_col("ab日本c", 2), 2);
+        assert_eq!(char_col_to_visual_col("ab日本c", 3), 4);
+        assert_eq!(char_col_to_visual_col("ab日本c", 4), 6);
+        assert_eq!(char_col_to_visual_col("ab日本c", 5), 7);
+    }
+
+    #[test]
+    fn test_visual_col_to_char_col_lands_inside_wide_chars() {
+        assert_eq!(visual_col_to_char_col("ab日本c", 2), 2);
+        // A click on the second cell of a wide glyph lands after it.
+        assert_eq!(visual_col_to_char_col("ab日本c", 3), 3);
+        assert_eq!(visual_col_to_char_col("ab日本c", 4), 3);
+        assert_eq!(visual_col_to_char_col("ab日本c", 6), 4);
+    }
+
+    #[test]
+    fn test_expand_tabs_aligns_after_wide_chars() {
+        // The wide char pushes the next tab stop one cell closer.
+        assert_eq!(expand_tabs("日\ta", 4), "日  a");
+        assert_eq!(expand_tabs("ab\ta", 4), "ab  a");
+    }
+
+    #[test]
+    fn test_select_alpha_mode() {
+        let both = [CompositeAlphaMode::Opaque, CompositeAlphaMode::PreMultiplied];
+        let opaque_only = [CompositeAlphaMode::Opaque];
+
+        assert_eq!(
+            select_alpha_mode(0.8, &both),
+            CompositeAlphaMode::PreMultiplied
+        );
+        assert_eq!(select_alpha_mode(1.0, &both), CompositeAlphaMode::Opaque);
+        assert_eq!(
+            select_alpha_mode(0.8, &opaque_only),
+            CompositeAlphaMode::Opaque
+        );
+    }
+}
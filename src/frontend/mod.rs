@@ -1,3 +1,4 @@
+pub mod clipboard;
 pub mod gui;
 pub mod terminal;
 pub mod traits;
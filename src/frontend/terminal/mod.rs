@@ -11,12 +11,21 @@ use crossterm::{
 
 use crate::state::EditorState;
 
-use super::traits::{Frontend, FrontendError, FrontendEvent};
+use super::traits::{Frontend, FrontendCapabilities, FrontendError, FrontendEvent, MouseEventKind};
 
 pub struct TerminalFrontend {
     stdout: Stdout,
     width: u16,
     height: u16,
+    /// Color depth detected from the environment at startup; rendering
+    /// downsamples RGB colors to match.
+    color_support: render::ColorSupport,
+    /// The cell grid drawn last frame; each new frame is diffed against
+    /// it so only changed cells are rewritten.
+    back_buffer: Option<render::Grid>,
+    /// Title pushed to the terminal last; skips the escape sequence
+    /// when nothing changed.
+    last_title: Option<String>,
 }
 
 impl TerminalFrontend {
@@ -26,6 +35,9 @@ impl TerminalFrontend {
             stdout: io::stdout(),
             width,
             height,
+            color_support: render::ColorSupport::detect(),
+            back_buffer: None,
+            last_title: None,
         }
     }
 
@@ -51,6 +63,7 @@ impl Frontend for TerminalFrontend {
         execute!(
             self.stdout,
             terminal::EnterAlternateScreen,
+            event::EnableMouseCapture,
             cursor::Hide,
             terminal::Clear(ClearType::All)
         )?;
@@ -63,6 +76,7 @@ impl Frontend for TerminalFrontend {
     fn shutdown(&mut self) -> Result<(), FrontendError> {
         execute!(
             self.stdout,
+            event::DisableMouseCapture,
             cursor::Show,
             cursor::MoveTo(0, 0),
             terminal::LeaveAlternateScreen
@@ -77,9 +91,18 @@ impl Frontend for TerminalFrontend {
 
     fn run(mut self, mut state: EditorState) -> Result<(), FrontendError> {
         loop {
+            crate::commands::grep::poll(&mut state);
+            state.auto_save_tick();
+            state.which_key_tick();
+            let title = state.frame_title();
+            if self.last_title.as_deref() != Some(&title) {
+                self.set_title(&title);
+                self.last_title = Some(title);
+            }
             self.render(&state)?;
 
             if state.should_quit {
+                state.save_history_file();
                 break;
             }
 
@@ -92,8 +115,85 @@ impl Frontend for TerminalFrontend {
                         state.set_dimensions(width, height);
                         self.width = width;
                         self.height = height;
+                        // The old grid no longer matches the screen
+                        self.back_buffer = None;
                     }
-                    FrontendEvent::Mouse(_) => {}
+                    FrontendEvent::Mouse(mouse) => match mouse.kind {
+                        MouseEventKind::Down => {
+                            if let Some((id, offset)) =
+                                state.mouse_to_position(mouse.column, mouse.row)
+                            {
+                                state.windows.focus(id);
+                                if let Some(window) = state.windows.current_mut() {
+                                    let buffer_id = window.buffer_id;
+                                    window.cursors.remove_secondary_cursors();
+                                    window.cursors.primary.set_position(offset);
+                                    window.cursors.primary.clear_mark();
+                                    state.buffers.set_current(buffer_id);
+                                }
+                            }
+                        }
+                        MouseEventKind::Drag => {
+                            // Anchor the mark at the press position on the
+                            // first drag event, then follow the pointer.
+                            if let Some((id, offset)) =
+                                state.mouse_to_position(mouse.column, mouse.row)
+                            {
+                                if state.windows.current().map(|w| w.id) == Some(id) {
+                                    if let Some(window) = state.windows.current_mut() {
+                                        if !window.cursors.primary.mark_active {
+                                            let anchor = window.cursors.primary.position;
+                                            window.cursors.primary.set_mark(anchor);
+                                        }
+                                        window.cursors.primary.set_position(offset);
+                                    }
+                                }
+                            }
+                        }
+                        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                            const WHEEL_LINES: usize = 3;
+
+                            // Scroll the hovered window, falling back to
+                            // the active one; point stays where it is.
+                            let target = state
+                                .windows
+                                .iter()
+                                .find(|w| {
+                                    mouse.column >= w.x
+                                        && mouse.column < w.x + w.width
+                                        && mouse.row >= w.y
+                                        && mouse.row < w.y + w.height
+                                })
+                                .or_else(|| state.windows.current())
+                                .map(|w| (w.id, w.buffer_id, w.height));
+
+                            if let Some((id, buffer_id, height)) = target {
+                                use crate::core::rope_ext::RopeExt;
+
+                                let total_lines = state
+                                    .buffers
+                                    .get(buffer_id)
+                                    .map(|b| b.text.total_lines())
+                                    .unwrap_or(0);
+                                let max_scroll = total_lines
+                                    .saturating_sub(height.saturating_sub(1) as usize);
+
+                                if let Some(window) =
+                                    state.windows.iter_mut().find(|w| w.id == id)
+                                {
+                                    window.scroll_line = if matches!(
+                                        mouse.kind,
+                                        MouseEventKind::ScrollUp
+                                    ) {
+                                        window.scroll_line.saturating_sub(WHEEL_LINES)
+                                    } else {
+                                        (window.scroll_line + WHEEL_LINES).min(max_scroll)
+                                    };
+                                }
+                            }
+                        }
+                        _ => {}
+                    },
                     FrontendEvent::Focus(_) => {}
                     FrontendEvent::FileDrop(_) => {}
                     FrontendEvent::ScaleChange(_) => {}
@@ -104,7 +204,14 @@ impl Frontend for TerminalFrontend {
     }
 
     fn render(&mut self, state: &EditorState) -> Result<(), FrontendError> {
-        render::render(state, &mut self.stdout, self.width, self.height)?;
+        render::render(
+            state,
+            &mut self.stdout,
+            self.width,
+            self.height,
+            self.color_support,
+            &mut self.back_buffer,
+        )?;
         self.stdout.flush()?;
         Ok(())
     }
@@ -112,6 +219,19 @@ impl Frontend for TerminalFrontend {
     fn bell(&mut self) {
         let _ = execute!(self.stdout, crossterm::terminal::SetTitle("\x07"));
     }
+
+    fn set_title(&mut self, title: &str) {
+        let _ = execute!(self.stdout, crossterm::terminal::SetTitle(title));
+    }
+
+    fn capabilities(&self) -> FrontendCapabilities {
+        FrontendCapabilities {
+            images: false,
+            true_color: self.color_support == render::ColorSupport::TrueColor,
+            clipboard: false,
+            variable_width_fonts: false,
+        }
+    }
 }
 
 impl Drop for TerminalFrontend {
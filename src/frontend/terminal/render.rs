@@ -1,4 +1,4 @@
-use std::io::Stdout;
+use std::io::{Stdout, Write};
 
 use crossterm::{
     cursor::MoveTo,
@@ -8,60 +8,574 @@ use crossterm::{
     },
 };
 
+use unicode_width::UnicodeWidthChar;
+
 use crate::core::rope_ext::RopeExt;
+use crate::state::window_mgr::LineNumberStyle;
 use crate::state::EditorState;
 
+/// How many colors the terminal can show, detected from the
+/// environment once at startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSupport {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorSupport {
+    /// Reads `$COLORTERM` and `$TERM` the way terminals advertise their
+    /// color depth: `truecolor`/`24bit` for RGB, `256color` for the
+    /// xterm palette, 16 colors otherwise.
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        if colorterm.contains("truecolor") || colorterm.contains("24bit") {
+            return Self::TrueColor;
+        }
+        let term = std::env::var("TERM").unwrap_or_default();
+        if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+}
+
+/// One terminal cell: its glyph and styling. The second column of a
+/// wide glyph holds a `'\0'` continuation cell that is never printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cell {
+    ch: char,
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    reverse: bool,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            fg: None,
+            bg: None,
+            bold: false,
+            reverse: false,
+        }
+    }
+}
+
+/// A full-screen back-buffer of cells. Each frame is composed into a
+/// fresh grid and diffed against the previous frame's grid, so only
+/// changed cells are written to the terminal.
+pub struct Grid {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Grid {
+    fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    fn get(&self, x: u16, y: u16) -> &Cell {
+        &self.cells[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Writes outside the grid are dropped, so composition never has to
+    /// bounds-check narrow windows.
+    fn set(&mut self, x: u16, y: u16, cell: Cell) {
+        if x < self.width && y < self.height {
+            self.cells[y as usize * self.width as usize + x as usize] = cell;
+        }
+    }
+}
+
+/// A pen over a [`Grid`]: tracks the position and the styling that the
+/// composition code sets and resets as it goes, mirroring how the old
+/// direct-to-terminal renderer drove `queue!`.
+struct GridWriter<'a> {
+    grid: &'a mut Grid,
+    x: u16,
+    y: u16,
+    fg: Option<Color>,
+    bg: Option<Color>,
+    bold: bool,
+    reverse: bool,
+}
+
+impl<'a> GridWriter<'a> {
+    fn new(grid: &'a mut Grid) -> Self {
+        Self {
+            grid,
+            x: 0,
+            y: 0,
+            fg: None,
+            bg: None,
+            bold: false,
+            reverse: false,
+        }
+    }
+
+    fn move_to(&mut self, x: u16, y: u16) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn reset_color(&mut self) {
+        self.fg = None;
+        self.bg = None;
+    }
+
+    fn reset_all(&mut self) {
+        self.reset_color();
+        self.bold = false;
+        self.reverse = false;
+    }
+
+    fn print(&mut self, ch: char) {
+        let cell = Cell {
+            ch,
+            fg: self.fg,
+            bg: self.bg,
+            bold: self.bold,
+            reverse: self.reverse,
+        };
+        let wide = ch.width().unwrap_or(1) > 1;
+        self.grid.set(self.x, self.y, cell);
+        if wide {
+            self.grid.set(
+                self.x + 1,
+                self.y,
+                Cell {
+                    ch: '\0',
+                    fg: self.fg,
+                    bg: self.bg,
+                    bold: self.bold,
+                    reverse: self.reverse,
+                },
+            );
+        }
+        self.x += if wide { 2 } else { 1 };
+    }
+
+    fn print_str(&mut self, s: &str) {
+        for ch in s.chars() {
+            self.print(ch);
+        }
+    }
+}
+
+/// Writes only the cells that differ from `prev`, moving the cursor and
+/// switching styles as little as possible. A missing or resized `prev`
+/// forces a full repaint.
+fn flush_diff<W: Write>(out: &mut W, prev: Option<&Grid>, next: &Grid) -> std::io::Result<()> {
+    let prev = prev.filter(|p| p.width == next.width && p.height == next.height);
+
+    // The terminal cursor position and styling we have emitted so far;
+    // unknown until the first changed cell.
+    let mut cursor: Option<(u16, u16)> = None;
+    let mut style: Option<Cell> = None;
+    let same_style = |a: &Cell, b: &Cell| {
+        a.fg == b.fg && a.bg == b.bg && a.bold == b.bold && a.reverse == b.reverse
+    };
+
+    for y in 0..next.height {
+        for x in 0..next.width {
+            let cell = next.get(x, y);
+            if cell.ch == '\0' {
+                continue;
+            }
+            if prev.map(|p| p.get(x, y) == cell).unwrap_or(false) {
+                continue;
+            }
+
+            if cursor != Some((x, y)) {
+                queue!(out, MoveTo(x, y))?;
+            }
+            if style.as_ref().map(|s| same_style(s, cell)) != Some(true) {
+                queue!(out, ResetColor, SetAttribute(Attribute::Reset))?;
+                if let Some(fg) = cell.fg {
+                    queue!(out, SetForegroundColor(fg))?;
+                }
+                if let Some(bg) = cell.bg {
+                    queue!(out, SetBackgroundColor(bg))?;
+                }
+                if cell.bold {
+                    queue!(out, SetAttribute(Attribute::Bold))?;
+                }
+                if cell.reverse {
+                    queue!(out, SetAttribute(Attribute::Reverse))?;
+                }
+                style = Some(cell.clone());
+            }
+            queue!(out, Print(cell.ch))?;
+            cursor = Some((x + cell.ch.width().unwrap_or(1).max(1) as u16, y));
+        }
+    }
+
+    if style.is_some() {
+        queue!(out, ResetColor, SetAttribute(Attribute::Reset))?;
+    }
+    Ok(())
+}
+
+/// Terminal cells a character occupies: tabs are printed as four
+/// spaces, CJK and other wide glyphs take two cells.
+fn char_cells(c: char) -> usize {
+    if c == '\t' {
+        4
+    } else {
+        c.width().unwrap_or(1)
+    }
+}
+
+/// Background for the current line when hl-line-mode is on; dark
+/// enough not to clash with the blue selection region.
+fn hl_line_bg(support: ColorSupport) -> Color {
+    term_color(support, 40, 40, 40)
+}
+
+/// Foregrounds cycled by rainbow-delimiters-mode, one per nesting
+/// depth.
+const RAINBOW_COLORS: [Color; 6] = [
+    Color::Yellow,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Green,
+    Color::Blue,
+    Color::Red,
+];
+
+/// The nearest of the 16 ANSI colors by squared RGB distance, for
+/// terminals without true-color support.
+fn nearest_ansi_color(r: u8, g: u8, b: u8) -> Color {
+    const PALETTE: [(u8, u8, u8, Color); 16] = [
+        (0, 0, 0, Color::Black),
+        (128, 0, 0, Color::DarkRed),
+        (0, 128, 0, Color::DarkGreen),
+        (128, 128, 0, Color::DarkYellow),
+        (0, 0, 128, Color::DarkBlue),
+        (128, 0, 128, Color::DarkMagenta),
+        (0, 128, 128, Color::DarkCyan),
+        (192, 192, 192, Color::Grey),
+        (128, 128, 128, Color::DarkGrey),
+        (255, 0, 0, Color::Red),
+        (0, 255, 0, Color::Green),
+        (255, 255, 0, Color::Yellow),
+        (0, 0, 255, Color::Blue),
+        (255, 0, 255, Color::Magenta),
+        (0, 255, 255, Color::Cyan),
+        (255, 255, 255, Color::White),
+    ];
+
+    let distance = |pr: u8, pg: u8, pb: u8| {
+        let dr = pr as i32 - r as i32;
+        let dg = pg as i32 - g as i32;
+        let db = pb as i32 - b as i32;
+        dr * dr + dg * dg + db * db
+    };
+    PALETTE
+        .iter()
+        .min_by_key(|&&(pr, pg, pb, _)| distance(pr, pg, pb))
+        .map(|&(_, _, _, c)| c)
+        .unwrap_or(Color::White)
+}
+
+/// The nearest xterm-256 palette index: the 6x6x6 color cube (16-231)
+/// or the grayscale ramp (232-255), whichever is closer.
+pub fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    // Cube levels sit at 0, 95, 135, 175, 215, 255.
+    let cube_index = |c: u8| -> u8 {
+        if c < 48 {
+            0
+        } else if c < 115 {
+            1
+        } else {
+            (c - 35) / 40
+        }
+    };
+    let cube_value = |i: u8| -> u8 {
+        if i == 0 {
+            0
+        } else {
+            55 + i * 40
+        }
+    };
+
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let (cr, cg, cb) = (cube_value(ri), cube_value(gi), cube_value(bi));
+
+    // Grayscale ramp runs 8, 18, ... 238.
+    let avg = (r as u16 + g as u16 + b as u16) / 3;
+    let gray_index = if avg > 238 { 23 } else { (avg.saturating_sub(3) / 10) as u8 };
+    let gray = 8 + gray_index * 10;
+
+    let distance = |ar: u8, ag: u8, ab: u8| {
+        let dr = ar as i32 - r as i32;
+        let dg = ag as i32 - g as i32;
+        let db = ab as i32 - b as i32;
+        dr * dr + dg * dg + db * db
+    };
+
+    if distance(gray, gray, gray) < distance(cr, cg, cb) {
+        232 + gray_index
+    } else {
+        16 + 36 * ri + 6 * gi + bi
+    }
+}
+
+/// True color when the terminal supports it, otherwise the nearest
+/// xterm-256 or 16-color approximation.
+fn term_color(support: ColorSupport, r: u8, g: u8, b: u8) -> Color {
+    match support {
+        ColorSupport::TrueColor => Color::Rgb { r, g, b },
+        ColorSupport::Ansi256 => Color::AnsiValue(rgb_to_ansi256(r, g, b)),
+        ColorSupport::Ansi16 => nearest_ansi_color(r, g, b),
+    }
+}
+
+/// Foreground for a styled preview span; `None` keeps the default.
+fn md_style_color(support: ColorSupport, style: crate::commands::markdown::MdStyle) -> Option<Color> {
+    use crate::commands::markdown::MdStyle;
+    match style {
+        MdStyle::Text => None,
+        MdStyle::Heading => Some(term_color(support, 215, 135, 0)),
+        MdStyle::Bold => Some(term_color(support, 255, 255, 255)),
+        MdStyle::Bullet => Some(term_color(support, 0, 175, 215)),
+        MdStyle::Code => Some(term_color(support, 95, 175, 95)),
+    }
+}
+
+/// Composes the frame into a cell grid, writes only what changed since
+/// `back`, and leaves the new grid in `back` for the next frame.
 pub fn render(
     state: &EditorState,
     stdout: &mut Stdout,
     width: u16,
     height: u16,
+    support: ColorSupport,
+    back: &mut Option<Grid>,
 ) -> std::io::Result<()> {
-    for window in state.windows.iter() {
-        render_window(state, stdout, window)?;
+    let mut grid = Grid::new(width, height);
+    {
+        let mut pen = GridWriter::new(&mut grid);
+        let active_id = state.windows.current().map(|w| w.id);
+
+        for window in state.windows.iter() {
+            render_window(state, &mut pen, window, width, support);
+            render_window_modeline(state, &mut pen, window, active_id == Some(window.id));
+        }
+
+        render_minibuffer(state, &mut pen, width, height);
     }
 
-    render_modeline(state, stdout, width, height)?;
-    render_minibuffer(state, stdout, width, height)?;
+    flush_diff(stdout, back.as_ref(), &grid)?;
+    *back = Some(grid);
 
     Ok(())
 }
 
 fn render_window(
     state: &EditorState,
-    stdout: &mut Stdout,
+    pen: &mut GridWriter,
     window: &crate::state::Window,
-) -> std::io::Result<()> {
+    total_width: u16,
+    support: ColorSupport,
+) {
     let buffer = match state.buffers.get(window.buffer_id) {
         Some(b) => b,
-        None => return Ok(()),
+        None => return,
     };
 
-    for row in 0..window.height {
-        let line_idx = window.scroll_line + row as usize;
-        let y = window.y + row;
+    // Side-by-side windows get a `│` divider in their rightmost column;
+    // the bottom row of every window is reserved for its modeline.
+    let has_right_neighbor = window.x + window.width < total_width;
+    let gutter = if window.display_line_numbers.is_some() {
+        LineNumberStyle::gutter_width(buffer.text.total_lines()) as u16
+    } else {
+        0
+    };
+    let text_width = if has_right_neighbor {
+        window.width.saturating_sub(1)
+    } else {
+        window.width
+    }
+    .saturating_sub(gutter);
+
+    let text_height = window.height.saturating_sub(1);
+    let current_line = buffer
+        .text
+        .char_to_position(window.cursors.primary.position)
+        .line;
+
+    // show-paren: invert both halves of a matched bracket pair
+    let is_active_window = state.windows.current().map(|w| w.id) == Some(window.id);
+    let paren_pair = if is_active_window {
+        state.matching_bracket_pair()
+    } else {
+        None
+    };
+    let hl_line_row = (state.hl_line && is_active_window).then_some(current_line);
+    let rect_region = window.rectangle_region(&buffer.text);
+    let bracket_depths = state
+        .rainbow_delimiters
+        .then(|| crate::core::rope_ext::bracket_depths(&buffer.text));
+
+    // Lines outside a narrowed range render like past-EOF space.
+    let (narrow_first, narrow_last) = {
+        let (start, end) = buffer.narrow_bounds();
+        (
+            buffer.text.char_to_position(start).line,
+            buffer.text.char_to_position(end).line,
+        )
+    };
+    let line_visible =
+        |idx: usize| idx < buffer.text.total_lines() && (narrow_first..=narrow_last).contains(&idx);
+
+    // Styled spans of the markdown preview, when this window shows it
+    let preview_lines = state
+        .markdown_preview
+        .as_ref()
+        .filter(|p| p.preview == window.buffer_id)
+        .map(|p| &p.lines);
+
+    // Each screen row shows one segment of a logical line: the line
+    // index and the char column its text starts at. Under visual-line
+    // wrapping a long line spans several rows, chunked at the same
+    // wrap width the motion commands use; otherwise row N is simply
+    // logical line scroll_line + N.
+    let wrap = state
+        .visual_line_mode
+        .then(|| window.wrap_width(buffer.text.total_lines()));
+    let mut rows: Vec<(usize, usize)> = Vec::with_capacity(text_height as usize);
+    if let Some(wrap) = wrap {
+        let mut line_idx = window.scroll_line;
+        let mut seg_start = 0;
+        while rows.len() < text_height as usize {
+            rows.push((line_idx, seg_start));
+            if line_visible(line_idx) && seg_start + wrap <= buffer.text.line_len_chars(line_idx) {
+                seg_start += wrap;
+            } else {
+                line_idx += 1;
+                seg_start = 0;
+            }
+        }
+    } else {
+        // Without wrapping every row starts at the window's horizontal
+        // scroll offset instead.
+        for row in 0..text_height {
+            rows.push((window.scroll_line + row as usize, window.scroll_column));
+        }
+    }
+
+    for (row, &(line_idx, seg_start)) in rows.iter().enumerate() {
+        let y = window.y + row as u16;
+
+        pen.move_to(window.x, y);
+
+        if let (true, Some(style)) = (gutter > 0, window.display_line_numbers) {
+            // Continuation rows leave the gutter blank.
+            if line_visible(line_idx) && (wrap.is_none() || seg_start == 0) {
+                let color = if line_idx == current_line {
+                    Color::White
+                } else {
+                    Color::DarkGrey
+                };
+                pen.fg = Some(color);
+                pen.print_str(&format!(
+                    "{:>width$} ",
+                    style.label(line_idx, current_line),
+                    width = gutter as usize - 1
+                ));
+                pen.reset_color();
+            } else {
+                for _ in 0..gutter {
+                    pen.print(' ');
+                }
+            }
+        }
 
-        queue!(stdout, MoveTo(window.x, y))?;
+        let hl_line = hl_line_row == Some(line_idx);
 
-        if line_idx < buffer.text.total_lines() {
+        if line_visible(line_idx) {
             let line = buffer.text.line(line_idx);
-            let line_str: String = line.chars().take(window.width as usize).collect();
+            // Whether the line keeps going on the next screen row; the
+            // last column is then reserved for the wrap indicator.
+            let continues = wrap
+                .map(|w| seg_start + w <= buffer.text.line_len_chars(line_idx))
+                .unwrap_or(false);
+            // When horizontally scrolled, the first cell shows a `$`
+            // truncation marker in place of the first visible char.
+            let truncated_left =
+                wrap.is_none() && seg_start > 0 && buffer.text.line_len_chars(line_idx) > 0;
+            let seg_start = seg_start + truncated_left as usize;
+            // Truncate by cells, not chars, so wide glyphs never spill
+            // past the window edge.
+            let mut line_str = String::new();
+            let mut printed_len = truncated_left as usize;
+            let mut seg_chars = 0;
+            for ch in line.chars().skip(seg_start) {
+                if wrap.map(|w| seg_chars >= w).unwrap_or(false) {
+                    break;
+                }
+                let cells = char_cells(ch);
+                if printed_len + cells > text_width as usize {
+                    break;
+                }
+                printed_len += cells;
+                seg_chars += 1;
+                line_str.push(ch);
+            }
 
             let line_start_char = buffer.text.line_start_char(line_idx).0;
 
+            // Per-char span colors for this line, if it has styled runs
+            let span_colors: Option<Vec<Option<Color>>> = preview_lines
+                .and_then(|lines| lines.get(line_idx))
+                .map(|spans| {
+                    spans
+                        .iter()
+                        .flat_map(|span| {
+                            let color = md_style_color(support, span.style);
+                            span.text.chars().map(move |_| color)
+                        })
+                        .collect()
+                });
+
+            if truncated_left {
+                pen.fg = Some(Color::DarkGrey);
+                if hl_line {
+                    pen.bg = Some(hl_line_bg(support));
+                }
+                pen.print('$');
+                pen.reset_color();
+            }
+
             for (col, ch) in line_str.chars().enumerate() {
-                if col >= window.width as usize {
+                if col >= text_width as usize {
                     break;
                 }
 
-                let char_offset = line_start_char + col;
+                let char_offset = line_start_char + seg_start + col;
 
                 let mut in_any_region = false;
-                for cursor in window.cursors.all_cursors() {
-                    if let Some((start, end)) = cursor.region() {
-                        if char_offset >= start.0 && char_offset < end.0 {
-                            in_any_region = true;
-                            break;
+                if let Some((top, bottom, left, right)) = rect_region {
+                    // Rectangle selections highlight only the columnar
+                    // intersection of each line.
+                    in_any_region = (top..=bottom).contains(&line_idx)
+                        && (left..right).contains(&(seg_start + col));
+                } else {
+                    for cursor in window.cursors.all_cursors() {
+                        if let Some((start, end)) = cursor.region() {
+                            if char_offset >= start.0 && char_offset < end.0 {
+                                in_any_region = true;
+                                break;
+                            }
                         }
                     }
                 }
@@ -71,49 +585,77 @@ fn render_window(
                     .all_cursors()
                     .any(|c| c.position.0 == char_offset);
                 let is_primary_cursor = window.cursors.primary.position.0 == char_offset;
+                let is_paren_match = paren_pair
+                    .map(|(open, close)| open.0 == char_offset || close.0 == char_offset)
+                    .unwrap_or(false);
 
                 if is_primary_cursor {
-                    queue!(
-                        stdout,
-                        SetBackgroundColor(Color::Black),
-                        SetForegroundColor(Color::White)
-                    )?;
+                    pen.bg = Some(Color::Black);
+                    pen.fg = Some(Color::White);
                 } else if in_any_region {
-                    queue!(
-                        stdout,
-                        SetBackgroundColor(Color::Blue),
-                        SetForegroundColor(Color::White)
-                    )?;
+                    pen.bg = Some(Color::Blue);
+                    pen.fg = Some(Color::White);
                 } else if is_cursor_pos {
-                    queue!(
-                        stdout,
-                        SetBackgroundColor(Color::DarkGrey),
-                        SetForegroundColor(Color::White)
-                    )?;
+                    pen.bg = Some(Color::DarkGrey);
+                    pen.fg = Some(Color::White);
+                } else if is_paren_match {
+                    pen.reverse = true;
+                } else if hl_line {
+                    pen.bg = Some(hl_line_bg(support));
+                }
+
+                // Span colors lose to the cursor/region styling above
+                let span_color = span_colors
+                    .as_ref()
+                    .and_then(|colors| colors.get(seg_start + col).copied().flatten())
+                    .filter(|_| !(is_primary_cursor || in_any_region || is_cursor_pos));
+                if let Some(color) = span_color {
+                    pen.fg = Some(color);
+                }
+
+                // Rainbow delimiters color only the brackets themselves,
+                // layered over span colors but under cursor/region styling
+                let rainbow_color = bracket_depths
+                    .as_ref()
+                    .and_then(|depths| {
+                        depths.get(&crate::core::position::CharOffset(char_offset))
+                    })
+                    .map(|&depth| RAINBOW_COLORS[depth % RAINBOW_COLORS.len()])
+                    .filter(|_| !(is_primary_cursor || in_any_region || is_cursor_pos));
+                if let Some(color) = rainbow_color {
+                    pen.fg = Some(color);
                 }
 
                 if ch == '\n' {
-                    queue!(stdout, Print(' '))?;
+                    pen.print(' ');
                 } else if ch == '\t' {
-                    queue!(stdout, Print("    "))?;
+                    pen.print_str("    ");
                 } else {
-                    queue!(stdout, Print(ch))?;
+                    pen.print(ch);
                 }
 
-                if is_primary_cursor || in_any_region || is_cursor_pos {
-                    queue!(stdout, ResetColor)?;
+                if is_primary_cursor
+                    || in_any_region
+                    || is_cursor_pos
+                    || hl_line
+                    || span_color.is_some()
+                    || rainbow_color.is_some()
+                {
+                    pen.reset_color();
+                }
+                if is_paren_match {
+                    pen.reverse = false;
                 }
             }
 
-            let line_char_count = line_str.chars().count();
-            let printed_len = line_str
-                .chars()
-                .map(|c| if c == '\t' { 4 } else { 1 })
-                .sum::<usize>();
-
             let line_ends_with_newline = line_str.ends_with('\n');
-            let cursor_at_eol = line_start_char + line_char_count;
-            let check_eol_cursor = !line_ends_with_newline;
+            let cursor_at_eol = line_start_char + seg_start + seg_chars;
+            // A cursor past the segment belongs to a later row when the
+            // line continues; a line scrolled entirely off the left edge
+            // has no end-of-line cell at all.
+            let check_eol_cursor = !line_ends_with_newline
+                && !continues
+                && seg_start <= buffer.text.line_len_chars(line_idx);
             let is_primary_at_eol =
                 check_eol_cursor && window.cursors.primary.position.0 == cursor_at_eol;
             let is_any_cursor_at_eol = check_eol_cursor
@@ -122,63 +664,71 @@ fn render_window(
                     .all_cursors()
                     .any(|c| c.position.0 == cursor_at_eol);
 
+            let pad_width = if continues {
+                // Leave the last column for the wrap indicator.
+                (text_width as usize).saturating_sub(1)
+            } else {
+                text_width as usize
+            };
             let mut first_pad = true;
-            for _ in printed_len..window.width as usize {
+            for _ in printed_len..pad_width {
                 if first_pad && is_primary_at_eol {
-                    queue!(
-                        stdout,
-                        SetBackgroundColor(Color::Black),
-                        SetForegroundColor(Color::White),
-                        Print(' '),
-                        ResetColor
-                    )?;
+                    pen.bg = Some(Color::Black);
+                    pen.fg = Some(Color::White);
+                    pen.print(' ');
+                    pen.reset_color();
                 } else if first_pad && is_any_cursor_at_eol {
-                    queue!(
-                        stdout,
-                        SetBackgroundColor(Color::DarkGrey),
-                        SetForegroundColor(Color::White),
-                        Print(' '),
-                        ResetColor
-                    )?;
+                    pen.bg = Some(Color::DarkGrey);
+                    pen.fg = Some(Color::White);
+                    pen.print(' ');
+                    pen.reset_color();
+                } else if hl_line {
+                    pen.bg = Some(hl_line_bg(support));
+                    pen.print(' ');
+                    pen.reset_color();
                 } else {
-                    queue!(stdout, Print(' '))?;
+                    pen.print(' ');
                 }
                 first_pad = false;
             }
+            if continues && printed_len < text_width as usize {
+                pen.fg = Some(Color::DarkGrey);
+                pen.print('\\');
+                pen.reset_color();
+            }
         } else {
-            queue!(
-                stdout,
-                SetForegroundColor(Color::DarkGrey),
-                Print('~'),
-                ResetColor
-            )?;
-            for _ in 1..window.width {
-                queue!(stdout, Print(' '))?;
+            pen.fg = Some(Color::DarkGrey);
+            pen.print('~');
+            pen.reset_color();
+            for _ in 1..text_width {
+                pen.print(' ');
             }
         }
-    }
 
-    Ok(())
+        if has_right_neighbor {
+            pen.fg = Some(Color::DarkGrey);
+            pen.print('│');
+            pen.reset_color();
+        }
+    }
 }
 
-fn render_modeline(
+fn render_window_modeline(
     state: &EditorState,
-    stdout: &mut Stdout,
-    width: u16,
-    height: u16,
-) -> std::io::Result<()> {
-    let modeline_y = height - 2;
-
-    queue!(
-        stdout,
-        MoveTo(0, modeline_y),
-        SetBackgroundColor(Color::White),
-        SetForegroundColor(Color::Black),
-        SetAttribute(Attribute::Bold)
-    )?;
-
-    let buffer = state.current_buffer();
-    let window = state.current_window();
+    pen: &mut GridWriter,
+    window: &crate::state::Window,
+    active: bool,
+) {
+    let modeline_y = window.y + window.height.saturating_sub(1);
+
+    pen.move_to(window.x, modeline_y);
+    pen.bg = Some(Color::White);
+    pen.fg = Some(Color::Black);
+    if active {
+        pen.bold = true;
+    }
+
+    let buffer = state.buffers.get(window.buffer_id);
     let buffer_name = buffer.map(|b| b.name.as_str()).unwrap_or("[No buffer]");
     let modified = buffer
         .map(|b| if b.modified { "**" } else { "--" })
@@ -186,63 +736,49 @@ fn render_modeline(
     let readonly = buffer
         .map(|b| if b.read_only { "%%" } else { "--" })
         .unwrap_or("--");
-
-    let mark_indicator = window
-        .map(|w| {
-            if w.cursors.primary.mark_active {
-                " Mark"
-            } else {
-                ""
-            }
-        })
+    let mode_name = buffer
+        .map(|b| b.major_mode.name)
+        .unwrap_or(crate::core::MajorMode::FUNDAMENTAL.name);
+    let overwrite = buffer
+        .map(|b| if b.overwrite { " Ovwrt" } else { "" })
         .unwrap_or("");
 
-    let cursor_indicator = window
-        .map(|w| {
-            if w.cursors.count() > 1 {
-                format!(" [{}c]", w.cursors.count())
-            } else {
-                String::new()
-            }
-        })
-        .unwrap_or_default();
+    let mark_indicator = if window.cursors.primary.mark_active {
+        " Mark"
+    } else {
+        ""
+    };
 
-    let (line, col) = match (buffer, window) {
-        (Some(b), Some(w)) => {
-            let pos = b.text.char_to_position(w.cursors.primary.position);
-            (pos.line + 1, pos.column + 1)
-        }
-        _ => (1, 1),
+    let cursor_indicator = if window.cursors.count() > 1 {
+        format!(" [{}c]", window.cursors.count())
+    } else {
+        String::new()
     };
 
+    let (line, col) = state.window_position(window);
+
     let left = format!(
-        "-{}:{}- {}{}{} ",
-        modified, readonly, buffer_name, mark_indicator, cursor_indicator
+        "-{}:{}- {} ({}){}{}{} ",
+        modified, readonly, buffer_name, mode_name, overwrite, mark_indicator, cursor_indicator
     );
     let right = format!(" L{}:C{} ", line, col);
 
-    let padding = (width as usize).saturating_sub(left.len() + right.len());
+    let padding = (window.width as usize).saturating_sub(left.len() + right.len());
 
-    queue!(stdout, Print(&left))?;
+    pen.print_str(&left);
     for _ in 0..padding {
-        queue!(stdout, Print('-'))?;
+        pen.print('-');
     }
-    queue!(stdout, Print(&right))?;
+    pen.print_str(&right);
 
-    queue!(stdout, ResetColor, SetAttribute(Attribute::Reset))?;
-
-    Ok(())
+    pen.reset_all();
 }
 
-fn render_minibuffer(
-    state: &EditorState,
-    stdout: &mut Stdout,
-    width: u16,
-    height: u16,
-) -> std::io::Result<()> {
+fn render_minibuffer(state: &EditorState, pen: &mut GridWriter, width: u16, height: u16) {
     let y = height - 1;
 
-    queue!(stdout, MoveTo(0, y), ResetColor)?;
+    pen.move_to(0, y);
+    pen.reset_color();
 
     let content = if state.minibuffer.is_active() {
         state.minibuffer.display()
@@ -262,15 +798,12 @@ fn render_minibuffer(
 
     for (i, ch) in content.chars().take(width as usize).enumerate() {
         if cursor_pos == Some(i) {
-            queue!(
-                stdout,
-                SetBackgroundColor(Color::Black),
-                SetForegroundColor(Color::White),
-                Print(ch),
-                ResetColor
-            )?;
+            pen.bg = Some(Color::Black);
+            pen.fg = Some(Color::White);
+            pen.print(ch);
+            pen.reset_color();
         } else {
-            queue!(stdout, Print(ch))?;
+            pen.print(ch);
         }
     }
 
@@ -278,17 +811,92 @@ fn render_minibuffer(
 
     for i in content_len..width as usize {
         if cursor_pos == Some(i) {
-            queue!(
-                stdout,
-                SetBackgroundColor(Color::Black),
-                SetForegroundColor(Color::White),
-                Print(' '),
-                ResetColor
-            )?;
+            pen.bg = Some(Color::Black);
+            pen.fg = Some(Color::White);
+            pen.print(' ');
+            pen.reset_color();
         } else {
-            queue!(stdout, Print(' '))?;
+            pen.print(' ');
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid_with(text: &str) -> Grid {
+        let mut grid = Grid::new(10, 2);
+        let mut pen = GridWriter::new(&mut grid);
+        pen.print_str(text);
+        grid
+    }
 
-    Ok(())
+    #[test]
+    fn test_char_cells_widths() {
+        assert_eq!(char_cells('a'), 1);
+        assert_eq!(char_cells('\t'), 4);
+        assert_eq!(char_cells('日'), 2);
+    }
+
+    #[test]
+    fn test_flush_diff_emits_nothing_for_identical_frames() {
+        let grid = grid_with("hello");
+        let mut out = Vec::new();
+        flush_diff(&mut out, Some(&grid), &grid).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_flush_diff_emits_only_changed_cells() {
+        let prev = grid_with("hello");
+        let next = grid_with("hallo");
+
+        let mut out = Vec::new();
+        flush_diff(&mut out, Some(&prev), &next).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains('a'));
+        assert!(!text.contains('h'));
+        assert!(!text.contains("llo"));
+    }
+
+    #[test]
+    fn test_flush_diff_repaints_fully_without_a_back_buffer() {
+        let next = grid_with("hi");
+        let mut out = Vec::new();
+        flush_diff(&mut out, None, &next).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("hi"));
+        // The rest of the grid is spaces, also painted on first frame
+        assert!(text.matches(' ').count() >= 18);
+    }
+
+    #[test]
+    fn test_wide_glyphs_occupy_a_continuation_cell() {
+        let grid = grid_with("日x");
+        assert_eq!(grid.get(0, 0).ch, '日');
+        assert_eq!(grid.get(1, 0).ch, '\0');
+        assert_eq!(grid.get(2, 0).ch, 'x');
+
+        // Continuation cells are skipped, so the glyph is printed once
+        let mut out = Vec::new();
+        flush_diff(&mut out, None, &grid).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.matches('日').count(), 1);
+    }
+
+    #[test]
+    fn test_rgb_to_ansi256_cube_corners() {
+        assert_eq!(rgb_to_ansi256(0, 0, 0), 16);
+        assert_eq!(rgb_to_ansi256(255, 255, 255), 231);
+        assert_eq!(rgb_to_ansi256(255, 0, 0), 196);
+        assert_eq!(rgb_to_ansi256(0, 0, 255), 21);
+    }
+
+    #[test]
+    fn test_rgb_to_ansi256_prefers_gray_ramp() {
+        // 0x808080 sits closer to gray entry 244 (0x808080) than to any
+        // cube level.
+        assert_eq!(rgb_to_ansi256(128, 128, 128), 244);
+    }
 }
@@ -24,6 +24,16 @@ pub fn default_keymap() -> KeyMap {
     map.bind_command(KeyEvent::meta('f'), "forward-word");
     map.bind_command(KeyEvent::meta('b'), "backward-word");
 
+    // Balanced-expression movement (Ctrl+Meta)
+    map.bind_command(
+        KeyEvent::new(Key::Char('f'), Modifiers::CTRL | Modifiers::META),
+        "forward-sexp",
+    );
+    map.bind_command(
+        KeyEvent::new(Key::Char('b'), Modifiers::CTRL | Modifiers::META),
+        "backward-sexp",
+    );
+
     // Shift+Meta word movement (selection)
     map.bind_command(KeyEvent::meta_shift('f'), "forward-word-shift");
     map.bind_command(KeyEvent::meta_shift('b'), "backward-word-shift");
@@ -50,6 +60,7 @@ pub fn default_keymap() -> KeyMap {
     map.bind_command(KeyEvent::ctrl('v'), "scroll-up-command");
     map.bind_command(KeyEvent::meta('v'), "scroll-down-command");
     map.bind_command(KeyEvent::ctrl('l'), "recenter-top-bottom");
+    map.bind_command(KeyEvent::meta('r'), "move-to-window-line-top-bottom");
 
     map.bind_command(KeyEvent::new(Key::Right, Modifiers::NONE), "forward-char");
     map.bind_command(KeyEvent::new(Key::Left, Modifiers::NONE), "backward-char");
@@ -133,9 +144,15 @@ pub fn default_keymap() -> KeyMap {
         "delete-backward-char",
     );
     map.bind_command(KeyEvent::new(Key::Enter, Modifiers::NONE), "newline");
+    map.bind_command(
+        KeyEvent::new(Key::Tab, Modifiers::NONE),
+        "indent-for-tab-command",
+    );
+    map.bind_command(KeyEvent::new(Key::Insert, Modifiers::NONE), "overwrite-mode");
+    map.bind_command(KeyEvent::ctrl('q'), "quoted-insert");
     map.bind_command(KeyEvent::ctrl('o'), "open-line");
     map.bind_command(KeyEvent::ctrl('t'), "transpose-chars");
-    map.bind_command(KeyEvent::ctrl('j'), "newline");
+    map.bind_command(KeyEvent::ctrl('j'), "newline-and-indent");
 
     map.bind_command(KeyEvent::ctrl('k'), "kill-line");
     map.bind_command(KeyEvent::meta('d'), "kill-word");
@@ -153,14 +170,37 @@ pub fn default_keymap() -> KeyMap {
         "set-mark-command",
     );
     map.bind_command(KeyEvent::meta('h'), "mark-paragraph");
+    map.bind_command(KeyEvent::meta('q'), "fill-paragraph");
+    map.bind_command(KeyEvent::meta('='), "count-words-region");
 
     map.bind_command(KeyEvent::new(Key::Char('/'), Modifiers::CTRL), "undo");
 
     // alternative keyboard layout is gay
     map.bind_command(KeyEvent::ctrl('\''), "spawn-cursors-at-word-matches");
+    map.bind_command(KeyEvent::ctrl('>'), "mark-next-like-this");
+    map.bind_command(KeyEvent::ctrl('<'), "unmark-last-like-this");
+    map.bind_command(
+        KeyEvent::new(Key::Down, Modifiers::CTRL | Modifiers::SHIFT),
+        "add-cursor-on-next-line",
+    );
+    map.bind_command(
+        KeyEvent::new(Key::Up, Modifiers::CTRL | Modifiers::SHIFT),
+        "add-cursor-on-previous-line",
+    );
+
+    map.bind_command(KeyEvent::ctrl('u'), "universal-argument");
+    map.bind_command(KeyEvent::ctrl('-'), "negative-argument");
 
     map.bind_command(KeyEvent::ctrl('g'), "keyboard-quit");
     map.bind_command(KeyEvent::meta('x'), "execute-extended-command");
+    map.bind_command(
+        KeyEvent::new(Key::Char('!'), Modifiers::META),
+        "shell-command",
+    );
+    map.bind_command(
+        KeyEvent::new(Key::Char('|'), Modifiers::META),
+        "shell-command-on-region",
+    );
 
     let mut cx_map = KeyMap::new();
 
@@ -168,8 +208,12 @@ pub fn default_keymap() -> KeyMap {
     cx_map.bind_command(KeyEvent::ctrl('w'), "write-file");
     cx_map.bind_command(KeyEvent::ctrl('f'), "find-file");
     cx_map.bind_command(KeyEvent::char('b'), "switch-to-buffer");
+    cx_map.bind_command(KeyEvent::char('i'), "insert-file");
     cx_map.bind_command(KeyEvent::char('k'), "kill-buffer");
     cx_map.bind_command(KeyEvent::ctrl('b'), "list-buffers");
+    cx_map.bind_command(KeyEvent::ctrl('q'), "read-only-mode");
+    cx_map.bind_command(KeyEvent::char('='), "what-cursor-position");
+    cx_map.bind_command(KeyEvent::ctrl('n'), "set-goal-column");
 
     cx_map.bind_command(KeyEvent::char('2'), "split-window-below");
     cx_map.bind_command(KeyEvent::char('3'), "split-window-right");
@@ -177,15 +221,50 @@ pub fn default_keymap() -> KeyMap {
     cx_map.bind_command(KeyEvent::char('1'), "delete-other-windows");
     cx_map.bind_command(KeyEvent::char('o'), "other-window");
 
+    cx_map.bind_command(KeyEvent::ctrl('+'), "text-scale-increase");
+    cx_map.bind_command(KeyEvent::ctrl('-'), "text-scale-decrease");
+
     cx_map.bind_command(KeyEvent::ctrl('x'), "exchange-point-and-mark");
     cx_map.bind_command(KeyEvent::char('h'), "mark-whole-buffer");
     cx_map.bind_command(KeyEvent::char('u'), "undo");
     cx_map.bind_command(KeyEvent::char('m'), "spawn-cursors-at-word-matches");
+    cx_map.bind_command(KeyEvent::char(' '), "rectangle-mark-mode");
+    cx_map.bind_command(KeyEvent::new(Key::Char(' '), Modifiers::CTRL), "pop-global-mark");
+
+    cx_map.bind_command(KeyEvent::ctrl(';'), "comment-line");
+    cx_map.bind_command(KeyEvent::new(Key::Tab, Modifiers::NONE), "indent-rigidly");
 
     cx_map.bind_command(KeyEvent::ctrl('c'), "exit");
 
+    cx_map.bind_command(KeyEvent::char('('), "start-kbd-macro");
+    cx_map.bind_command(KeyEvent::char(')'), "end-kbd-macro");
+    cx_map.bind_command(KeyEvent::char('e'), "call-last-kbd-macro");
+    cx_map.bind_command(KeyEvent::char('z'), "repeat");
+
+    let mut narrow_map = KeyMap::new();
+    narrow_map.bind_command(KeyEvent::char('n'), "narrow-to-region");
+    narrow_map.bind_command(KeyEvent::char('w'), "widen");
+    cx_map.bind_prefix(KeyEvent::char('n'), narrow_map);
+
+    let mut register_map = KeyMap::new();
+    register_map.bind_command(KeyEvent::char(' '), "point-to-register");
+    register_map.bind_command(KeyEvent::char('j'), "jump-to-register");
+    register_map.bind_command(KeyEvent::char('s'), "copy-to-register");
+    register_map.bind_command(KeyEvent::char('i'), "insert-register");
+    cx_map.bind_prefix(KeyEvent::char('r'), register_map);
+
     map.bind_prefix(KeyEvent::ctrl('x'), cx_map);
 
+    let mut cc_map = KeyMap::new();
+    cc_map.bind_command(KeyEvent::new(Key::Left, Modifiers::NONE), "winner-undo");
+    cc_map.bind_command(KeyEvent::new(Key::Right, Modifiers::NONE), "winner-redo");
+    map.bind_prefix(KeyEvent::ctrl('c'), cc_map);
+
+    let mut help_map = KeyMap::new();
+    help_map.bind_command(KeyEvent::char('k'), "describe-key");
+    help_map.bind_command(KeyEvent::char('b'), "describe-bindings");
+    map.bind_prefix(KeyEvent::ctrl('h'), help_map);
+
     let mut mg_map = KeyMap::new();
     mg_map.bind_command(KeyEvent::char('g'), "goto-line");
     map.bind_prefix(KeyEvent::meta('g'), mg_map);
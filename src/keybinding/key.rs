@@ -140,6 +140,69 @@ impl fmt::Display for KeyEvent {
     }
 }
 
+impl std::str::FromStr for KeyEvent {
+    type Err = String;
+
+    /// Parses the textual form `Display` produces ("C-x", "M-<f5>",
+    /// "<return>"), plus "SPC" for the space character.
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::NONE;
+        let mut rest = spec;
+        loop {
+            if let Some(r) = rest.strip_prefix("C-") {
+                modifiers |= Modifiers::CTRL;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("M-") {
+                modifiers |= Modifiers::META;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("s-") {
+                modifiers |= Modifiers::SUPER;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("S-") {
+                modifiers |= Modifiers::SHIFT;
+                rest = r;
+            } else {
+                break;
+            }
+        }
+
+        let key = match rest {
+            "SPC" => Key::Char(' '),
+            "<backspace>" => Key::Backspace,
+            "<tab>" => Key::Tab,
+            "<return>" => Key::Enter,
+            "<escape>" => Key::Escape,
+            "<up>" => Key::Up,
+            "<down>" => Key::Down,
+            "<left>" => Key::Left,
+            "<right>" => Key::Right,
+            "<home>" => Key::Home,
+            "<end>" => Key::End,
+            "<prior>" => Key::PageUp,
+            "<next>" => Key::PageDown,
+            "<insert>" => Key::Insert,
+            "<delete>" => Key::Delete,
+            _ => {
+                if let Some(n) = rest
+                    .strip_prefix("<f")
+                    .and_then(|r| r.strip_suffix('>'))
+                    .and_then(|n| n.parse::<u8>().ok())
+                {
+                    Key::F(n)
+                } else {
+                    let mut chars = rest.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Key::Char(c),
+                        _ => return Err(format!("Unknown key: {}", spec)),
+                    }
+                }
+            }
+        };
+
+        Ok(Self { key, modifiers })
+    }
+}
+
 impl From<crossterm::event::KeyEvent> for KeyEvent {
     fn from(event: crossterm::event::KeyEvent) -> Self {
         use crossterm::event::{KeyCode, KeyModifiers};
@@ -248,6 +311,26 @@ mod tests {
         assert_eq!(KeyEvent::char('a').to_string(), "a");
     }
 
+    #[test]
+    fn test_key_parse_round_trips_display() {
+        for event in [
+            KeyEvent::ctrl('x'),
+            KeyEvent::ctrl_meta('f'),
+            KeyEvent::char('a'),
+            KeyEvent::new(Key::F(5), Modifiers::META),
+            KeyEvent::new(Key::Enter, Modifiers::NONE),
+        ] {
+            assert_eq!(event.to_string().parse::<KeyEvent>(), Ok(event));
+        }
+
+        assert_eq!("SPC".parse::<KeyEvent>(), Ok(KeyEvent::char(' ')));
+        assert_eq!(
+            "C--".parse::<KeyEvent>(),
+            Ok(KeyEvent::new(Key::Char('-'), Modifiers::CTRL))
+        );
+        assert!("C-xy".parse::<KeyEvent>().is_err());
+    }
+
     #[test]
     fn test_is_printable() {
         assert!(KeyEvent::char('a').is_printable());
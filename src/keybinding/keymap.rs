@@ -69,9 +69,48 @@ impl KeyMap {
         self.get_prefix_mut(&key).unwrap()
     }
 
+    /// Binds a multi-key sequence, creating intermediate prefix maps as
+    /// needed.
+    pub fn bind_sequence(&mut self, keys: &[KeyEvent], command: &'static str) {
+        let Some((last, prefix)) = keys.split_last() else {
+            return;
+        };
+        let mut map = self;
+        for key in prefix {
+            map = map.ensure_prefix(*key);
+        }
+        map.bind_command(*last, command);
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&KeyEvent, &KeyBinding)> {
         self.bindings.iter()
     }
+
+    /// Reverse lookup: a key sequence bound to `command`, descending into
+    /// prefix maps. Prefers the shortest (then lexicographically first)
+    /// sequence so the answer is stable despite hash-map iteration order.
+    pub fn key_for_command(&self, command: &str) -> Option<String> {
+        let mut best: Option<String> = None;
+        for (key, binding) in &self.bindings {
+            let candidate = match binding {
+                KeyBinding::Command(c) if *c == command => Some(key.to_string()),
+                KeyBinding::Prefix(map) => map
+                    .key_for_command(command)
+                    .map(|rest| format!("{} {}", key, rest)),
+                _ => None,
+            };
+            if let Some(c) = candidate {
+                if best
+                    .as_ref()
+                    .map(|b| (c.len(), &c) < (b.len(), b))
+                    .unwrap_or(true)
+                {
+                    best = Some(c);
+                }
+            }
+        }
+        best
+    }
 }
 
 #[cfg(test)]
@@ -90,6 +129,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_key_for_command_descends_prefixes() {
+        let mut map = KeyMap::new();
+        map.bind_command(KeyEvent::ctrl('f'), "forward-char");
+        let mut prefix = KeyMap::new();
+        prefix.bind_command(KeyEvent::ctrl('s'), "save-buffer");
+        map.bind_prefix(KeyEvent::ctrl('x'), prefix);
+
+        assert_eq!(map.key_for_command("forward-char").as_deref(), Some("C-f"));
+        assert_eq!(
+            map.key_for_command("save-buffer").as_deref(),
+            Some("C-x C-s")
+        );
+        assert_eq!(map.key_for_command("no-such-command"), None);
+    }
+
     #[test]
     fn test_bind_prefix() {
         let mut map = KeyMap::new();
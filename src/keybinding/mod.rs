@@ -1,6 +1,7 @@
 pub mod default;
 pub mod key;
 pub mod keymap;
+pub mod parse;
 pub mod resolver;
 
 pub use key::{Key, KeyEvent, Modifiers};
@@ -0,0 +1,111 @@
+use super::key::{Key, KeyEvent, Modifiers};
+
+/// Parses a key spec like `"C-x C-s"` into the sequence of key events
+/// it names. Accepts the same names `KeyEvent`'s `Display` produces
+/// (`<tab>`, `<f5>`, ...) plus the Emacs shorthands `SPC`, `TAB`,
+/// `RET`, `ESC`, and `DEL`.
+pub fn parse_key_sequence(spec: &str) -> Result<Vec<KeyEvent>, String> {
+    let keys: Vec<KeyEvent> = spec
+        .split_whitespace()
+        .map(parse_key)
+        .collect::<Result<_, _>>()?;
+    if keys.is_empty() {
+        return Err("Empty key sequence".to_string());
+    }
+    Ok(keys)
+}
+
+fn parse_key(token: &str) -> Result<KeyEvent, String> {
+    let mut modifiers = Modifiers::NONE;
+    let mut rest = token;
+
+    loop {
+        let (modifier, tail) = if let Some(tail) = rest.strip_prefix("C-") {
+            (Modifiers::CTRL, tail)
+        } else if let Some(tail) = rest.strip_prefix("M-") {
+            (Modifiers::META, tail)
+        } else if let Some(tail) = rest.strip_prefix("S-") {
+            (Modifiers::SHIFT, tail)
+        } else if let Some(tail) = rest.strip_prefix("s-") {
+            (Modifiers::SUPER, tail)
+        } else {
+            break;
+        };
+        // A trailing "-" is the key itself, as in "C--"
+        if tail.is_empty() {
+            break;
+        }
+        modifiers |= modifier;
+        rest = tail;
+    }
+
+    let key = match rest {
+        "SPC" => Key::Char(' '),
+        "TAB" | "<tab>" => Key::Tab,
+        "RET" | "<return>" => Key::Enter,
+        "ESC" | "<escape>" => Key::Escape,
+        "DEL" | "<backspace>" => Key::Backspace,
+        "<delete>" => Key::Delete,
+        "<insert>" => Key::Insert,
+        "<up>" => Key::Up,
+        "<down>" => Key::Down,
+        "<left>" => Key::Left,
+        "<right>" => Key::Right,
+        "<home>" => Key::Home,
+        "<end>" => Key::End,
+        "<prior>" => Key::PageUp,
+        "<next>" => Key::PageDown,
+        _ => {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Key::Char(c),
+                _ => rest
+                    .strip_prefix("<f")
+                    .and_then(|r| r.strip_suffix('>'))
+                    .and_then(|n| n.parse().ok())
+                    .map(Key::F)
+                    .ok_or_else(|| format!("Invalid key: {}", token))?,
+            }
+        }
+    };
+
+    Ok(KeyEvent::new(key, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_modifiers_and_named_keys() {
+        assert_eq!(
+            parse_key_sequence("C-x C-s").unwrap(),
+            vec![KeyEvent::ctrl('x'), KeyEvent::ctrl('s')]
+        );
+        assert_eq!(
+            parse_key_sequence("C-M-f").unwrap(),
+            vec![KeyEvent::ctrl_meta('f')]
+        );
+        assert_eq!(
+            parse_key_sequence("TAB <f5> SPC").unwrap(),
+            vec![
+                KeyEvent::new(Key::Tab, Modifiers::NONE),
+                KeyEvent::new(Key::F(5), Modifiers::NONE),
+                KeyEvent::char(' ')
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_dash_is_a_key_not_a_modifier() {
+        assert_eq!(parse_key_sequence("C--").unwrap(), vec![KeyEvent::ctrl('-')]);
+        assert_eq!(parse_key_sequence("-").unwrap(), vec![KeyEvent::char('-')]);
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert!(parse_key_sequence("").is_err());
+        assert!(parse_key_sequence("C-x foo").is_err());
+        assert!(parse_key_sequence("<f>").is_err());
+    }
+}
@@ -13,6 +13,9 @@ pub enum KeyResolution {
 pub struct KeyResolver {
     pending_keys: Vec<KeyEvent>,
     prefix_display: String,
+    /// Set when a standalone ESC was pressed; the next key gets the
+    /// META modifier, for terminals (and users) that send ESC f for M-f.
+    esc_pending: bool,
 }
 
 impl Default for KeyResolver {
@@ -26,10 +29,28 @@ impl KeyResolver {
         Self {
             pending_keys: Vec::new(),
             prefix_display: String::new(),
+            esc_pending: false,
         }
     }
 
     pub fn resolve(&mut self, key: KeyEvent, keymap: &KeyMap) -> KeyResolution {
+        let mut key = key;
+
+        // A standalone ESC acts as a Meta prefix for the next key.
+        if key == KeyEvent::new(super::key::Key::Escape, super::key::Modifiers::NONE)
+            && self.pending_keys.is_empty()
+            && !self.esc_pending
+        {
+            self.esc_pending = true;
+            self.prefix_display = "ESC-".to_string();
+            return KeyResolution::Prefix(self.prefix_display.clone());
+        }
+        if self.esc_pending {
+            self.esc_pending = false;
+            self.prefix_display.clear();
+            key.modifiers |= super::key::Modifiers::META;
+        }
+
         self.pending_keys.push(key);
 
         let mut current_map = keymap;
@@ -78,16 +99,45 @@ impl KeyResolver {
     pub fn clear(&mut self) {
         self.pending_keys.clear();
         self.prefix_display.clear();
+        self.esc_pending = false;
     }
 
     pub fn is_pending(&self) -> bool {
-        !self.pending_keys.is_empty()
+        !self.pending_keys.is_empty() || self.esc_pending
     }
 
     pub fn pending_display(&self) -> &str {
         &self.prefix_display
     }
 
+    /// The next-key options of the pending prefix: each continuation
+    /// key with the command (or `+prefix`) it leads to, sorted so the
+    /// listing is stable despite hash-map iteration order.
+    pub fn pending_continuations(&self, keymap: &KeyMap) -> Vec<String> {
+        if self.pending_keys.is_empty() {
+            return Vec::new();
+        }
+
+        let mut map = keymap;
+        for key in &self.pending_keys {
+            match map.get(key) {
+                Some(KeyBinding::Prefix(sub)) => map = sub,
+                _ => return Vec::new(),
+            }
+        }
+
+        let mut options: Vec<String> = map
+            .iter()
+            .filter_map(|(key, binding)| match binding {
+                KeyBinding::Command(cmd) => Some(format!("{}: {}", key, cmd)),
+                KeyBinding::Prefix(_) => Some(format!("{}: +prefix", key)),
+                KeyBinding::Unbound => None,
+            })
+            .collect();
+        options.sort_unstable();
+        options
+    }
+
     fn update_prefix_display(&mut self) {
         self.prefix_display = self
             .pending_keys
@@ -102,7 +152,7 @@ impl KeyResolver {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::keybinding::key::Key;
+    use crate::keybinding::key::{Key, Modifiers};
 
     fn make_test_keymap() -> KeyMap {
         let mut map = KeyMap::new();
@@ -160,6 +210,48 @@ mod tests {
         assert!(matches!(result, KeyResolution::Unbound(_)));
     }
 
+    #[test]
+    fn test_esc_acts_as_meta_prefix() {
+        let mut keymap = make_test_keymap();
+        keymap.bind_command(KeyEvent::meta('f'), "forward-word");
+        let mut resolver = KeyResolver::new();
+
+        let result = resolver.resolve(KeyEvent::new(Key::Escape, Modifiers::NONE), &keymap);
+        assert_eq!(result, KeyResolution::Prefix("ESC-".to_string()));
+        assert!(resolver.is_pending());
+
+        let result = resolver.resolve(KeyEvent::char('f'), &keymap);
+        assert_eq!(result, KeyResolution::Complete("forward-word"));
+        assert!(!resolver.is_pending());
+    }
+
+    #[test]
+    fn test_esc_esc_is_meta_escape() {
+        let keymap = make_test_keymap();
+        let mut resolver = KeyResolver::new();
+
+        let _ = resolver.resolve(KeyEvent::new(Key::Escape, Modifiers::NONE), &keymap);
+        let result = resolver.resolve(KeyEvent::new(Key::Escape, Modifiers::NONE), &keymap);
+        assert_eq!(
+            result,
+            KeyResolution::Unbound(vec![KeyEvent::new(Key::Escape, Modifiers::META)])
+        );
+    }
+
+    #[test]
+    fn test_pending_continuations_list_prefix_map() {
+        let keymap = make_test_keymap();
+        let mut resolver = KeyResolver::new();
+
+        assert!(resolver.pending_continuations(&keymap).is_empty());
+
+        let _ = resolver.resolve(KeyEvent::ctrl('x'), &keymap);
+        assert_eq!(
+            resolver.pending_continuations(&keymap),
+            vec!["C-f: find-file", "C-s: save-buffer", "b: switch-to-buffer"]
+        );
+    }
+
     #[test]
     fn test_resolve_unbound_after_prefix() {
         let keymap = make_test_keymap();
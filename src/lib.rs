@@ -4,4 +4,5 @@ pub mod frontend;
 pub mod keybinding;
 pub mod state;
 
+pub use state::builder::EditorBuilder;
 pub use state::editor::EditorState;
@@ -1,20 +1,121 @@
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use std::io::{IsTerminal, Read};
+
+use enacs::commands::motion;
+use enacs::commands::CommandContext;
+use enacs::core::rope_ext::RopeExt;
+use enacs::core::{Buffer, Position};
 use enacs::frontend::{Frontend, TerminalFrontend};
 use enacs::state::EditorState;
 
+/// Parses a file argument of the form `file`, `file:line`, or
+/// `file:line:col` with one-based line and column, as printed by grep
+/// and compilers. A path that exists on disk is never split, so file
+/// names containing colons still open; non-numeric suffixes stay part
+/// of the name.
+fn parse_file_arg(arg: &str) -> (PathBuf, Option<(usize, usize)>) {
+    if Path::new(arg).exists() {
+        return (PathBuf::from(arg), None);
+    }
+
+    let parts: Vec<&str> = arg.split(':').collect();
+    if parts.len() >= 3 {
+        let line = parts[parts.len() - 2].parse::<usize>();
+        let col = parts[parts.len() - 1].parse::<usize>();
+        if let (Ok(line), Ok(col)) = (line, col) {
+            let path = parts[..parts.len() - 2].join(":");
+            return (PathBuf::from(path), Some((line, col)));
+        }
+    }
+    if parts.len() >= 2 {
+        if let Ok(line) = parts[parts.len() - 1].parse::<usize>() {
+            let path = parts[..parts.len() - 1].join(":");
+            return (PathBuf::from(path), Some((line, 1)));
+        }
+    }
+    (PathBuf::from(arg), None)
+}
+
+/// Loads piped stdin into a `*stdin*` buffer with no file path and
+/// displays it, for `cat file | enacs -` style use.
+fn load_stdin_buffer(state: &mut EditorState, content: &str) {
+    let buffer = Buffer::from_string("*stdin*", content);
+    let id = state.buffers.add(buffer);
+    state.buffers.set_current(id);
+    state.windows.set_current_buffer(id);
+}
+
+/// Moves point to a one-based `(line, col)` target in the current
+/// buffer, clamped to the text, and recenters the window on it.
+fn goto_target(state: &mut EditorState, line: usize, col: usize) {
+    let buffer_id = match state.windows.current() {
+        Some(w) => w.buffer_id,
+        None => return,
+    };
+    let pos = match state.buffers.get(buffer_id) {
+        Some(buffer) => buffer.text.position_to_char(Position::new(
+            line.saturating_sub(1),
+            col.saturating_sub(1),
+        )),
+        None => return,
+    };
+
+    if let Some(window) = state.windows.current_mut() {
+        for cursor in window.cursors.all_cursors_mut() {
+            cursor.position = pos;
+            cursor.goal_column = None;
+        }
+    }
+    let _ = motion::recenter_top_bottom(state, &CommandContext::new());
+}
+
 fn main() -> anyhow::Result<()> {
     env_logger::init();
 
     let mut state = EditorState::new();
+    state.load_init_file();
+    state.load_history_file();
 
     let args: Vec<String> = env::args().collect();
-    if args.len() > 1 && args[1] != "--gui" {
-        let path = PathBuf::from(&args[1]);
-        if let Err(e) = state.open_file(path) {
-            state.message = Some(format!("Error opening file: {}", e));
+    let has_file_args = args.iter().skip(1).any(|a| a != "--gui" && a != "-");
+    let read_stdin = args.iter().skip(1).any(|a| a == "-")
+        || (!has_file_args && !std::io::stdin().is_terminal());
+    if read_stdin {
+        // Crossterm reads events from /dev/tty, so the terminal
+        // frontend still works after stdin is exhausted.
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes)?;
+        load_stdin_buffer(&mut state, &String::from_utf8_lossy(&bytes));
+    }
+
+    let mut target: Option<(usize, usize)> = None;
+    for arg in args.iter().skip(1) {
+        if arg == "--gui" || arg == "-" {
+            continue;
         }
+        // A leading `+N` sets the line for the file that follows it
+        if let Some(line) = arg.strip_prefix('+').and_then(|n| n.parse::<usize>().ok()) {
+            target = Some((line, 1));
+            continue;
+        }
+
+        let (path, parsed) = parse_file_arg(arg);
+        if parsed.is_some() {
+            target = parsed;
+        }
+        match state.open_file(path) {
+            Ok(_) => {
+                if let Some((line, col)) = target {
+                    goto_target(&mut state, line, col);
+                }
+            }
+            Err(e) => {
+                state.message = Some(format!("Error opening file: {}", e));
+            }
+        }
+        break;
     }
 
     if args.iter().any(|a| a == "--gui") {
@@ -31,3 +132,64 @@ fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_arg_plain_path() {
+        let (path, target) = parse_file_arg("src/main.rs");
+        assert_eq!(path, PathBuf::from("src/main.rs"));
+        assert_eq!(target, None);
+    }
+
+    #[test]
+    fn test_parse_file_arg_line_and_column() {
+        let (path, target) = parse_file_arg("no-such-file.rs:12:5");
+        assert_eq!(path, PathBuf::from("no-such-file.rs"));
+        assert_eq!(target, Some((12, 5)));
+
+        let (path, target) = parse_file_arg("no-such-file.rs:12");
+        assert_eq!(path, PathBuf::from("no-such-file.rs"));
+        assert_eq!(target, Some((12, 1)));
+    }
+
+    #[test]
+    fn test_parse_file_arg_keeps_non_numeric_suffix() {
+        let (path, target) = parse_file_arg("no-such:file.rs");
+        assert_eq!(path, PathBuf::from("no-such:file.rs"));
+        assert_eq!(target, None);
+    }
+
+    #[test]
+    fn test_load_stdin_buffer_has_no_file_path() {
+        let mut state = EditorState::new();
+        load_stdin_buffer(&mut state, "piped input\n");
+
+        let buffer = state.current_buffer().unwrap();
+        assert_eq!(buffer.name, "*stdin*");
+        assert_eq!(buffer.file_path, None);
+        assert_eq!(buffer.text.to_string(), "piped input\n");
+    }
+
+    #[test]
+    fn test_goto_target_moves_point_and_clamps() {
+        use enacs::core::position::CharOffset;
+
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", "one\ntwo\nthree\n");
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+
+        goto_target(&mut state, 2, 2);
+        let cursor = &state.windows.current().unwrap().cursors.primary;
+        assert_eq!(cursor.position, CharOffset(5));
+
+        // A column past the end of the line clamps to it
+        goto_target(&mut state, 2, 100);
+        let cursor = &state.windows.current().unwrap().cursors.primary;
+        assert_eq!(cursor.position, CharOffset(7));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::commands::Command;
+use crate::core::Buffer;
+use crate::keybinding::KeyMap;
+
+use super::EditorState;
+
+/// Configures an [`EditorState`] for embedding the editor as a library.
+///
+/// [`EditorState::new()`] remains the default-configured shortcut; the
+/// builder only overrides what it is given:
+///
+/// - a supplied keymap replaces the default bindings wholesale and is
+///   owned by the state,
+/// - extra commands are registered on top of the default registry
+///   (replacing any default with the same name),
+/// - preloaded buffers are added after the `*scratch*` buffer and the
+///   first one becomes current,
+/// - unset dimensions and everything else keep their
+///   [`EditorState::new()`] defaults.
+#[derive(Default)]
+pub struct EditorBuilder {
+    keymap: Option<KeyMap>,
+    commands: Vec<Command>,
+    buffers: Vec<Buffer>,
+    dimensions: Option<(u16, u16)>,
+}
+
+impl EditorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the default keymap entirely.
+    pub fn keymap(mut self, keymap: KeyMap) -> Self {
+        self.keymap = Some(keymap);
+        self
+    }
+
+    /// Registers `command` in addition to the defaults.
+    pub fn register_command(mut self, command: Command) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    /// Preloads `buffer`; the first preloaded buffer is displayed
+    /// instead of `*scratch*`.
+    pub fn buffer(mut self, buffer: Buffer) -> Self {
+        self.buffers.push(buffer);
+        self
+    }
+
+    /// Sets the initial window dimensions, as a frontend would after
+    /// measuring its surface.
+    pub fn dimensions(mut self, width: u16, height: u16) -> Self {
+        self.dimensions = Some((width, height));
+        self
+    }
+
+    pub fn build(self) -> EditorState {
+        let mut state = EditorState::new();
+
+        if let Some(keymap) = self.keymap {
+            state.keymap = keymap;
+        }
+        for command in self.commands {
+            state.command_registry.register(command);
+        }
+
+        let mut first = None;
+        for buffer in self.buffers {
+            let id = state.buffers.add(buffer);
+            first.get_or_insert(id);
+        }
+        if let Some(id) = first {
+            state.buffers.set_current(id);
+            state.windows.set_current_buffer(id);
+        }
+
+        if let Some((width, height)) = self.dimensions {
+            state.set_dimensions(width, height);
+        }
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::registry::CommandContext;
+    use crate::keybinding::KeyEvent;
+
+    #[test]
+    fn test_builder_defaults_match_new() {
+        let state = EditorBuilder::new().build();
+        assert_eq!(state.current_buffer().unwrap().name, "*scratch*");
+        assert!(state.command_registry.get("find-file").is_some());
+    }
+
+    #[test]
+    fn test_builder_preloads_buffers_and_shows_the_first() {
+        let state = EditorBuilder::new()
+            .buffer(Buffer::from_string("first", "one"))
+            .buffer(Buffer::from_string("second", "two"))
+            .build();
+
+        assert_eq!(state.current_buffer().unwrap().name, "first");
+        assert!(state.buffers.find_by_name("second").is_some());
+        assert!(state.buffers.find_by_name("*scratch*").is_some());
+    }
+
+    #[test]
+    fn test_builder_custom_keymap_and_command() {
+        fn fortytwo(
+            state: &mut EditorState,
+            _ctx: &CommandContext,
+        ) -> crate::commands::CommandResult {
+            state.message = Some("42".to_string());
+            Ok(())
+        }
+
+        let mut keymap = KeyMap::new();
+        keymap.bind_command(KeyEvent::ctrl('q'), "fortytwo");
+
+        let mut state = EditorBuilder::new()
+            .keymap(keymap)
+            .register_command(Command::new("fortytwo", fortytwo))
+            .dimensions(100, 40)
+            .build();
+
+        state.handle_key(KeyEvent::ctrl('q'));
+        assert_eq!(state.message.as_deref(), Some("42"));
+        // The default bindings are gone along with the default keymap
+        assert!(state.keymap.get(&KeyEvent::ctrl('f')).is_none());
+    }
+}
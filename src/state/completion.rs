@@ -0,0 +1,114 @@
+//! Fuzzy subsequence matching for minibuffer completion.
+//!
+//! Used as a fallback when the user's input isn't a straight prefix of
+//! any candidate: every input character must appear in order in the
+//! candidate, and matches at word boundaries (`find-file` for `ff`)
+//! score higher than matches buried mid-word.
+
+/// Score bonus for matching the first character of a word.
+const BOUNDARY_BONUS: i32 = 3;
+/// Score bonus for extending a run of consecutive matches.
+const CONSECUTIVE_BONUS: i32 = 2;
+
+/// True when `c` starts a word within a candidate: the very first
+/// character, or one following a common separator.
+fn is_word_boundary(prev: Option<char>, _c: char) -> bool {
+    match prev {
+        None => true,
+        Some(p) => matches!(p, '-' | '_' | '/' | '.' | ' '),
+    }
+}
+
+/// Greedy left-to-right subsequence score of `input` against
+/// `candidate`, or `None` when `input` is not a subsequence.
+fn subsequence_score(candidate: &str, input: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut chars = candidate.chars();
+    let mut prev: Option<char> = None;
+    let mut last_matched = false;
+
+    for want in input.chars() {
+        let mut found = false;
+        for c in chars.by_ref() {
+            if c.eq_ignore_ascii_case(&want) {
+                score += 1;
+                if is_word_boundary(prev, c) {
+                    score += BOUNDARY_BONUS;
+                }
+                if last_matched {
+                    score += CONSECUTIVE_BONUS;
+                }
+                last_matched = true;
+                prev = Some(c);
+                found = true;
+                break;
+            }
+            last_matched = false;
+            prev = Some(c);
+        }
+        if !found {
+            return None;
+        }
+    }
+
+    Some(score)
+}
+
+/// Ranks `candidates` against `input`, best first. Candidates that
+/// don't contain `input` as a subsequence are dropped; ties fall back
+/// to shorter-candidate-first, then lexicographic order.
+pub fn fuzzy_rank<'a>(candidates: &'a [String], input: &str) -> Vec<(i32, &'a str)> {
+    let mut ranked: Vec<(i32, &str)> = candidates
+        .iter()
+        .filter_map(|c| subsequence_score(c, input).map(|score| (score, c.as_str())))
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(a.1.len().cmp(&b.1.len()))
+            .then(a.1.cmp(b.1))
+    });
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_rank_matches_word_boundaries() {
+        let cands = candidates(&["find-file-read-buffer", "forward-char", "buffer-list"]);
+        let ranked = fuzzy_rank(&cands, "ffrb");
+
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].1, "find-file-read-buffer");
+    }
+
+    #[test]
+    fn test_fuzzy_rank_prefers_boundary_matches() {
+        let cands = candidates(&["offset-frob", "find-file"]);
+        let ranked = fuzzy_rank(&cands, "ff");
+
+        // `find-file` hits two word starts; `offset-frob` buries one `f`.
+        assert_eq!(ranked[0].1, "find-file");
+    }
+
+    #[test]
+    fn test_fuzzy_rank_ties_go_to_shorter_candidate() {
+        let cands = candidates(&["abcd-x", "abc-x"]);
+        let ranked = fuzzy_rank(&cands, "ax");
+
+        assert_eq!(ranked[0].1, "abc-x");
+        assert_eq!(ranked[1].1, "abcd-x");
+    }
+
+    #[test]
+    fn test_non_subsequence_is_dropped() {
+        let cands = candidates(&["forward-char"]);
+        assert!(fuzzy_rank(&cands, "xyz").is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::commands::registry::{
@@ -9,8 +10,75 @@ use crate::keybinding::{KeyEvent, KeyMap, KeyResolution, KeyResolver};
 
 use super::buffer_mgr::BufferManager;
 use super::minibuffer::Minibuffer;
+use super::registers::Register;
 use super::window_mgr::{Window, WindowManager};
 
+/// Live theme preview started by `list-themes`: remembers what to
+/// restore when the preview is cancelled.
+pub struct ThemePreview {
+    pub saved_theme: String,
+    pub return_buffer: Option<BufferId>,
+}
+
+/// A numeric argument being accumulated (C-u, digits, minus) before
+/// the command it applies to arrives.
+pub struct PrefixPending {
+    /// Number of C-u presses; each multiplies the argument by 4.
+    pub universal: u32,
+    pub digits: Option<i32>,
+    pub negative: bool,
+    /// Keys typed so far, for the echo area.
+    pub keys: Vec<String>,
+}
+
+impl PrefixPending {
+    pub fn universal() -> Self {
+        Self {
+            universal: 1,
+            digits: None,
+            negative: false,
+            keys: vec!["C-u".to_string()],
+        }
+    }
+
+    pub fn negative() -> Self {
+        Self {
+            universal: 0,
+            digits: None,
+            negative: true,
+            keys: vec!["C--".to_string()],
+        }
+    }
+
+    pub fn digit(d: i32, key: &KeyEvent) -> Self {
+        Self {
+            universal: 0,
+            digits: Some(d),
+            negative: false,
+            keys: vec![key.to_string()],
+        }
+    }
+
+    pub fn push_digit(&mut self, d: i32, key: &KeyEvent) {
+        self.digits = Some(self.digits.unwrap_or(0).saturating_mul(10).saturating_add(d));
+        self.keys.push(key.to_string());
+    }
+
+    pub fn echo(&self) -> String {
+        format!("{}-", self.keys.join(" "))
+    }
+
+    pub fn to_prefix_arg(&self) -> PrefixArg {
+        if let Some(n) = self.digits {
+            PrefixArg::Raw(if self.negative { -n } else { n })
+        } else if self.negative {
+            PrefixArg::Negative
+        } else {
+            PrefixArg::Universal(4_i32.saturating_pow(self.universal))
+        }
+    }
+}
+
 pub struct EditorState {
     pub buffers: BufferManager,
     pub windows: WindowManager,
@@ -23,6 +91,90 @@ pub struct EditorState {
     pub prefix_arg: PrefixArg,
     pub should_quit: bool,
     pub pending_exit: bool,
+    pub registers: HashMap<char, Register>,
+    pub pending_char_capture: Option<&'static str>,
+    /// Keys collected so far by `describe-key`; the next full sequence
+    /// is reported instead of executed while this is set.
+    pub describing_key: Option<Vec<KeyEvent>>,
+    /// Key sequence parsed by `global-set-key` while it prompts for the
+    /// command name.
+    pub pending_key_binding: Option<Vec<KeyEvent>>,
+    /// Pattern read by `replace-regexp` while it prompts for the
+    /// replacement.
+    pub pending_replace: Option<String>,
+    /// When the current prefix key was pressed; after a short delay the
+    /// frontends show a which-key listing of its continuations.
+    pub prefix_key_since: Option<std::time::Instant>,
+    pub macro_keys: Vec<KeyEvent>,
+    pub recording_macro: bool,
+    pub executing_macro: bool,
+    /// Keys recorded since the last completed command; lets the macro
+    /// commands strip their own invoking key sequence from a recording.
+    pub macro_pending_keys: usize,
+    pub active_theme: String,
+    /// Themes loaded from files by `load-theme`, keyed by name.
+    pub custom_themes: HashMap<String, crate::commands::theme_cmds::ThemeSpec>,
+    pub theme_preview: Option<ThemePreview>,
+    /// Font requested by `set-font`; the GUI applies it on its next
+    /// frame, the terminal ignores it.
+    pub font: Option<crate::commands::theme_cmds::FontSpec>,
+    /// Window arrangements saved before each layout change, oldest
+    /// first, for winner-undo.
+    pub window_configs: Vec<super::window_mgr::WindowConfiguration>,
+    /// Position in `window_configs` while stepping with winner-undo and
+    /// winner-redo; `None` outside a cycle.
+    pub window_config_index: Option<usize>,
+    /// When on, windows showing the current buffer scroll as one
+    /// continuous view (follow-mode).
+    pub follow_mode: bool,
+    /// Base for column numbers in the modeline and column commands:
+    /// 1 (the default) or 0.
+    pub column_number_base: usize,
+    pub prefix_pending: Option<PrefixPending>,
+    /// When true, indentation commands keep literal tabs.
+    pub indent_tabs_mode: bool,
+    /// Columns per tab stop, used by tabify/untabify.
+    pub tab_width: usize,
+    /// When true, typing an opening bracket or quote also inserts the
+    /// matching close and leaves point between the pair.
+    pub electric_pair: bool,
+    /// Column `fill-paragraph` wraps at.
+    pub fill_column: usize,
+    /// Edits between auto-saves of a file-visiting buffer; 0 disables
+    /// auto-saving.
+    pub auto_save_threshold: usize,
+    /// When true, the line holding the primary cursor gets a faint
+    /// full-width background.
+    pub hl_line: bool,
+    /// When true, brackets are colored by nesting depth, cycling the
+    /// rainbow palette.
+    pub rainbow_delimiters: bool,
+    /// When true, the terminal renderer soft-wraps long lines onto
+    /// continuation rows and `next-line`/`previous-line` move by screen
+    /// rows of the wrapped text.
+    pub visual_line_mode: bool,
+    /// Active markdown preview, if `markdown-toggle-preview` is on.
+    pub markdown_preview: Option<crate::commands::markdown::MarkdownPreview>,
+    /// Headings listing shown by `M-x outline`, while its buffer is up.
+    pub outline: Option<crate::commands::outline::OutlineState>,
+    /// Kill-ring listing shown by `browse-kill-ring`, while its buffer
+    /// is up.
+    pub kill_ring_browse: Option<crate::commands::kill_yank::KillRingBrowse>,
+    /// The running or finished `project-grep`, with its result matches.
+    pub grep: Option<crate::commands::grep::GrepState>,
+    /// Mark positions recorded across buffers; `pop-global-mark` cycles
+    /// through them to retrace cross-buffer jumps.
+    pub global_mark_ring: std::collections::VecDeque<(BufferId, crate::core::mark::Mark)>,
+    /// The prefix argument the last command ran with, so `repeat` can
+    /// re-run it unchanged.
+    pub last_prefix_arg: PrefixArg,
+    /// Set after `repeat` runs; a solitary `z` then repeats again.
+    pub repeat_active: bool,
+    pub last_search: Option<String>,
+    /// Set when the last non-interactive search found no match, so
+    /// keyboard macros can branch on failure.
+    pub search_failed: bool,
+    pub whitespace_cleanup: crate::commands::whitespace::CleanupOptions,
 }
 
 impl Default for EditorState {
@@ -51,6 +203,43 @@ impl EditorState {
             prefix_arg: PrefixArg::None,
             should_quit: false,
             pending_exit: false,
+            registers: HashMap::new(),
+            pending_char_capture: None,
+            describing_key: None,
+            pending_key_binding: None,
+            pending_replace: None,
+            prefix_key_since: None,
+            macro_keys: Vec::new(),
+            recording_macro: false,
+            executing_macro: false,
+            macro_pending_keys: 0,
+            active_theme: "modus-operandi".to_string(),
+            custom_themes: HashMap::new(),
+            theme_preview: None,
+            font: None,
+            window_configs: Vec::new(),
+            window_config_index: None,
+            follow_mode: false,
+            column_number_base: 1,
+            prefix_pending: None,
+            indent_tabs_mode: false,
+            tab_width: 4,
+            electric_pair: false,
+            fill_column: 70,
+            auto_save_threshold: 300,
+            hl_line: false,
+            rainbow_delimiters: false,
+            visual_line_mode: false,
+            markdown_preview: None,
+            outline: None,
+            kill_ring_browse: None,
+            grep: None,
+            global_mark_ring: std::collections::VecDeque::new(),
+            last_prefix_arg: PrefixArg::None,
+            repeat_active: false,
+            last_search: None,
+            search_failed: false,
+            whitespace_cleanup: crate::commands::whitespace::CleanupOptions::default(),
         }
     }
 
@@ -90,12 +279,118 @@ impl EditorState {
         }
 
         let buffer = Buffer::from_file(path)?;
+        if buffer.mixed_line_endings {
+            self.message = Some(format!(
+                "{} mixes LF and CRLF line endings; normalized to LF",
+                buffer.name
+            ));
+        }
+        if buffer.large_file {
+            self.message = Some(format!("{}: large file; editing disabled", buffer.name));
+        }
+        if buffer.has_newer_auto_save() {
+            self.message = Some(format!(
+                "{} has a newer auto-save file; M-x recover-file to restore it",
+                buffer.name
+            ));
+        }
         let id = self.buffers.add(buffer);
         self.buffers.set_current(id);
         self.windows.set_current_buffer(id);
         Ok(id)
     }
 
+    /// Auto-saves any file-visiting buffer that has accumulated enough
+    /// unsaved edits. Called from the frontend run loops on their idle
+    /// tick.
+    pub fn auto_save_tick(&mut self) {
+        if self.auto_save_threshold == 0 {
+            return;
+        }
+        for buffer in self.buffers.iter_mut() {
+            if buffer.modified
+                && buffer.file_path.is_some()
+                && buffer.change_count >= self.auto_save_threshold
+            {
+                let _ = buffer.auto_save();
+            }
+        }
+    }
+
+    /// Loads `~/.enacs` if present, applying its directives. Errors are
+    /// reported in the echo area rather than aborting startup.
+    pub fn load_init_file(&mut self) {
+        let Some(home) = std::env::var_os("HOME") else {
+            return;
+        };
+        let path = std::path::Path::new(&home).join(".enacs");
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            self.apply_init_file(&contents);
+        }
+    }
+
+    fn history_file_path() -> Option<std::path::PathBuf> {
+        let home = std::env::var_os("HOME")?;
+        Some(std::path::Path::new(&home).join(".enacs_history"))
+    }
+
+    /// Loads `~/.enacs_history` if present, restoring the per-prompt
+    /// minibuffer history of earlier sessions.
+    pub fn load_history_file(&mut self) {
+        let Some(path) = Self::history_file_path() else {
+            return;
+        };
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            self.minibuffer.load_history(&contents);
+        }
+    }
+
+    /// Writes the minibuffer history back to `~/.enacs_history`, called
+    /// by the frontends on exit. Errors are ignored; losing history is
+    /// not worth blocking quit over.
+    pub fn save_history_file(&self) {
+        let Some(path) = Self::history_file_path() else {
+            return;
+        };
+        let _ = std::fs::write(path, self.minibuffer.serialize_history());
+    }
+
+    /// Applies init-file directives, one per line. Blank lines and `#`
+    /// comments are skipped; `bind KEYS COMMAND` rebinds the keymap.
+    pub fn apply_init_file(&mut self, contents: &str) {
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Err(e) = self.apply_init_line(line) {
+                self.message = Some(format!(".enacs:{}: {}", idx + 1, e));
+            }
+        }
+    }
+
+    fn apply_init_line(&mut self, line: &str) -> Result<(), String> {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("bind") => {
+                let rest: Vec<&str> = words.collect();
+                let Some((command, keys)) = rest.split_last() else {
+                    return Err("bind needs a key sequence and a command".to_string());
+                };
+                let keys = crate::keybinding::parse::parse_key_sequence(&keys.join(" "))?;
+                let name = self
+                    .command_registry
+                    .get(command)
+                    .map(|c| c.name)
+                    .ok_or_else(|| format!("Unknown command: {}", command))?;
+                self.keymap.bind_sequence(&keys, name);
+                Ok(())
+            }
+            Some(directive) => Err(format!("Unknown directive: {}", directive)),
+            None => Ok(()),
+        }
+    }
+
     pub fn switch_buffer(&mut self, name: &str) {
         if let Some(id) = self.buffers.find_by_name(name) {
             self.buffers.set_current(id);
@@ -109,6 +404,11 @@ impl EditorState {
     }
 
     pub fn handle_key(&mut self, key: KeyEvent) {
+        if self.recording_macro && !self.executing_macro {
+            self.macro_keys.push(key);
+            self.macro_pending_keys += 1;
+        }
+
         if self.pending_exit {
             self.handle_exit_confirmation(key);
             return;
@@ -119,22 +419,67 @@ impl EditorState {
             return;
         }
 
+        if let Some(callback) = self.pending_char_capture.take() {
+            self.handle_char_capture(callback, key);
+            return;
+        }
+
+        if self.theme_preview.is_some() && crate::commands::theme_cmds::handle_preview_key(self, key)
+        {
+            return;
+        }
+
+        if self.outline.is_some() && crate::commands::outline::handle_outline_key(self, key) {
+            return;
+        }
+
+        if self.kill_ring_browse.is_some()
+            && crate::commands::kill_yank::handle_kill_ring_browse_key(self, key)
+        {
+            return;
+        }
+
+        if self.describing_key.is_some() {
+            self.handle_describe_key(key);
+            return;
+        }
+
+        // After `repeat`, a solitary `z` repeats again (C-x z z z…);
+        // any other key ends the mini-mode and dispatches normally.
+        if self.repeat_active {
+            self.repeat_active = false;
+            if key == KeyEvent::char('z') {
+                self.execute_command("repeat");
+                self.macro_pending_keys = 0;
+                return;
+            }
+        }
+
+        if self.handle_prefix_key(key) {
+            return;
+        }
+
         self.message = None;
 
         let resolution = self.key_resolver.resolve(key, &self.keymap);
 
+        self.prefix_key_since = None;
+
         match resolution {
             KeyResolution::Complete(command_name) => {
                 self.execute_command(command_name);
+                self.macro_pending_keys = 0;
             }
             KeyResolution::Prefix(display) => {
                 self.message = Some(display);
+                self.prefix_key_since = Some(std::time::Instant::now());
             }
             KeyResolution::SelfInsert(c) => {
                 if let Err(e) = crate::commands::editing::self_insert(self, c) {
                     self.message = Some(format!("{}", e));
                 }
                 self.post_command("self-insert-command");
+                self.macro_pending_keys = 0;
             }
             KeyResolution::Unbound(keys) => {
                 let key_str: String = keys
@@ -143,15 +488,47 @@ impl EditorState {
                     .collect::<Vec<_>>()
                     .join(" ");
                 self.message = Some(format!("{} is undefined", key_str));
+                self.macro_pending_keys = 0;
+            }
+        }
+    }
+
+    /// Routes a key through the resolver in describe mode: the sequence
+    /// is reported rather than executed.
+    fn handle_describe_key(&mut self, key: KeyEvent) {
+        let mut keys = self.describing_key.take().unwrap_or_default();
+        keys.push(key);
+        let key_str = keys
+            .iter()
+            .map(|k| k.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        match self.key_resolver.resolve(key, &self.keymap) {
+            KeyResolution::Complete(command_name) => {
+                self.message = Some(format!("{} runs the command {}", key_str, command_name));
+            }
+            KeyResolution::Prefix(_) => {
+                self.message = Some(format!("Describe key: {}-", key_str));
+                self.describing_key = Some(keys);
+            }
+            KeyResolution::SelfInsert(_) => {
+                self.message = Some(format!("{} runs the command self-insert-command", key_str));
+            }
+            KeyResolution::Unbound(_) => {
+                self.message = Some(format!("{} is undefined", key_str));
             }
         }
     }
 
-    fn execute_command(&mut self, name: &'static str) {
+    pub(crate) fn execute_command(&mut self, name: &'static str) {
         let ctx = CommandContext {
             prefix_arg: std::mem::take(&mut self.prefix_arg),
             last_command: self.last_command,
         };
+        if name != "repeat" {
+            self.last_prefix_arg = ctx.prefix_arg;
+        }
 
         let result = if let Some(cmd) = self.command_registry.get(name) {
             let exec = cmd.execute;
@@ -172,6 +549,12 @@ impl EditorState {
     }
 
     fn post_command(&mut self, command_name: &'static str) {
+        // `repeat` is transparent: the repeated command already ran its
+        // own post-command pass, and last-command must keep naming it.
+        if command_name == "repeat" {
+            return;
+        }
+
         let (is_kill, preserves_mark, breaks_undo) = self
             .command_registry
             .get(command_name)
@@ -189,6 +572,7 @@ impl EditorState {
         if !preserves_mark {
             if let Some(window) = self.current_window_mut() {
                 window.cursors.deactivate_all_marks();
+                window.rectangle_mark = false;
             }
         }
 
@@ -200,13 +584,91 @@ impl EditorState {
             }
         }
 
+        self.clamp_point_to_narrow();
         self.ensure_cursor_visible();
+
+        if self.follow_mode {
+            self.windows.sync_follow();
+        }
+
+        if self.theme_preview.is_some() {
+            crate::commands::theme_cmds::sync_preview(self);
+        }
+
+        if self.markdown_preview.is_some() {
+            crate::commands::markdown::sync_preview(self);
+        }
+    }
+
+    /// Saves the current window arrangement before a layout change so
+    /// winner-undo can bring it back. Oldest entries fall off once the
+    /// ring is full.
+    pub fn record_window_config(&mut self) {
+        const WINNER_RING_MAX: usize = 32;
+
+        self.window_config_index = None;
+        self.window_configs.push(self.windows.save_configuration());
+        if self.window_configs.len() > WINNER_RING_MAX {
+            self.window_configs.remove(0);
+        }
+    }
+
+    /// Title for the frontend window or terminal tab: the current
+    /// buffer's name, starred when it has unsaved changes. Frontends
+    /// poll this each loop and push changes to the display server.
+    pub fn frame_title(&self) -> String {
+        match self.current_buffer() {
+            Some(buffer) if buffer.modified => format!("{}* — Enacs", buffer.name),
+            Some(buffer) => format!("{} — Enacs", buffer.name),
+            None => "Enacs".to_string(),
+        }
+    }
+
+    /// After a short delay on a pending prefix key, swaps the echo area
+    /// over to a which-key listing of the available continuations.
+    /// Called from the frontend run loops on their idle tick.
+    pub fn which_key_tick(&mut self) {
+        const WHICH_KEY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+        let Some(since) = self.prefix_key_since else {
+            return;
+        };
+        if since.elapsed() < WHICH_KEY_DELAY || !self.key_resolver.is_pending() {
+            return;
+        }
+
+        let options = self.key_resolver.pending_continuations(&self.keymap);
+        if !options.is_empty() {
+            self.message = Some(format!(
+                "{} {}",
+                self.key_resolver.pending_display(),
+                options.join("  ")
+            ));
+        }
+    }
+
+    /// Pulls every cursor in the current window back inside the
+    /// buffer's narrowed range, so motion and editing cannot escape it.
+    fn clamp_point_to_narrow(&mut self) {
+        let bounds = self
+            .current_window()
+            .and_then(|w| self.buffers.get(w.buffer_id))
+            .and_then(|b| b.narrow);
+        let Some((start, end)) = bounds else {
+            return;
+        };
+
+        if let Some(window) = self.current_window_mut() {
+            for cursor in window.cursors.all_cursors_mut() {
+                cursor.position = cursor.position.clamp(start, end);
+            }
+        }
     }
 
     fn ensure_cursor_visible(&mut self) {
         use crate::core::rope_ext::RopeExt;
 
-        let (cursor_line, window_height) = {
+        let (cursor_line, cursor_col, window_height, text_width) = {
             let window = match self.current_window() {
                 Some(w) => w,
                 None => return,
@@ -215,12 +677,17 @@ impl EditorState {
                 Some(b) => b,
                 None => return,
             };
-            let line = buffer
+            let pos = buffer
                 .text
-                .char_to_position(window.cursors.primary.position)
-                .line;
-            (line, window.height as usize)
+                .char_to_position(window.cursors.primary.position);
+            (
+                pos.line,
+                pos.column,
+                window.height as usize,
+                window.wrap_width(buffer.text.total_lines()),
+            )
         };
+        let visual_line_mode = self.visual_line_mode;
 
         if let Some(window) = self.current_window_mut() {
             let visible_start = window.scroll_line;
@@ -231,27 +698,55 @@ impl EditorState {
             } else if cursor_line >= visible_end {
                 window.scroll_line = cursor_line.saturating_sub(window_height.saturating_sub(2));
             }
+
+            // Horizontal follow for unwrapped lines. Once scrolled, the
+            // leftmost cell shows the `$` truncation marker, so keep one
+            // column of slack between the cursor and each edge.
+            if visual_line_mode {
+                window.scroll_column = 0;
+            } else if cursor_col < window.scroll_column + usize::from(window.scroll_column > 0) {
+                window.scroll_column = cursor_col.saturating_sub(1);
+            } else if cursor_col >= window.scroll_column + text_width {
+                window.scroll_column = (cursor_col + 2).saturating_sub(text_width);
+            }
         }
     }
 
     fn handle_minibuffer_key(&mut self, key: KeyEvent) {
         use crate::keybinding::key::{Key, Modifiers};
 
+        // Any key other than TAB dismisses a pending candidate listing.
+        self.minibuffer.completion_hint = None;
+
         match (key.key, key.modifiers) {
             (Key::Enter, Modifiers::NONE) => {
+                // RET accepts the highlighted candidate over the typed
+                // text when a selection is active.
+                if let Some(picked) = self
+                    .minibuffer
+                    .selected_candidate
+                    .and_then(|i| self.minibuffer.candidates.get(i).cloned())
+                {
+                    self.minibuffer.content = picked;
+                }
                 if let Some((content, callback)) = self.minibuffer.submit() {
                     self.handle_minibuffer_callback(callback, content);
                 }
             }
+            (Key::Tab, Modifiers::NONE) => {
+                self.minibuffer_complete();
+            }
             (Key::Char('g'), Modifiers::CTRL) | (Key::Escape, _) => {
                 self.minibuffer.clear();
                 self.message = Some("Quit".to_string());
             }
             (Key::Backspace, Modifiers::NONE) => {
                 self.minibuffer.delete_backward();
+                self.refresh_completion_hint();
             }
             (Key::Delete, Modifiers::NONE) | (Key::Char('d'), Modifiers::CTRL) => {
                 self.minibuffer.delete_forward();
+                self.refresh_completion_hint();
             }
             (Key::Char('f'), Modifiers::CTRL) | (Key::Right, Modifiers::NONE) => {
                 self.minibuffer.move_forward();
@@ -266,21 +761,181 @@ impl EditorState {
                 self.minibuffer.move_to_end();
             }
             (Key::Char('p'), Modifiers::CTRL) | (Key::Up, Modifiers::NONE) => {
-                self.minibuffer.history_prev();
+                if self.minibuffer.candidates.is_empty() {
+                    self.minibuffer.history_prev();
+                } else {
+                    self.move_completion_selection(-1);
+                }
             }
             (Key::Char('n'), Modifiers::CTRL) | (Key::Down, Modifiers::NONE) => {
-                self.minibuffer.history_next();
+                if self.minibuffer.candidates.is_empty() {
+                    self.minibuffer.history_next();
+                } else {
+                    self.move_completion_selection(1);
+                }
             }
             (Key::Char(c), Modifiers::NONE) => {
                 self.minibuffer.insert_char(c);
+                self.refresh_completion_hint();
             }
             (Key::Char(c), Modifiers::SHIFT) => {
                 self.minibuffer.insert_char(c);
+                self.refresh_completion_hint();
             }
             _ => {}
         }
     }
 
+    /// Live narrowing: refilters the candidates after every edit so the
+    /// hint tracks the input without waiting for TAB. Large sets are
+    /// truncated to a handful of leading candidates.
+    fn refresh_completion_hint(&mut self) {
+        let complete = match self.minibuffer.completion_fn {
+            Some(f) => f,
+            None => return,
+        };
+
+        let input = self.minibuffer.content.clone();
+        let mut candidates = complete(self, &input);
+        candidates.sort();
+
+        if candidates.is_empty() && !input.is_empty() {
+            let all = complete(self, "");
+            candidates = super::completion::fuzzy_rank(&all, &input)
+                .into_iter()
+                .map(|(_, name)| name.to_string())
+                .collect();
+        }
+
+        self.minibuffer.candidates = candidates;
+        self.minibuffer.selected_candidate = None;
+        self.render_completion_hint();
+    }
+
+    /// Renders the candidate listing into the hint, bracketing the
+    /// selected pick and keeping it within the shown window.
+    fn render_completion_hint(&mut self) {
+        const MAX_SHOWN: usize = 8;
+
+        let candidates = &self.minibuffer.candidates;
+        if candidates.is_empty() {
+            self.minibuffer.completion_hint = None;
+            return;
+        }
+
+        let selected = self.minibuffer.selected_candidate;
+        let start = match selected {
+            Some(s) if s + 1 > MAX_SHOWN => s + 1 - MAX_SHOWN,
+            _ => 0,
+        };
+
+        let parts: Vec<String> = candidates
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(MAX_SHOWN)
+            .map(|(i, c)| {
+                if Some(i) == selected {
+                    format!("[{}]", c)
+                } else {
+                    c.clone()
+                }
+            })
+            .collect();
+
+        let mut listing = parts.join(" | ");
+        let shown_end = start + MAX_SHOWN;
+        if shown_end < candidates.len() {
+            listing.push_str(&format!(" | +{} more", candidates.len() - shown_end));
+        }
+        self.minibuffer.completion_hint = Some(listing);
+    }
+
+    /// Moves the completion selection by `delta`, wrapping around.
+    fn move_completion_selection(&mut self, delta: isize) {
+        let len = self.minibuffer.candidates.len();
+        if len == 0 {
+            return;
+        }
+        let next = match self.minibuffer.selected_candidate {
+            None => {
+                if delta > 0 {
+                    0
+                } else {
+                    len - 1
+                }
+            }
+            Some(i) => (i as isize + delta).rem_euclid(len as isize) as usize,
+        };
+        self.minibuffer.selected_candidate = Some(next);
+        self.render_completion_hint();
+    }
+
+    /// TAB in the minibuffer: fill the longest common prefix of the
+    /// candidates; on a second TAB with no progress, list them.
+    fn minibuffer_complete(&mut self) {
+        let complete = match self.minibuffer.completion_fn {
+            Some(f) => f,
+            None => return,
+        };
+
+        let input = self.minibuffer.content.clone();
+        let mut candidates = complete(self, &input);
+        candidates.sort();
+
+        // When nothing prefix-matches, fall back to fuzzy subsequence
+        // matching over the full candidate set.
+        if candidates.is_empty() && !input.is_empty() {
+            let all = complete(self, "");
+            candidates = super::completion::fuzzy_rank(&all, &input)
+                .into_iter()
+                .map(|(_, name)| name.to_string())
+                .collect();
+
+            if candidates.len() == 1 {
+                self.minibuffer.content = candidates.remove(0);
+                self.minibuffer.cursor_pos = self.minibuffer.content.len();
+                return;
+            } else if !candidates.is_empty() {
+                self.minibuffer.completion_hint = Some(candidates.join(" | "));
+                return;
+            }
+        }
+
+        if candidates.is_empty() {
+            self.minibuffer.completion_hint = Some("No completions".to_string());
+            return;
+        }
+
+        let prefix = super::minibuffer::longest_common_prefix(&candidates);
+        if prefix.len() > input.len() {
+            self.minibuffer.content = prefix;
+            self.minibuffer.cursor_pos = self.minibuffer.content.len();
+            if candidates.len() == 1 {
+                self.minibuffer.completion_hint = None;
+            }
+        } else if candidates.len() == 1 {
+            self.minibuffer.completion_hint = Some("Sole completion".to_string());
+        } else {
+            // Command candidates get their keybinding (if any) appended,
+            // found by reverse-searching the keymap.
+            let annotate = self.minibuffer.callback == Some("execute-extended-command");
+            let listing = candidates
+                .iter()
+                .map(|candidate| {
+                    match annotate.then(|| self.keymap.key_for_command(candidate)).flatten() {
+                        Some(key) => format!("{} ({})", candidate, key),
+                        None => candidate.clone(),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" | ");
+            self.minibuffer.completion_hint = Some(listing);
+            self.minibuffer.candidates = candidates;
+            self.minibuffer.selected_candidate = None;
+        }
+    }
+
     fn handle_minibuffer_callback(&mut self, callback: &str, content: String) {
         match callback {
             "find-file-complete" => {
@@ -307,6 +962,27 @@ impl EditorState {
                     }
                 }
             }
+            "insert-file-complete" => {
+                crate::commands::file_cmds::insert_file_at_point(self, &content);
+            }
+            "write-region-complete" => {
+                crate::commands::file_cmds::write_region_to(self, &content);
+            }
+            "revert-coding-complete" => {
+                crate::commands::file_cmds::revert_buffer_as(self, &content);
+            }
+            "shell-command-complete" => {
+                crate::commands::shell::run_command_into(self, &content, false);
+            }
+            "shell-command-insert-complete" => {
+                crate::commands::shell::run_command_into(self, &content, true);
+            }
+            "shell-on-region-complete" => {
+                crate::commands::shell::filter_region_through(self, &content, false);
+            }
+            "shell-on-region-replace-complete" => {
+                crate::commands::shell::filter_region_through(self, &content, true);
+            }
             "switch-to-buffer-complete" => {
                 self.switch_buffer(&content);
             }
@@ -326,6 +1002,135 @@ impl EditorState {
                     }
                 }
             }
+            "search-forward-complete"
+            | "search-backward-complete"
+            | "search-regexp-forward-complete"
+            | "search-regexp-backward-complete" => {
+                let forward = matches!(
+                    callback,
+                    "search-forward-complete" | "search-regexp-forward-complete"
+                );
+                let regexp = callback.starts_with("search-regexp-");
+                let query = if content.is_empty() {
+                    self.last_search.clone().unwrap_or_default()
+                } else {
+                    content
+                };
+                if query.is_empty() {
+                    self.message = Some("No previous search".to_string());
+                    return;
+                }
+                let pattern = match crate::commands::search::SearchPattern::parse(&query, regexp) {
+                    Ok(pattern) => pattern,
+                    Err(e) => {
+                        self.message = Some(e);
+                        return;
+                    }
+                };
+                self.last_search = Some(query.clone());
+
+                let target = self.current_window().and_then(|window| {
+                    let buffer = self.buffers.get(window.buffer_id)?;
+                    crate::commands::search::find_pattern_in_rope(
+                        &buffer.text,
+                        &pattern,
+                        window.cursors.primary.position,
+                        forward,
+                    )
+                });
+
+                match target {
+                    Some(pos) => {
+                        if let Some(window) = self.windows.current_mut() {
+                            window.cursors.primary.set_position(pos);
+                        }
+                        self.search_failed = false;
+                        self.ensure_cursor_visible();
+                    }
+                    None => {
+                        self.search_failed = true;
+                        self.message = Some(format!("Search failed: \"{}\"", query));
+                    }
+                }
+            }
+            "goto-line-complete" => match content.trim().parse::<usize>() {
+                Ok(line) => {
+                    crate::commands::motion::goto_line_number(self, line.saturating_sub(1));
+                    self.ensure_cursor_visible();
+                }
+                Err(_) => {
+                    self.message = Some("Invalid line number".to_string());
+                }
+            },
+            "wrap-region" => {
+                crate::commands::editing::wrap_region_with(self, &content);
+            }
+            "keep-lines" | "flush-lines" => {
+                let keep = callback == "keep-lines";
+                crate::commands::whitespace::filter_lines_with(self, &content, keep);
+            }
+            "replace-regexp-pattern" => {
+                if content.is_empty() {
+                    self.message = Some("Empty pattern".to_string());
+                } else {
+                    let prompt = format!("Replace regexp {} with: ", content.trim());
+                    self.pending_replace = Some(content);
+                    self.start_minibuffer_prompt(&prompt, "replace-regexp-with");
+                }
+            }
+            "replace-regexp-with" => {
+                if let Some(pattern) = self.pending_replace.take() {
+                    crate::commands::search::replace_regexp_from_point(self, &pattern, &content);
+                }
+            }
+            "global-set-key-sequence" => {
+                match crate::keybinding::parse::parse_key_sequence(&content) {
+                    Ok(keys) => {
+                        self.pending_key_binding = Some(keys);
+                        let prompt = format!("Bind {} to command: ", content.trim());
+                        self.start_minibuffer_prompt(&prompt, "global-set-key-command");
+                    }
+                    Err(e) => self.message = Some(e),
+                }
+            }
+            "global-set-key-command" => {
+                let name = self.command_registry.get(content.trim()).map(|c| c.name);
+                match (self.pending_key_binding.take(), name) {
+                    (Some(keys), Some(name)) => {
+                        self.keymap.bind_sequence(&keys, name);
+                        self.message = Some(format!("Bound to {}", name));
+                    }
+                    (_, None) => {
+                        self.message = Some(format!("No such command: {}", content.trim()));
+                    }
+                    (None, _) => {}
+                }
+            }
+            "project-grep" => {
+                crate::commands::grep::start_search(self, &content);
+            }
+            "yank-from-kill-ring" => {
+                crate::commands::kill_yank::apply_yank_from_kill_ring(self, &content);
+            }
+            "load-theme" => {
+                crate::commands::theme_cmds::apply_theme(self, &content);
+            }
+            "set-font" => {
+                crate::commands::theme_cmds::apply_font(self, &content);
+            }
+            "find-file-in-project" => match crate::commands::project::current_root(self) {
+                Some(root) => match self.open_file(root.join(&content)) {
+                    Ok(_) => {
+                        self.message = Some(format!("Opened {}", content));
+                    }
+                    Err(e) => {
+                        self.message = Some(format!("Error opening file: {}", e));
+                    }
+                },
+                None => {
+                    self.message = Some("No project root found".to_string());
+                }
+            },
             "execute-extended-command" => {
                 if self.command_registry.get(&content).is_some() {
                     let name: &'static str = Box::leak(content.into_boxed_str());
@@ -353,27 +1158,504 @@ impl EditorState {
         }
     }
 
+    /// Accumulates a numeric prefix argument. Returns true when the key
+    /// was consumed; otherwise any finished argument has been moved
+    /// into `prefix_arg` and the key should resolve normally.
+    fn handle_prefix_key(&mut self, key: KeyEvent) -> bool {
+        use crate::keybinding::key::{Key, Modifiers};
+
+        match &mut self.prefix_pending {
+            None => {
+                // M-<digit> and C-<digit> start a digit argument.
+                if let Key::Char(c) = key.key {
+                    if c.is_ascii_digit()
+                        && (key.modifiers == Modifiers::META || key.modifiers == Modifiers::CTRL)
+                    {
+                        let pending = PrefixPending::digit(c as i32 - '0' as i32, &key);
+                        self.message = Some(pending.echo());
+                        self.prefix_pending = Some(pending);
+                        return true;
+                    }
+                    // M-- starts a negative argument; C-- goes through
+                    // the keymap to `negative-argument`.
+                    if c == '-' && key.modifiers == Modifiers::META {
+                        let pending = PrefixPending::negative();
+                        self.message = Some(pending.echo());
+                        self.prefix_pending = Some(pending);
+                        return true;
+                    }
+                }
+                false
+            }
+            Some(pending) => {
+                match (key.key, key.modifiers) {
+                    (Key::Char('u'), Modifiers::CTRL) if pending.digits.is_none() => {
+                        pending.universal += 1;
+                        pending.keys.push("C-u".to_string());
+                        self.message = Some(pending.echo());
+                        true
+                    }
+                    (Key::Char(c), m)
+                        if c.is_ascii_digit()
+                            && (m == Modifiers::NONE
+                                || m == Modifiers::CTRL
+                                || m == Modifiers::META) =>
+                    {
+                        pending.push_digit(c as i32 - '0' as i32, &key);
+                        self.message = Some(pending.echo());
+                        true
+                    }
+                    (Key::Char('-'), m)
+                        if pending.digits.is_none()
+                            && !pending.negative
+                            && (m == Modifiers::NONE
+                                || m == Modifiers::CTRL
+                                || m == Modifiers::META) =>
+                    {
+                        pending.negative = true;
+                        pending.keys.push(key.to_string());
+                        self.message = Some(pending.echo());
+                        true
+                    }
+                    (Key::Char('g'), Modifiers::CTRL) => {
+                        self.prefix_pending = None;
+                        self.message = Some("Quit".to_string());
+                        true
+                    }
+                    _ => {
+                        // Argument complete: hand it to the command this
+                        // key resolves to.
+                        self.prefix_arg = pending.to_prefix_arg();
+                        self.prefix_pending = None;
+                        false
+                    }
+                }
+            }
+        }
+    }
+
     pub fn start_minibuffer_prompt(&mut self, prompt: &str, callback: &'static str) {
         self.minibuffer.start_prompt(prompt, callback);
+
+        // Wire up TAB completion for the prompts that have a natural
+        // candidate source.
+        self.minibuffer.completion_fn = match callback {
+            "find-file-complete"
+            | "write-file-complete"
+            | "insert-file-complete"
+            | "write-region-complete" => {
+                Some(super::minibuffer::complete_path as super::minibuffer::CompletionFn)
+            }
+            "switch-to-buffer-complete" | "kill-buffer-complete" => {
+                Some(super::minibuffer::complete_buffer_name as super::minibuffer::CompletionFn)
+            }
+            "execute-extended-command" | "global-set-key-command" => {
+                Some(super::minibuffer::complete_command as super::minibuffer::CompletionFn)
+            }
+            "find-file-in-project" => Some(
+                crate::commands::project::complete_project_file as super::minibuffer::CompletionFn,
+            ),
+            "load-theme" => Some(
+                crate::commands::theme_cmds::complete_theme as super::minibuffer::CompletionFn,
+            ),
+            "revert-coding-complete" => Some(
+                crate::commands::file_cmds::complete_coding_system
+                    as super::minibuffer::CompletionFn,
+            ),
+            "yank-from-kill-ring" => Some(
+                crate::commands::kill_yank::complete_kill_ring as super::minibuffer::CompletionFn,
+            ),
+            _ => None,
+        };
     }
 
-    pub fn set_dimensions(&mut self, width: u16, height: u16) {
-        self.windows.set_dimensions(width, height);
+    /// Arranges for the next key press to be delivered to `callback` as a
+    /// raw character instead of going through normal key resolution.
+    pub fn start_char_capture(&mut self, prompt: &str, callback: &'static str) {
+        self.message = Some(prompt.to_string());
+        self.pending_char_capture = Some(callback);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::keybinding::key::Key;
+    fn handle_char_capture(&mut self, callback: &'static str, key: KeyEvent) {
+        use crate::keybinding::key::{Key, Modifiers};
 
-    #[test]
-    fn test_editor_state_new() {
-        let state = EditorState::new();
+        // C-g or Escape cancels the capture.
+        if matches!(
+            (key.key, key.modifiers),
+            (Key::Char('g'), Modifiers::CTRL) | (Key::Escape, _)
+        ) {
+            self.message = Some("Quit".to_string());
+            return;
+        }
+
+        // quoted-insert accepts any key that names a character, control
+        // characters included, before the register-style filtering.
+        if callback == "quoted-insert" {
+            let c = match key.key {
+                Key::Char(c) if key.modifiers.contains(Modifiers::CTRL) => {
+                    let lower = c.to_ascii_lowercase();
+                    if lower.is_ascii_lowercase() {
+                        // C-a .. C-z are the control codes 1..26
+                        char::from(lower as u8 - b'a' + 1)
+                    } else {
+                        c
+                    }
+                }
+                Key::Char(c) => c,
+                Key::Tab => '\t',
+                Key::Enter => '\n',
+                _ => {
+                    self.message = Some(format!("{} does not name a character", key));
+                    return;
+                }
+            };
+            self.message = None;
+            if let Err(e) = crate::commands::editing::insert_literal_char(self, c) {
+                self.message = Some(e.to_string());
+            }
+            return;
+        }
+
+        let c = match key.key {
+            Key::Char(c) => c,
+            _ => {
+                self.message = Some(format!("{} is not a valid register", key));
+                return;
+            }
+        };
+
+        match callback {
+            "point-to-register" => {
+                let location = self
+                    .current_window()
+                    .map(|w| (w.buffer_id, w.cursors.primary.position));
+                if let Some((buffer_id, position)) = location {
+                    self.registers.insert(
+                        c,
+                        Register::Point {
+                            buffer_id,
+                            position,
+                        },
+                    );
+                    self.message = Some(format!("Point stored in register {}", c));
+                }
+            }
+            "jump-to-register" => match self.registers.get(&c) {
+                Some(&Register::Point {
+                    buffer_id,
+                    position,
+                }) => {
+                    if self.buffers.get(buffer_id).is_none() {
+                        self.message = Some("Register points to a deleted buffer".to_string());
+                        return;
+                    }
+                    self.buffers.set_current(buffer_id);
+                    self.windows.set_current_buffer(buffer_id);
+                    let max = self
+                        .buffers
+                        .get(buffer_id)
+                        .map(|b| b.len_chars())
+                        .unwrap_or(0);
+                    if let Some(window) = self.windows.current_mut() {
+                        window
+                            .cursors
+                            .primary
+                            .set_position(crate::core::CharOffset(position.0.min(max)));
+                    }
+                    self.ensure_cursor_visible();
+                }
+                Some(Register::Text(_)) => {
+                    self.message = Some("Register doesn't contain a buffer position".to_string());
+                }
+                None => {
+                    self.message = Some(format!("Register {} is empty", c));
+                }
+            },
+            "copy-to-register" => {
+                let copied = self.current_window().and_then(|window| {
+                    let buffer = self.buffers.get(window.buffer_id)?;
+                    let (start, end) = window.cursors.primary.region()?;
+                    Some(buffer.slice(start, end))
+                });
+                match copied {
+                    Some(text) => {
+                        self.registers.insert(c, Register::Text(text));
+                        if let Some(window) = self.windows.current_mut() {
+                            window.cursors.primary.deactivate_mark();
+                        }
+                        self.message = Some(format!("Copied to register {}", c));
+                    }
+                    None => {
+                        self.message = Some("The mark is not active now".to_string());
+                    }
+                }
+            }
+            "insert-register" | "insert-register-before" => match self.registers.get(&c) {
+                Some(Register::Text(text)) => {
+                    let text = text.clone();
+                    let buffer_id = match self.current_window() {
+                        Some(w) => w.buffer_id,
+                        None => return,
+                    };
+                    let start = self
+                        .windows
+                        .current()
+                        .map(|w| w.cursors.primary.position)
+                        .unwrap_or(crate::core::CharOffset(0));
+                    let cursors = &mut self.windows.current_mut().unwrap().cursors;
+                    if let Some(buffer) = self.buffers.get_mut(buffer_id) {
+                        buffer.insert_string(cursors, &text);
+                    }
+                    // With a prefix argument, leave point before the
+                    // inserted text and the mark after it.
+                    if callback == "insert-register-before" {
+                        if let Some(window) = self.windows.current_mut() {
+                            let end = window.cursors.primary.position;
+                            window.cursors.primary.mark = Some(end);
+                            window.cursors.primary.set_position(start);
+                        }
+                    }
+                    self.ensure_cursor_visible();
+                }
+                Some(Register::Point { .. }) => {
+                    self.message = Some("Register does not contain text".to_string());
+                }
+                None => {
+                    self.message = Some(format!("Register {} is empty", c));
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Line and column of the primary cursor for the modeline. Lines
+    /// are always 1-based; the column honors `column_number_base`.
+    pub fn modeline_position(&self) -> (usize, usize) {
+        match self.current_window() {
+            Some(window) => self.window_position(window),
+            None => (1, self.column_number_base),
+        }
+    }
+
+    /// Line and column of `window`'s primary cursor, for its modeline.
+    pub fn window_position(&self, window: &Window) -> (usize, usize) {
+        use crate::core::rope_ext::RopeExt;
+
+        match self.buffers.get(window.buffer_id) {
+            Some(buffer) => {
+                let pos = buffer.text.char_to_position(window.cursors.primary.position);
+                (pos.line + 1, pos.column + self.column_number_base)
+            }
+            None => (1, self.column_number_base),
+        }
+    }
+
+    pub fn set_dimensions(&mut self, width: u16, height: u16) {
+        self.windows.set_dimensions(width, height);
+    }
+
+    /// Registers `command` at runtime, replacing any existing command
+    /// with the same name. It is immediately available to
+    /// `execute-extended-command` and `global-set-key`.
+    pub fn register_command(&mut self, command: crate::commands::Command) {
+        self.command_registry.register(command);
+    }
+
+    /// Feeds `keys` through the normal key-resolution path, exactly as
+    /// a frontend would; for headless scripting and tests.
+    pub fn feed_keys(&mut self, keys: &[KeyEvent]) {
+        for &key in keys {
+            self.handle_key(key);
+        }
+    }
+
+    /// Parses a space-separated key spec like `"C-x C-s"` (the form
+    /// `describe-key` prints) and feeds it through [`Self::feed_keys`].
+    pub fn dispatch(&mut self, spec: &str) -> Result<(), String> {
+        for part in spec.split_whitespace() {
+            let key: KeyEvent = part.parse()?;
+            self.handle_key(key);
+        }
+        Ok(())
+    }
+
+    /// Runs the command named `name` directly, bypassing key
+    /// resolution; errors are reported in the echo area as usual.
+    pub fn run_command(&mut self, name: &str) {
+        // execute_command wants the registry's 'static name
+        let name_static = self
+            .command_registry
+            .names()
+            .find(|n| **n == name)
+            .copied();
+        match name_static {
+            Some(name) => self.execute_command(name),
+            None => self.message = Some(format!("Command not found: {}", name)),
+        }
+    }
+
+    /// The bracket at point (or the one just before it) together with
+    /// its match, when balanced; what show-paren highlighting renders.
+    /// `None` when point isn't at a bracket or the match is missing.
+    pub fn matching_bracket_pair(
+        &self,
+    ) -> Option<(
+        crate::core::position::CharOffset,
+        crate::core::position::CharOffset,
+    )> {
+        use crate::core::position::CharOffset;
+        use crate::core::rope_ext::{find_sexp_backward, find_sexp_forward, RopeExt};
+
+        let window = self.windows.current()?;
+        let buffer = self.buffers.get(window.buffer_id)?;
+        let point = window.cursors.primary.position;
+
+        if matches!(buffer.text.char_at(point), Some('(' | '[' | '{')) {
+            let end = find_sexp_forward(&buffer.text, point)?;
+            return Some((point, CharOffset(end.0 - 1)));
+        }
+        if point.0 > 0 && matches!(buffer.text.char_at(CharOffset(point.0 - 1)), Some(')' | ']' | '}')) {
+            let start = find_sexp_backward(&buffer.text, point)?;
+            return Some((start, CharOffset(point.0 - 1)));
+        }
+        None
+    }
+
+    /// Translates a terminal grid cell into the window under it and the
+    /// char offset of that cell, accounting for scroll, the gutter and
+    /// tab expansion. Modeline and minibuffer rows yield `None`.
+    pub fn mouse_to_position(
+        &self,
+        column: u16,
+        row: u16,
+    ) -> Option<(super::window_mgr::WindowId, crate::core::position::CharOffset)> {
+        use crate::core::position::CharOffset;
+        use crate::core::rope_ext::RopeExt;
+
+        const TAB_WIDTH: usize = 4;
+
+        let window = self.windows.iter().find(|w| {
+            column >= w.x
+                && column < w.x + w.width
+                && row >= w.y
+                && row < w.y + w.height.saturating_sub(1)
+        })?;
+        let buffer = self.buffers.get(window.buffer_id)?;
+
+        let gutter = if window.display_line_numbers.is_some() {
+            super::window_mgr::LineNumberStyle::gutter_width(buffer.text.total_lines()) as u16
+        } else {
+            0
+        };
+        let col = (column - window.x).checked_sub(gutter)? as usize;
+
+        let line_idx = window.scroll_line + (row - window.y) as usize;
+        if line_idx >= buffer.text.total_lines() {
+            return Some((window.id, CharOffset(buffer.text.total_chars())));
+        }
+
+        // Tab-aware inverse of visual-column expansion
+        let line_text: String = buffer.text.line(line_idx).chars().collect();
+        let trimmed = line_text.trim_end_matches('\n');
+        let mut visual = 0;
+        let mut char_col = trimmed.chars().count();
+        for (i, ch) in trimmed.chars().enumerate() {
+            if visual >= col {
+                char_col = i;
+                break;
+            }
+            visual += if ch == '\t' {
+                TAB_WIDTH - (visual % TAB_WIDTH)
+            } else {
+                unicode_width::UnicodeWidthChar::width(ch).unwrap_or(1)
+            };
+        }
+
+        let line_start = buffer.text.line_start_char(line_idx);
+        Some((window.id, CharOffset(line_start.0 + char_col)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keybinding::key::Key;
+
+    #[test]
+    fn test_editor_state_new() {
+        let state = EditorState::new();
         assert!(state.current_buffer().is_some());
         assert_eq!(state.current_buffer().unwrap().name, "*scratch*");
     }
 
+    #[test]
+    fn test_dispatch_drives_full_workflows() {
+        use crate::core::position::CharOffset;
+
+        // (key spec, resulting text, resulting point)
+        let table = [
+            ("h i", "hi", 2),
+            ("h i C-a x", "xhi", 1),
+            ("a b c C-b C-b <delete>", "ac", 1),
+            ("w C-a C-k y", "y", 1),
+        ];
+
+        for (spec, text, point) in table {
+            let mut state = EditorState::new();
+            state.dispatch(spec).unwrap();
+            let buffer = state.current_buffer().unwrap();
+            assert_eq!(buffer.text.to_string(), text, "keys: {}", spec);
+            assert_eq!(
+                state.windows.current().unwrap().cursors.primary.position,
+                CharOffset(point),
+                "keys: {}",
+                spec
+            );
+        }
+
+        let mut state = EditorState::new();
+        assert!(state.dispatch("C-x bogus-key").is_err());
+    }
+
+    #[test]
+    fn test_run_command_bypasses_key_resolution() {
+        let mut state = EditorState::new();
+        state.dispatch("h i").unwrap();
+        state.run_command("move-beginning-of-line");
+        assert_eq!(
+            state.windows.current().unwrap().cursors.primary.position,
+            crate::core::position::CharOffset(0)
+        );
+
+        state.run_command("no-such-command");
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Command not found: no-such-command")
+        );
+    }
+
+    #[test]
+    fn test_register_command_from_outside_the_commands_module() {
+        fn greet(
+            state: &mut EditorState,
+            _ctx: &CommandContext,
+        ) -> crate::commands::CommandResult {
+            state.message = Some("hello from outside".to_string());
+            Ok(())
+        }
+
+        let mut state = EditorState::new();
+        state.register_command(crate::commands::Command::new("greet", greet));
+
+        // Resolvable by M-x completion and bindable like any built-in
+        let candidates = super::super::minibuffer::complete_command(&state, "gree");
+        assert!(candidates.iter().any(|c| c == "greet"));
+
+        state.keymap.bind_command(KeyEvent::ctrl('q'), "greet");
+        state.handle_key(KeyEvent::ctrl('q'));
+        assert_eq!(state.message.as_deref(), Some("hello from outside"));
+    }
+
     #[test]
     fn test_self_insert() {
         let mut state = EditorState::new();
@@ -398,4 +1680,436 @@ mod tests {
             0
         );
     }
+
+    #[test]
+    fn test_universal_argument_repeats_command() {
+        let mut state = EditorState::new();
+        for _ in 0..20 {
+            state.handle_key(KeyEvent::char('x'));
+        }
+
+        state.handle_key(KeyEvent::ctrl('a'));
+        state.handle_key(KeyEvent::ctrl('u'));
+        state.handle_key(KeyEvent::ctrl('f'));
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position.0,
+            4
+        );
+
+        state.handle_key(KeyEvent::ctrl('a'));
+        state.handle_key(KeyEvent::ctrl('u'));
+        state.handle_key(KeyEvent::ctrl('u'));
+        state.handle_key(KeyEvent::ctrl('f'));
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position.0,
+            16
+        );
+    }
+
+    #[test]
+    fn test_digit_argument_accumulates() {
+        let mut state = EditorState::new();
+        for _ in 0..30 {
+            state.handle_key(KeyEvent::char('x'));
+        }
+        state.handle_key(KeyEvent::ctrl('a'));
+
+        state.handle_key(KeyEvent::meta('2'));
+        assert!(state.prefix_pending.is_some());
+        state.handle_key(KeyEvent::char('3'));
+        state.handle_key(KeyEvent::ctrl('f'));
+
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position.0,
+            23
+        );
+        assert!(state.prefix_pending.is_none());
+    }
+
+    #[test]
+    fn test_prefix_pending_to_prefix_arg() {
+        assert_eq!(
+            PrefixPending::universal().to_prefix_arg(),
+            PrefixArg::Universal(4)
+        );
+        assert_eq!(
+            PrefixPending::negative().to_prefix_arg(),
+            PrefixArg::Negative
+        );
+
+        let mut pending = PrefixPending::negative();
+        pending.push_digit(4, &KeyEvent::char('4'));
+        pending.push_digit(2, &KeyEvent::char('2'));
+        assert_eq!(pending.to_prefix_arg(), PrefixArg::Raw(-42));
+    }
+
+    #[test]
+    fn test_prefix_argument_cancel() {
+        let mut state = EditorState::new();
+        state.handle_key(KeyEvent::ctrl('u'));
+        state.handle_key(KeyEvent::ctrl('g'));
+        assert!(state.prefix_pending.is_none());
+
+        state.handle_key(KeyEvent::char('a'));
+        assert_eq!(state.current_buffer().unwrap().text.to_string(), "a");
+    }
+
+    #[test]
+    fn test_modeline_position_column_base() {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", "\tabc");
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = crate::core::CharOffset(1);
+
+        assert_eq!(state.modeline_position(), (1, 2));
+
+        state.column_number_base = 0;
+        assert_eq!(state.modeline_position(), (1, 1));
+    }
+
+    #[test]
+    fn test_minibuffer_tab_completes_buffer_names() {
+        use crate::keybinding::key::{Key, Modifiers};
+
+        let mut state = EditorState::new();
+        state.buffers.add(Buffer::from_string("alpha", ""));
+        state.buffers.add(Buffer::from_string("alpine", ""));
+
+        state.start_minibuffer_prompt("Switch to buffer: ", "switch-to-buffer-complete");
+        state.handle_key(KeyEvent::char('a'));
+        state.handle_key(KeyEvent::char('l'));
+        state.handle_key(KeyEvent::new(Key::Tab, Modifiers::NONE));
+
+        // Longest common prefix is filled in
+        assert_eq!(state.minibuffer.content, "alp");
+
+        // A second TAB with no progress lists the candidates
+        state.handle_key(KeyEvent::new(Key::Tab, Modifiers::NONE));
+        let display = state.minibuffer.display();
+        assert!(display.contains("alpha"));
+        assert!(display.contains("alpine"));
+
+        // Typing dismisses the listing and narrows to one candidate
+        state.handle_key(KeyEvent::char('h'));
+        state.handle_key(KeyEvent::new(Key::Tab, Modifiers::NONE));
+        assert_eq!(state.minibuffer.content, "alpha");
+    }
+
+    #[test]
+    fn test_frame_title_tracks_buffer_and_modified_state() {
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("notes.txt", "hello");
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+
+        assert_eq!(state.frame_title(), "notes.txt — Enacs");
+
+        state.handle_key(KeyEvent::char('x'));
+        assert_eq!(state.frame_title(), "notes.txt* — Enacs");
+    }
+
+    #[test]
+    fn test_horizontal_scroll_follows_the_cursor() {
+        let long_line = "x".repeat(200);
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", &long_line);
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state.set_dimensions(80, 24);
+
+        // Past the right edge the window scrolls to keep the cursor one
+        // column short of it (wrap_width is 79 at 80 columns).
+        state.run_command("move-end-of-line");
+        assert_eq!(state.windows.current().unwrap().scroll_column, 200 + 2 - 79);
+
+        // Returning to the start scrolls all the way back.
+        state.run_command("move-beginning-of-line");
+        assert_eq!(state.windows.current().unwrap().scroll_column, 0);
+
+        // Visual-line mode wraps instead of scrolling.
+        state.visual_line_mode = true;
+        state.run_command("move-end-of-line");
+        assert_eq!(state.windows.current().unwrap().scroll_column, 0);
+    }
+
+    #[test]
+    fn test_mouse_to_position_is_tab_aware() {
+        use crate::core::position::CharOffset;
+
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", "a\tbc\nxy\n");
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        state.set_dimensions(80, 24);
+
+        let window_id = state.windows.current().unwrap().id;
+
+        // Column 5 lands on `c`: `a` is one cell, the tab expands to
+        // the next stop, `b` sits at column 4.
+        assert_eq!(
+            state.mouse_to_position(5, 0),
+            Some((window_id, CharOffset(3)))
+        );
+        assert_eq!(
+            state.mouse_to_position(1, 1),
+            Some((window_id, CharOffset(6)))
+        );
+
+        // Clicking past the buffer goes to the end; the minibuffer row
+        // belongs to no window.
+        assert_eq!(
+            state.mouse_to_position(0, 10),
+            Some((window_id, CharOffset(8)))
+        );
+        assert_eq!(state.mouse_to_position(0, 23), None);
+    }
+
+    #[test]
+    fn test_cn_selects_and_ret_accepts_candidate() {
+        use crate::keybinding::key::{Key, Modifiers};
+
+        let mut state = EditorState::new();
+        state.buffers.add(Buffer::from_string("alpha", ""));
+        state.buffers.add(Buffer::from_string("alpine", ""));
+
+        state.start_minibuffer_prompt("Switch to buffer: ", "switch-to-buffer-complete");
+        state.handle_key(KeyEvent::char('a'));
+        assert_eq!(state.minibuffer.candidates.len(), 2);
+
+        // C-n twice highlights the second candidate; the hint brackets it
+        state.handle_key(KeyEvent::ctrl('n'));
+        state.handle_key(KeyEvent::ctrl('n'));
+        assert_eq!(state.minibuffer.selected_candidate, Some(1));
+        assert!(state
+            .minibuffer
+            .completion_hint
+            .as_deref()
+            .unwrap()
+            .contains("[alpine]"));
+
+        // C-p moves back; another C-p wraps to the end
+        state.handle_key(KeyEvent::ctrl('p'));
+        assert_eq!(state.minibuffer.selected_candidate, Some(0));
+
+        state.handle_key(KeyEvent::ctrl('n'));
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+        assert_eq!(state.current_buffer().unwrap().name, "alpine");
+    }
+
+    #[test]
+    fn test_matching_bracket_pair_at_point() {
+        use crate::core::position::CharOffset;
+
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", "(foo [bar])");
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+
+        // On the opener
+        assert_eq!(
+            state.matching_bracket_pair(),
+            Some((CharOffset(0), CharOffset(10)))
+        );
+
+        // Just after the inner closer
+        state
+            .windows
+            .current_mut()
+            .unwrap()
+            .cursors
+            .primary
+            .position = CharOffset(10);
+        assert_eq!(
+            state.matching_bracket_pair(),
+            Some((CharOffset(5), CharOffset(9)))
+        );
+
+        // Unmatched bracket highlights nothing
+        let id = state.buffers.add(Buffer::from_string("bad", "(foo"));
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+        assert_eq!(state.matching_bracket_pair(), None);
+    }
+
+    #[test]
+    fn test_goto_line_prompts_without_prefix() {
+        use crate::core::position::CharOffset;
+        use crate::keybinding::key::{Key, Modifiers};
+
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", "one\ntwo\nthree\n");
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+
+        state.handle_key(KeyEvent::meta('g'));
+        state.handle_key(KeyEvent::char('g'));
+        assert!(state.minibuffer.is_active());
+        assert_eq!(state.minibuffer.prompt, "Goto line: ");
+
+        state.handle_key(KeyEvent::char('3'));
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(8)
+        );
+
+        // Non-numeric input reports instead of failing silently
+        state.handle_key(KeyEvent::meta('g'));
+        state.handle_key(KeyEvent::char('g'));
+        state.handle_key(KeyEvent::char('x'));
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+        assert_eq!(state.message.as_deref(), Some("Invalid line number"));
+    }
+
+    #[test]
+    fn test_which_key_tick_waits_for_the_delay() {
+        let mut state = EditorState::new();
+
+        state.handle_key(KeyEvent::meta('g'));
+        assert_eq!(state.message.as_deref(), Some("M-g-"));
+
+        // Immediately after the prefix the echo area is untouched
+        state.which_key_tick();
+        assert_eq!(state.message.as_deref(), Some("M-g-"));
+
+        // Pretend the prefix has been pending for a while
+        state.prefix_key_since =
+            std::time::Instant::now().checked_sub(std::time::Duration::from_secs(1));
+        state.which_key_tick();
+        assert_eq!(state.message.as_deref(), Some("M-g- g: goto-line"));
+    }
+
+    #[test]
+    fn test_apply_init_file_bind_lines() {
+        use crate::keybinding::keymap::KeyBinding;
+
+        let mut state = EditorState::new();
+        state.apply_init_file("# comment\n\nbind C-c C-k kill-line\nbind C-z bogus-command\n");
+
+        let cc_map = state.keymap.get_prefix(&KeyEvent::ctrl('c')).unwrap();
+        assert!(matches!(
+            cc_map.get(&KeyEvent::ctrl('k')),
+            Some(KeyBinding::Command("kill-line"))
+        ));
+        assert_eq!(
+            state.message.as_deref(),
+            Some(".enacs:4: Unknown command: bogus-command")
+        );
+    }
+
+    #[test]
+    fn test_motion_cannot_leave_narrowed_region() {
+        use crate::core::position::CharOffset;
+        use crate::keybinding::key::Modifiers;
+
+        let mut state = EditorState::new();
+        let buffer = Buffer::from_string("test", "one\ntwo\nthree\n");
+        let id = state.buffers.add(buffer);
+        state.buffers.set_current(id);
+        state.windows.set_current_buffer(id);
+
+        // Narrow to the "two" line
+        state.buffers.get_mut(id).unwrap().narrow = Some((CharOffset(4), CharOffset(7)));
+        state.windows.current_mut().unwrap().cursors.primary.position = CharOffset(7);
+
+        state.handle_key(KeyEvent::ctrl('f'));
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(7)
+        );
+
+        state.handle_key(KeyEvent::new(Key::Char('<'), Modifiers::META));
+        assert_eq!(
+            state.current_window().unwrap().cursors.primary.position,
+            CharOffset(4)
+        );
+    }
+
+    #[test]
+    fn test_typing_narrows_completions_live() {
+        let mut state = EditorState::new();
+        state.buffers.add(Buffer::from_string("alpha", ""));
+        state.buffers.add(Buffer::from_string("alpine", ""));
+        state.buffers.add(Buffer::from_string("beta", ""));
+
+        state.start_minibuffer_prompt("Switch to buffer: ", "switch-to-buffer-complete");
+        state.handle_key(KeyEvent::char('a'));
+
+        let hint = state.minibuffer.completion_hint.clone().unwrap();
+        assert!(hint.contains("alpha"));
+        assert!(hint.contains("alpine"));
+        assert!(!hint.contains("beta"));
+
+        for c in "lpha".chars() {
+            state.handle_key(KeyEvent::char(c));
+        }
+        let hint = state.minibuffer.completion_hint.clone().unwrap();
+        assert!(hint.contains("alpha"));
+        assert!(!hint.contains("alpine"));
+    }
+
+    #[test]
+    fn test_mx_tab_completes_command_names() {
+        use crate::keybinding::key::{Key, Modifiers};
+
+        let mut state = EditorState::new();
+
+        state.handle_key(KeyEvent::meta('x'));
+        assert!(state.minibuffer.is_active());
+
+        for c in "what-col".chars() {
+            state.handle_key(KeyEvent::char(c));
+        }
+        state.handle_key(KeyEvent::new(Key::Tab, Modifiers::NONE));
+        assert_eq!(state.minibuffer.content, "what-column");
+
+        state.handle_key(KeyEvent::new(Key::Enter, Modifiers::NONE));
+        assert!(state.message.as_deref().unwrap_or("").starts_with("Column"));
+    }
+
+    #[test]
+    fn test_mx_listing_shows_keybindings() {
+        use crate::keybinding::key::{Key, Modifiers};
+
+        let mut state = EditorState::new();
+
+        state.handle_key(KeyEvent::meta('x'));
+        for c in "forward-c".chars() {
+            state.handle_key(KeyEvent::char(c));
+        }
+        // First TAB fills "forward-char", second lists both candidates
+        // with their bindings.
+        state.handle_key(KeyEvent::new(Key::Tab, Modifiers::NONE));
+        state.handle_key(KeyEvent::new(Key::Tab, Modifiers::NONE));
+
+        let display = state.minibuffer.display();
+        assert!(display.contains("forward-char (C-f)"));
+        assert!(display.contains("forward-char-shift"));
+    }
+
+    #[test]
+    fn test_longest_common_prefix() {
+        use crate::state::minibuffer::longest_common_prefix;
+
+        let candidates = vec!["alpha".to_string(), "alpine".to_string()];
+        assert_eq!(longest_common_prefix(&candidates), "alp");
+        assert_eq!(longest_common_prefix(&[]), "");
+        assert_eq!(
+            longest_common_prefix(&["solo".to_string()]),
+            "solo"
+        );
+    }
 }
@@ -1,3 +1,84 @@
+use std::collections::HashMap;
+
+use super::EditorState;
+
+/// Entries kept per history ring; the oldest fall off first.
+const HISTORY_MAX: usize = 100;
+
+/// Produces completion candidates for the current minibuffer input.
+/// Keyed off the prompt's callback so each prompt kind can plug in its
+/// own source (paths, buffer names, ...).
+pub type CompletionFn = fn(&EditorState, &str) -> Vec<String>;
+
+/// The longest prefix shared by every candidate; what a single TAB
+/// press fills in.
+pub fn longest_common_prefix(candidates: &[String]) -> String {
+    let mut iter = candidates.iter();
+    let mut prefix = match iter.next() {
+        Some(first) => first.clone(),
+        None => return String::new(),
+    };
+
+    for candidate in iter {
+        let common = prefix
+            .chars()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let byte = prefix
+            .char_indices()
+            .nth(common)
+            .map(|(b, _)| b)
+            .unwrap_or(prefix.len());
+        prefix.truncate(byte);
+    }
+    prefix
+}
+
+/// Path completion for file prompts: lists the entries of the directory
+/// part of `input` that extend it.
+pub fn complete_path(_state: &EditorState, input: &str) -> Vec<String> {
+    let (dir, partial) = match input.rfind('/') {
+        Some(i) => (&input[..=i], &input[i + 1..]),
+        None => ("", input),
+    };
+
+    let read_dir = std::fs::read_dir(if dir.is_empty() { "." } else { dir });
+    let mut candidates = Vec::new();
+    if let Ok(entries) = read_dir {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with(partial) {
+                let suffix = if entry.path().is_dir() { "/" } else { "" };
+                candidates.push(format!("{}{}{}", dir, name, suffix));
+            }
+        }
+    }
+    candidates
+}
+
+/// Buffer-name completion for switch/kill prompts.
+pub fn complete_buffer_name(state: &EditorState, input: &str) -> Vec<String> {
+    state
+        .buffers
+        .names()
+        .into_iter()
+        .filter(|name| name.starts_with(input))
+        .map(String::from)
+        .collect()
+}
+
+/// Command-name completion for the `M-x` prompt.
+pub fn complete_command(state: &EditorState, input: &str) -> Vec<String> {
+    state
+        .command_registry
+        .sorted_names()
+        .into_iter()
+        .filter(|name| name.starts_with(input))
+        .map(String::from)
+        .collect()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MinibufferState {
     Inactive,
@@ -12,8 +93,18 @@ pub struct Minibuffer {
     pub content: String,
     pub cursor_pos: usize,
     pub callback: Option<&'static str>,
-    pub history: Vec<String>,
+    /// One history ring per prompt kind, keyed by callback, so `M-p`
+    /// at a file prompt cycles file names rather than `M-x` commands.
+    pub histories: HashMap<String, Vec<String>>,
     pub history_index: Option<usize>,
+    pub completion_fn: Option<CompletionFn>,
+    /// Candidate listing appended to the display after a second TAB.
+    pub completion_hint: Option<String>,
+    /// The current candidate set, kept in sync with the input so
+    /// `C-n`/`C-p` can move a selection through it.
+    pub candidates: Vec<String>,
+    /// Index into `candidates` of the highlighted pick, if any.
+    pub selected_candidate: Option<usize>,
 }
 
 impl Default for Minibuffer {
@@ -30,8 +121,12 @@ impl Minibuffer {
             content: String::new(),
             cursor_pos: 0,
             callback: None,
-            history: Vec::new(),
+            histories: HashMap::new(),
             history_index: None,
+            completion_fn: None,
+            completion_hint: None,
+            candidates: Vec::new(),
+            selected_candidate: None,
         }
     }
 
@@ -42,6 +137,10 @@ impl Minibuffer {
         self.cursor_pos = 0;
         self.callback = Some(callback);
         self.history_index = None;
+        self.completion_fn = None;
+        self.completion_hint = None;
+        self.candidates.clear();
+        self.selected_candidate = None;
     }
 
     pub fn insert_char(&mut self, c: char) {
@@ -84,33 +183,58 @@ impl Minibuffer {
         self.cursor_pos = self.content.len();
     }
 
+    /// The ring belonging to the active prompt's callback.
+    fn current_history(&self) -> &[String] {
+        self.callback
+            .and_then(|cb| self.histories.get(cb))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Appends to a ring, skipping consecutive duplicates and dropping
+    /// the oldest entry past the cap.
+    fn push_history(&mut self, callback: &str, content: &str) {
+        let ring = self.histories.entry(callback.to_string()).or_default();
+        if ring.last().map(String::as_str) == Some(content) {
+            return;
+        }
+        ring.push(content.to_string());
+        if ring.len() > HISTORY_MAX {
+            ring.remove(0);
+        }
+    }
+
     pub fn history_prev(&mut self) {
-        if self.history.is_empty() {
+        let history = self.current_history();
+        if history.is_empty() {
             return;
         }
 
         let new_index = match self.history_index {
-            None => self.history.len() - 1,
+            None => history.len() - 1,
             Some(0) => return,
             Some(i) => i - 1,
         };
 
+        let content = history[new_index].clone();
         self.history_index = Some(new_index);
-        self.content = self.history[new_index].clone();
+        self.content = content;
         self.cursor_pos = self.content.len();
     }
 
     pub fn history_next(&mut self) {
+        let history = self.current_history();
         match self.history_index {
             None => (),
-            Some(i) if i >= self.history.len() - 1 => {
+            Some(i) if i >= history.len() - 1 => {
                 self.history_index = None;
                 self.content.clear();
                 self.cursor_pos = 0;
             }
             Some(i) => {
+                let content = history[i + 1].clone();
                 self.history_index = Some(i + 1);
-                self.content = self.history[i + 1].clone();
+                self.content = content;
                 self.cursor_pos = self.content.len();
             }
         }
@@ -124,8 +248,8 @@ impl Minibuffer {
         let content = std::mem::take(&mut self.content);
         let callback = self.callback.take();
 
-        if !content.is_empty() {
-            self.history.push(content.clone());
+        if let (false, Some(cb)) = (content.is_empty(), callback) {
+            self.push_history(cb, &content);
         }
 
         self.clear();
@@ -133,6 +257,41 @@ impl Minibuffer {
         callback.map(|cb| (content, cb))
     }
 
+    /// Serializes every history ring as `callback<TAB>entry` lines for
+    /// the history file. Callbacks are sorted so the output is stable;
+    /// entries that couldn't round-trip through the format are skipped.
+    pub fn serialize_history(&self) -> String {
+        let mut callbacks: Vec<&String> = self.histories.keys().collect();
+        callbacks.sort();
+
+        let mut out = String::new();
+        for cb in callbacks {
+            for entry in &self.histories[cb] {
+                if entry.contains('\n') || entry.contains('\t') {
+                    continue;
+                }
+                out.push_str(cb);
+                out.push('\t');
+                out.push_str(entry);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Restores rings from [`Self::serialize_history`] output, keeping
+    /// the dedup and cap rules; malformed lines are skipped.
+    pub fn load_history(&mut self, contents: &str) {
+        for line in contents.lines() {
+            if let Some((cb, entry)) = line
+                .split_once('\t')
+                .filter(|(cb, entry)| !cb.is_empty() && !entry.is_empty())
+            {
+                self.push_history(cb, entry);
+            }
+        }
+    }
+
     pub fn clear(&mut self) {
         self.state = MinibufferState::Inactive;
         self.prompt.clear();
@@ -140,6 +299,10 @@ impl Minibuffer {
         self.cursor_pos = 0;
         self.callback = None;
         self.history_index = None;
+        self.completion_fn = None;
+        self.completion_hint = None;
+        self.candidates.clear();
+        self.selected_candidate = None;
     }
 
     pub fn is_active(&self) -> bool {
@@ -147,7 +310,10 @@ impl Minibuffer {
     }
 
     pub fn display(&self) -> String {
-        format!("{}{}", self.prompt, self.content)
+        match &self.completion_hint {
+            Some(hint) => format!("{}{} {{{}}}", self.prompt, self.content, hint),
+            None => format!("{}{}", self.prompt, self.content),
+        }
     }
 
     pub fn cursor_screen_pos(&self) -> usize {
@@ -189,6 +355,43 @@ mod tests {
         assert_eq!(mb.content, "ell");
     }
 
+    #[test]
+    fn test_history_is_separate_per_prompt_kind() {
+        let mut mb = Minibuffer::new();
+
+        mb.start_prompt("Find file: ", "find-file");
+        mb.content = "notes.txt".to_string();
+        mb.submit();
+
+        mb.start_prompt("M-x ", "execute-command");
+        mb.content = "undo".to_string();
+        mb.submit();
+
+        // M-p at a file prompt recalls file names, not commands.
+        mb.start_prompt("Find file: ", "find-file");
+        mb.history_prev();
+        assert_eq!(mb.content, "notes.txt");
+    }
+
+    #[test]
+    fn test_history_round_trips_through_the_file_format() {
+        let mut mb = Minibuffer::new();
+        for content in ["a.txt", "b.txt", "b.txt", "c.txt"] {
+            mb.start_prompt("Find file: ", "find-file");
+            mb.content = content.to_string();
+            mb.submit();
+        }
+
+        let mut reloaded = Minibuffer::new();
+        reloaded.load_history(&mb.serialize_history());
+
+        // Consecutive duplicates collapse into one entry.
+        assert_eq!(
+            reloaded.histories["find-file"],
+            vec!["a.txt", "b.txt", "c.txt"]
+        );
+    }
+
     #[test]
     fn test_minibuffer_submit() {
         let mut mb = Minibuffer::new();
@@ -1,9 +1,14 @@
 pub mod buffer_mgr;
+pub mod builder;
+pub mod completion;
 pub mod editor;
 pub mod minibuffer;
+pub mod registers;
 pub mod window_mgr;
 
 pub use buffer_mgr::BufferManager;
+pub use builder::EditorBuilder;
 pub use editor::EditorState;
 pub use minibuffer::Minibuffer;
+pub use registers::Register;
 pub use window_mgr::{Window, WindowId, WindowManager};
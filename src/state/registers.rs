@@ -0,0 +1,14 @@
+use crate::core::position::CharOffset;
+use crate::core::BufferId;
+
+/// A value stored in a named register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Register {
+    /// A saved location: a buffer and a position within it.
+    Point {
+        buffer_id: BufferId,
+        position: CharOffset,
+    },
+    /// A saved piece of text.
+    Text(String),
+}
@@ -31,6 +31,20 @@ pub struct Window {
     pub height: u16,
     pub scroll_line: usize,
     pub scroll_column: usize,
+    /// When set, the frontend draws a line-number gutter in this window.
+    pub display_line_numbers: Option<LineNumberStyle>,
+    /// Text-scale steps for this window only; each step scales the font
+    /// by 1.2 like Emacs `text-scale-mode`. Zero is the default size.
+    pub text_scale: i32,
+    /// When set, the region between point and mark is treated as a
+    /// rectangle (`rectangle-mark-mode`) by highlighting and kills.
+    pub rectangle_mark: bool,
+    /// Step of the recenter-top-bottom cycle this window is on:
+    /// 0 center, 1 top, 2 bottom.
+    pub recenter_cycle: u8,
+    /// Step of the move-to-window-line cycle: 0 top, 1 bottom,
+    /// 2 middle.
+    pub window_line_cycle: u8,
 }
 
 impl Window {
@@ -45,6 +59,11 @@ impl Window {
             height: 24,
             scroll_line: 0,
             scroll_column: 0,
+            display_line_numbers: None,
+            text_scale: 0,
+            rectangle_mark: false,
+            recenter_cycle: 0,
+            window_line_cycle: 0,
         }
     }
 
@@ -59,8 +78,229 @@ impl Window {
             height,
             scroll_line: 0,
             scroll_column: 0,
+            display_line_numbers: None,
+            text_scale: 0,
+            rectangle_mark: false,
+            recenter_cycle: 0,
+            window_line_cycle: 0,
         }
     }
+
+    /// The multiplier the frontend applies to this window's cell
+    /// dimensions: 1.2 raised to the text-scale step count.
+    pub fn text_scale_factor(&self) -> f32 {
+        1.2f32.powi(self.text_scale)
+    }
+
+    /// Columns available for buffer text under visual-line wrapping:
+    /// the window width minus the line-number gutter and the column
+    /// reserved for the continuation indicator. Shared by the terminal
+    /// renderer and the visual-line motions so they agree on layout.
+    pub fn wrap_width(&self, total_lines: usize) -> usize {
+        let gutter = if self.display_line_numbers.is_some() {
+            LineNumberStyle::gutter_width(total_lines)
+        } else {
+            0
+        };
+        (self.width as usize).saturating_sub(gutter + 1).max(1)
+    }
+
+    /// The rectangle spanned by the primary cursor's region while
+    /// `rectangle-mark-mode` is active, as (first line, last line, left
+    /// column, right column). Columns are half-open like regions.
+    pub fn rectangle_region(&self, text: &ropey::Rope) -> Option<(usize, usize, usize, usize)> {
+        use crate::core::rope_ext::RopeExt;
+
+        if !self.rectangle_mark {
+            return None;
+        }
+        let (start, end) = self.cursors.primary.region()?;
+        let a = text.char_to_position(start);
+        let b = text.char_to_position(end);
+        Some((
+            a.line.min(b.line),
+            a.line.max(b.line),
+            a.column.min(b.column),
+            a.column.max(b.column),
+        ))
+    }
+}
+
+/// Which numbers the gutter shows, matching Emacs
+/// `display-line-numbers-type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineNumberStyle {
+    Absolute,
+    /// The current line shows its absolute number; others show their
+    /// distance from it.
+    Relative,
+    /// Like `Relative`, but counts screen lines. Identical to it until
+    /// the frontends wrap long lines.
+    Visual,
+}
+
+impl LineNumberStyle {
+    /// The number displayed for `line` when point is on `current`.
+    pub fn label(&self, line: usize, current: usize) -> usize {
+        match self {
+            LineNumberStyle::Absolute => line + 1,
+            LineNumberStyle::Relative | LineNumberStyle::Visual => {
+                if line == current {
+                    line + 1
+                } else {
+                    line.abs_diff(current)
+                }
+            }
+        }
+    }
+
+    /// Columns taken by the gutter: the line count's digits plus a
+    /// trailing space.
+    pub fn gutter_width(total_lines: usize) -> usize {
+        let mut digits = 1;
+        let mut n = total_lines;
+        while n >= 10 {
+            digits += 1;
+            n /= 10;
+        }
+        digits + 1
+    }
+}
+
+/// How a split divides its rectangle among its children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitDirection {
+    /// Children are stacked top to bottom (`split-window-below`).
+    Vertical,
+    /// Children sit side by side (`split-window-right`).
+    Horizontal,
+}
+
+/// The split structure of the frame. `relayout` walks this tree to
+/// divide the available rectangle, so vertical and horizontal splits
+/// can coexist and survive resizes.
+#[derive(Debug, Clone)]
+enum LayoutNode {
+    Leaf(WindowId),
+    Split {
+        direction: SplitDirection,
+        children: Vec<LayoutNode>,
+    },
+}
+
+impl LayoutNode {
+    fn contains(&self, id: WindowId) -> bool {
+        match self {
+            LayoutNode::Leaf(w) => *w == id,
+            LayoutNode::Split { children, .. } => children.iter().any(|c| c.contains(id)),
+        }
+    }
+
+    /// Inserts `new_id` next to `target`, reusing the enclosing split
+    /// when it runs in the same direction and nesting a new one
+    /// otherwise. Returns true once the insertion happened.
+    fn insert_after(&mut self, target: WindowId, new_id: WindowId, direction: SplitDirection) -> bool {
+        if let LayoutNode::Split {
+            direction: dir,
+            children,
+        } = self
+        {
+            for i in 0..children.len() {
+                if let LayoutNode::Leaf(w) = children[i] {
+                    if w == target {
+                        if *dir == direction || children.len() == 1 {
+                            *dir = if children.len() == 1 { direction } else { *dir };
+                            children.insert(i + 1, LayoutNode::Leaf(new_id));
+                        } else {
+                            children[i] = LayoutNode::Split {
+                                direction,
+                                children: vec![LayoutNode::Leaf(target), LayoutNode::Leaf(new_id)],
+                            };
+                        }
+                        return true;
+                    }
+                } else if children[i].insert_after(target, new_id, direction) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Removes the leaf for `id`, pruning empty splits and collapsing
+    /// single-child splits into their child.
+    fn remove(&mut self, id: WindowId) {
+        if let LayoutNode::Split { children, .. } = self {
+            children.retain(|c| !matches!(c, LayoutNode::Leaf(w) if *w == id));
+            for child in children.iter_mut() {
+                child.remove(id);
+            }
+            children.retain(|c| !matches!(c, LayoutNode::Split { children, .. } if children.is_empty()));
+            for child in children.iter_mut() {
+                if let LayoutNode::Split {
+                    children: inner, ..
+                } = child
+                {
+                    if inner.len() == 1 {
+                        *child = inner.remove(0);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Assigns `rect` to this subtree, splitting it among children
+    /// according to the stored direction.
+    fn assign(&self, rect: (u16, u16, u16, u16), windows: &mut [Window]) {
+        let (x, y, width, height) = rect;
+        match self {
+            LayoutNode::Leaf(id) => {
+                if let Some(window) = windows.iter_mut().find(|w| w.id == *id) {
+                    window.x = x;
+                    window.y = y;
+                    window.width = width;
+                    window.height = height;
+                }
+            }
+            LayoutNode::Split {
+                direction,
+                children,
+            } => {
+                if children.is_empty() {
+                    return;
+                }
+                let n = children.len() as u16;
+                let total = match direction {
+                    SplitDirection::Vertical => height,
+                    SplitDirection::Horizontal => width,
+                };
+                let base = total / n;
+                let remainder = total % n;
+
+                let mut offset = 0u16;
+                for (i, child) in children.iter().enumerate() {
+                    let extra = if (i as u16) < remainder { 1 } else { 0 };
+                    let size = base + extra;
+                    let child_rect = match direction {
+                        SplitDirection::Vertical => (x, y + offset, width, size),
+                        SplitDirection::Horizontal => (x + offset, y, size, height),
+                    };
+                    child.assign(child_rect, windows);
+                    offset += size;
+                }
+            }
+        }
+    }
+}
+
+/// A saved window arrangement for winner-mode: the window list and the
+/// split tree, cloned wholesale so restoring brings back buffers,
+/// cursors, and scroll positions.
+#[derive(Debug, Clone)]
+pub struct WindowConfiguration {
+    windows: Vec<Window>,
+    current: usize,
+    layout: LayoutNode,
 }
 
 #[derive(Debug)]
@@ -69,6 +309,7 @@ pub struct WindowManager {
     current: usize,
     total_width: u16,
     total_height: u16,
+    layout: LayoutNode,
 }
 
 impl Default for WindowManager {
@@ -78,6 +319,10 @@ impl Default for WindowManager {
             current: 0,
             total_width: 80,
             total_height: 24,
+            layout: LayoutNode::Split {
+                direction: SplitDirection::Vertical,
+                children: Vec::new(),
+            },
         }
     }
 }
@@ -89,10 +334,9 @@ impl WindowManager {
 
     pub fn with_dimensions(width: u16, height: u16) -> Self {
         Self {
-            windows: Vec::new(),
-            current: 0,
             total_width: width,
             total_height: height,
+            ..Self::default()
         }
     }
 
@@ -112,6 +356,9 @@ impl WindowManager {
         );
         let id = window.id;
         self.windows.push(window);
+        if let LayoutNode::Split { children, .. } = &mut self.layout {
+            children.push(LayoutNode::Leaf(id));
+        }
         self.relayout();
         id
     }
@@ -136,6 +383,13 @@ impl WindowManager {
         }
     }
 
+    /// Makes the window with `id` current, if it exists.
+    pub fn focus(&mut self, id: WindowId) {
+        if let Some(idx) = self.windows.iter().position(|w| w.id == id) {
+            self.current = idx;
+        }
+    }
+
     pub fn cycle_next(&mut self) {
         if !self.windows.is_empty() {
             self.current = (self.current + 1) % self.windows.len();
@@ -153,47 +407,34 @@ impl WindowManager {
     }
 
     pub fn split_vertical(&mut self) {
-        if let Some(current) = self.windows.get(self.current).cloned() {
-            let half_height = current.height / 2;
-            let new_window = Window::with_dimensions(
-                current.buffer_id,
-                current.x,
-                current.y + half_height,
-                current.width,
-                current.height - half_height,
-            );
-
-            if let Some(window) = self.windows.get_mut(self.current) {
-                window.height = half_height;
-            }
-
-            self.windows.insert(self.current + 1, new_window);
-            self.relayout();
-        }
+        self.split(SplitDirection::Vertical);
     }
 
     pub fn split_horizontal(&mut self) {
+        self.split(SplitDirection::Horizontal);
+    }
+
+    fn split(&mut self, direction: SplitDirection) {
         if let Some(current) = self.windows.get(self.current).cloned() {
-            let half_width = current.width / 2;
             let new_window = Window::with_dimensions(
                 current.buffer_id,
-                current.x + half_width,
+                current.x,
                 current.y,
-                current.width - half_width,
+                current.width,
                 current.height,
             );
-
-            if let Some(window) = self.windows.get_mut(self.current) {
-                window.width = half_width;
-            }
+            let new_id = new_window.id;
 
             self.windows.insert(self.current + 1, new_window);
+            self.layout.insert_after(current.id, new_id, direction);
+            self.relayout();
         }
     }
 
     pub fn delete_current(&mut self) {
         if self.windows.len() > 1 {
-            self.windows.remove(self.current);
+            let removed = self.windows.remove(self.current);
+            self.layout.remove(removed.id);
             if self.current >= self.windows.len() {
                 self.current = self.windows.len() - 1;
             }
@@ -203,9 +444,14 @@ impl WindowManager {
 
     pub fn delete_others(&mut self) {
         if let Some(current) = self.windows.get(self.current).cloned() {
+            let id = current.id;
             self.windows.clear();
             self.windows.push(current);
             self.current = 0;
+            self.layout = LayoutNode::Split {
+                direction: SplitDirection::Vertical,
+                children: vec![LayoutNode::Leaf(id)],
+            };
             self.relayout();
         }
     }
@@ -222,25 +468,91 @@ impl WindowManager {
         self.windows.iter_mut()
     }
 
+    /// Chains every window showing the current window's buffer into one
+    /// continuous view (follow-mode): ordered left to right, each
+    /// window's scroll continues where the previous one leaves off.
+    /// The current window keeps its scroll; the others align to it.
+    pub fn sync_follow(&mut self) {
+        let Some(current) = self.current() else {
+            return;
+        };
+        let buffer_id = current.buffer_id;
+        let current_id = current.id;
+
+        let mut chain: Vec<usize> = (0..self.windows.len())
+            .filter(|&i| self.windows[i].buffer_id == buffer_id)
+            .collect();
+        if chain.len() < 2 {
+            return;
+        }
+        chain.sort_by_key(|&i| (self.windows[i].x, self.windows[i].y));
+
+        // Text rows per window (the bottom row is the modeline) and
+        // each window's line offset from the start of the chain.
+        let rows: Vec<usize> = chain
+            .iter()
+            .map(|&i| self.windows[i].height.saturating_sub(1).max(1) as usize)
+            .collect();
+        let offsets: Vec<usize> = rows
+            .iter()
+            .scan(0, |acc, &r| {
+                let offset = *acc;
+                *acc += r;
+                Some(offset)
+            })
+            .collect();
+
+        let pos = chain
+            .iter()
+            .position(|&i| self.windows[i].id == current_id)
+            .unwrap_or(0);
+        let base = self.windows[chain[pos]].scroll_line.saturating_sub(offsets[pos]);
+
+        for (j, &i) in chain.iter().enumerate() {
+            self.windows[i].scroll_line = base + offsets[j];
+        }
+    }
+
+    /// Captures the current arrangement so winner-mode can restore it.
+    pub fn save_configuration(&self) -> WindowConfiguration {
+        WindowConfiguration {
+            windows: self.windows.clone(),
+            current: self.current,
+            layout: self.layout.clone(),
+        }
+    }
+
+    /// Replaces the arrangement with a saved one, re-fitting it to the
+    /// current frame dimensions.
+    pub fn restore_configuration(&mut self, config: &WindowConfiguration) {
+        self.windows = config.windows.clone();
+        self.current = config.current.min(self.windows.len().saturating_sub(1));
+        self.layout = config.layout.clone();
+        self.relayout();
+    }
+
     fn relayout(&mut self) {
         if self.windows.is_empty() {
             return;
         }
 
-        let usable_height = self.total_height.saturating_sub(1);
-        let n = self.windows.len() as u16;
-        let base_height = usable_height / n;
-        let remainder = usable_height % n;
-
-        let mut y = 0u16;
-        for (i, window) in self.windows.iter_mut().enumerate() {
-            let extra = if (i as u16) < remainder { 1 } else { 0 };
-            window.x = 0;
-            window.y = y;
-            window.width = self.total_width;
-            window.height = base_height + extra;
-            y += window.height;
+        // Windows created before the layout tree knew about them (e.g.
+        // via direct construction in tests) still need a leaf.
+        let missing: Vec<WindowId> = self
+            .windows
+            .iter()
+            .map(|w| w.id)
+            .filter(|id| !self.layout.contains(*id))
+            .collect();
+        if let LayoutNode::Split { children, .. } = &mut self.layout {
+            for id in missing {
+                children.push(LayoutNode::Leaf(id));
+            }
         }
+
+        let usable_height = self.total_height.saturating_sub(1);
+        self.layout
+            .assign((0, 0, self.total_width, usable_height), &mut self.windows);
     }
 }
 
@@ -248,6 +560,50 @@ impl WindowManager {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_line_number_labels() {
+        assert_eq!(LineNumberStyle::Absolute.label(4, 10), 5);
+        assert_eq!(LineNumberStyle::Relative.label(10, 10), 11);
+        assert_eq!(LineNumberStyle::Relative.label(7, 10), 3);
+        assert_eq!(LineNumberStyle::Relative.label(13, 10), 3);
+        assert_eq!(LineNumberStyle::Visual.label(7, 10), 3);
+    }
+
+    #[test]
+    fn test_gutter_width_grows_with_digits() {
+        assert_eq!(LineNumberStyle::gutter_width(9), 2);
+        assert_eq!(LineNumberStyle::gutter_width(10), 3);
+        assert_eq!(LineNumberStyle::gutter_width(4200), 5);
+    }
+
+    #[test]
+    fn test_text_scale_factor_cell_dimensions() {
+        let mut window = Window::new(BufferId(1));
+        assert_eq!(window.text_scale_factor(), 1.0);
+
+        window.text_scale = 2;
+        let (cell_w, cell_h) = (
+            10.0 * window.text_scale_factor(),
+            20.0 * window.text_scale_factor(),
+        );
+        assert!((cell_w - 14.4).abs() < 1e-4);
+        assert!((cell_h - 28.8).abs() < 1e-4);
+
+        window.text_scale = -1;
+        assert!((10.0 * window.text_scale_factor() - 10.0 / 1.2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_wrap_width_reserves_indicator_and_gutter() {
+        let mut window = Window::new(BufferId(1));
+        window.width = 80;
+        assert_eq!(window.wrap_width(100), 79);
+
+        window.display_line_numbers = Some(LineNumberStyle::Absolute);
+        // Three digits plus a trailing space, plus the `\` column.
+        assert_eq!(window.wrap_width(100), 75);
+    }
+
     #[test]
     fn test_window_manager_add() {
         let mut mgr = WindowManager::with_dimensions(80, 24);
@@ -268,6 +624,69 @@ mod tests {
         assert_eq!(mgr.count(), 2);
     }
 
+    #[test]
+    fn test_window_split_horizontal_side_by_side() {
+        let mut mgr = WindowManager::with_dimensions(80, 24);
+        mgr.add(BufferId(1));
+        mgr.split_horizontal();
+
+        assert_eq!(mgr.count(), 2);
+        let windows: Vec<&Window> = mgr.iter().collect();
+        assert_eq!(windows[0].y, windows[1].y);
+        assert_eq!(windows[0].width, 40);
+        assert_eq!(windows[1].width, 40);
+        assert_eq!(windows[1].x, 40);
+        assert_eq!(windows[0].height, 23);
+        assert_eq!(windows[1].height, 23);
+    }
+
+    #[test]
+    fn test_horizontal_split_survives_resize() {
+        let mut mgr = WindowManager::with_dimensions(80, 24);
+        mgr.add(BufferId(1));
+        mgr.split_horizontal();
+        mgr.set_dimensions(100, 30);
+
+        let windows: Vec<&Window> = mgr.iter().collect();
+        assert_eq!(windows[0].y, 0);
+        assert_eq!(windows[1].y, 0);
+        assert_eq!(windows[0].width, 50);
+        assert_eq!(windows[1].width, 50);
+        assert_eq!(windows[0].height, 29);
+        assert_eq!(windows[1].height, 29);
+    }
+
+    #[test]
+    fn test_mixed_splits_coexist() {
+        let mut mgr = WindowManager::with_dimensions(80, 25);
+        mgr.add(BufferId(1));
+        mgr.split_horizontal();
+        // Split the left window below; the right column keeps its size.
+        mgr.split_vertical();
+
+        let windows: Vec<&Window> = mgr.iter().collect();
+        assert_eq!(windows[0].width, 40);
+        assert_eq!(windows[1].width, 40);
+        assert_eq!(windows[0].x, windows[1].x);
+        assert_eq!(windows[0].height, 12);
+        assert_eq!(windows[1].height, 12);
+        assert_eq!(windows[2].x, 40);
+        assert_eq!(windows[2].height, 24);
+    }
+
+    #[test]
+    fn test_delete_window_collapses_split() {
+        let mut mgr = WindowManager::with_dimensions(80, 24);
+        mgr.add(BufferId(1));
+        mgr.split_horizontal();
+        mgr.delete_current();
+
+        assert_eq!(mgr.count(), 1);
+        let window = mgr.current().unwrap();
+        assert_eq!(window.width, 80);
+        assert_eq!(window.height, 23);
+    }
+
     #[test]
     fn test_window_cycle() {
         let mut mgr = WindowManager::with_dimensions(80, 24);